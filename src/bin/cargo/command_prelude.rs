@@ -11,7 +11,7 @@ use cargo::util::important_paths::find_root_manifest_for_wd;
 
 pub use clap::{AppSettings, Arg, ArgMatches};
 pub use cargo::{CliError, CliResult, Config};
-pub use cargo::core::compiler::CompileMode;
+pub use cargo::core::compiler::{CompileMode, ProfileKind};
 
 pub type App = clap::App<'static, 'static>;
 
@@ -106,6 +106,10 @@ pub trait AppExt: Sized {
         self._arg(opt("release", release))
     }
 
+    fn arg_profile(self, profile: &'static str) -> Self {
+        self._arg(opt("profile", profile).value_name("PROFILE-NAME"))
+    }
+
     fn arg_doc(self, doc: &'static str) -> Self {
         self._arg(opt("doc", doc))
     }
@@ -136,6 +140,13 @@ pub trait AppExt: Sized {
         self._arg(opt("build-plan", "Output the build plan in JSON"))
     }
 
+    fn arg_timings(self) -> Self {
+        self._arg(opt(
+            "timings",
+            "Output a report of how long each crate took to build to target/cargo-timings",
+        ))
+    }
+
     fn arg_new_opts(self) -> Self {
         self._arg(
             opt(
@@ -252,6 +263,21 @@ pub trait ArgMatchesExt {
         self._value_of("target").map(|s| s.to_string())
     }
 
+    /// The profile selected with `--profile`, which can't be combined with
+    /// `--release` or `--debug`.
+    fn profile_kind(&self) -> CargoResult<Option<ProfileKind>> {
+        let name = match self._value_of("profile") {
+            Some(name) => name,
+            None => return Ok(None),
+        };
+        for flag in ["release", "debug"].iter() {
+            if self._is_present(flag) {
+                bail!("conflicting usage of --profile and --{}", flag);
+            }
+        }
+        Ok(Some(ProfileKind::new(name)))
+    }
+
     fn compile_options<'a>(
         &self,
         config: &'a Config,
@@ -280,8 +306,11 @@ pub trait ArgMatchesExt {
 
         let mut build_config = BuildConfig::new(config, self.jobs()?, &self.target(), mode)?;
         build_config.message_format = message_format;
-        build_config.release = self._is_present("release");
+        if self._is_present("release") {
+            build_config.profile = ProfileKind::Release;
+        }
         build_config.build_plan = self._is_present("build-plan");
+        build_config.timings = self._is_present("timings");
         if build_config.build_plan && !config.cli_unstable().unstable_options {
             Err(format_err!(
                 "`--build-plan` flag is unstable, pass `-Z unstable-options` to enable it"
@@ -0,0 +1,131 @@
+use command_prelude::*;
+
+use cargo::core::dependency::Kind;
+use cargo::core::GitReference;
+use cargo::ops::{self, AddOptions, AddSource, Packages};
+
+pub fn cli() -> App {
+    subcommand("add")
+        .about("Add dependencies to a Cargo.toml manifest file")
+        .arg(Arg::with_name("crate").multiple(true).help(
+            "Crates to add, optionally with a version requirement (`name@req`)",
+        ))
+        .arg(opt("dev", "Add as a development dependency").short("D"))
+        .arg(opt("build", "Add as a build dependency").short("B"))
+        .arg_target_triple("Add as a dependency for the given target triple or `cfg` expression")
+        .arg(
+            opt("features", "Space-separated list of features to enable on the dependency")
+                .value_name("FEATURES"),
+        )
+        .arg(opt(
+            "no-default-features",
+            "Disable the default features of the dependency",
+        ))
+        .arg(opt("optional", "Mark the dependency as optional"))
+        .arg(
+            opt("rename", "Rename the dependency in the manifest")
+                .value_name("NAME"),
+        )
+        .arg(opt("path", "Filesystem path to the local crate to add").value_name("PATH"))
+        .arg(opt("git", "Git repository location of the crate to add").value_name("URL"))
+        .arg(
+            opt("branch", "Branch to use when adding from git")
+                .value_name("BRANCH")
+                .requires("git"),
+        )
+        .arg(
+            opt("tag", "Tag to use when adding from git")
+                .value_name("TAG")
+                .requires("git"),
+        )
+        .arg(
+            opt("rev", "Specific commit to use when adding from git")
+                .value_name("SHA")
+                .requires("git"),
+        )
+        .arg(opt("registry", "Registry to use").value_name("REGISTRY"))
+        .arg_package("Package in the workspace to add dependencies to")
+        .arg_manifest_path()
+        .after_help(
+            "\
+This command adds dependencies to the `Cargo.toml` manifest of the current
+package, or of the package selected with `--package`. The rest of the manifest,
+including comments and formatting, is left untouched.
+
+Each crate may be followed by a version requirement, as in `serde@1.0`. If no
+requirement is given, the latest version in the registry is used. Dependencies
+already present in the chosen table are updated in place.
+",
+        )
+}
+
+pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
+    let ws = args.workspace(config)?;
+    let manifest_path = match args.value_of("package") {
+        Some(name) => Packages::Packages(vec![name.to_string()]).get_packages(&ws)?[0]
+            .manifest_path()
+            .to_path_buf(),
+        None => ws.current()?.manifest_path().to_path_buf(),
+    };
+
+    let kind = match (args.is_present("dev"), args.is_present("build")) {
+        (false, false) => Kind::Normal,
+        (true, false) => Kind::Development,
+        (false, true) => Kind::Build,
+        (true, true) => {
+            return Err(format_err!("the `--dev` and `--build` flags cannot be used together").into())
+        }
+    };
+
+    let source = if let Some(path) = args.value_of_path("path", config) {
+        if args.is_present("git") || args.is_present("registry") {
+            return Err(format_err!(
+                "`--path` cannot be used together with `--git` or `--registry`"
+            ).into());
+        }
+        AddSource::Path(path)
+    } else if let Some(git) = args.value_of("git") {
+        if args.is_present("registry") {
+            return Err(format_err!("`--git` cannot be used together with `--registry`").into());
+        }
+        let reference = match (
+            args.value_of("branch"),
+            args.value_of("tag"),
+            args.value_of("rev"),
+        ) {
+            (None, None, None) => None,
+            (Some(b), None, None) => Some(GitReference::Branch(b.to_string())),
+            (None, Some(t), None) => Some(GitReference::Tag(t.to_string())),
+            (None, None, Some(r)) => Some(GitReference::Rev(r.to_string())),
+            _ => {
+                return Err(format_err!(
+                    "only one of `--branch`, `--tag` or `--rev` can be specified"
+                ).into())
+            }
+        };
+        AddSource::Git(git.to_string(), reference)
+    } else {
+        AddSource::Registry(args.registry(config)?)
+    };
+
+    let features = values(args, "features")
+        .iter()
+        .flat_map(|s| s.split(|c: char| c == ' ' || c == ','))
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+        .collect();
+
+    let opts = AddOptions {
+        config,
+        crates: values(args, "crate"),
+        source,
+        kind,
+        target: args.target(),
+        features,
+        no_default_features: args.is_present("no-default-features"),
+        optional: args.is_present("optional"),
+        rename: args.value_of("rename").map(|s| s.to_string()),
+    };
+    ops::add(&manifest_path, &opts)?;
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use command_prelude::*;
+
+use cargo::core::compiler::MessageFormat;
+use cargo::ops::{self, AdvisoriesOptions};
+
+pub fn cli() -> App {
+    subcommand("advisories")
+        .about("Check Cargo.lock for packages with security advisories")
+        .arg(opt("db", "Directory of the advisory database").value_name("PATH"))
+        .arg_message_format()
+        .arg_manifest_path()
+        .after_help(
+            "\
+Checks every package in Cargo.lock against a local copy of an advisory database
+in the RustSec format, such as a checkout of
+https://github.com/RustSec/advisory-db, and fails if any advisory applies to
+one of them. Nothing is fetched from the network, so the database must be kept
+up to date separately.
+
+For each advisory the patched versions are shown, along with the path through
+which each package depending on the affected package is pulled in.
+
+The database is given with --db, or otherwise with the `advisories.db` config
+key.
+",
+        )
+}
+
+pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
+    let ws = args.workspace(config)?;
+
+    let db = match args.value_of_path("db", config) {
+        Some(db) => db,
+        None => match config.get_path("advisories.db")? {
+            Some(db) => db.val,
+            None => {
+                return Err(format_err!(
+                    "no advisory database given, pass `--db` or set `advisories.db` \
+                     in the Cargo config"
+                ).into())
+            }
+        },
+    };
+    let message_format = match args.value_of("message-format") {
+        Some(f) if f.eq_ignore_ascii_case("json") => MessageFormat::Json,
+        Some(f) if f.eq_ignore_ascii_case("short") => MessageFormat::Short,
+        _ => MessageFormat::Human,
+    };
+    let opts = AdvisoriesOptions { db, message_format };
+    ops::advisories(&ws, &opts)?;
+    Ok(())
+}
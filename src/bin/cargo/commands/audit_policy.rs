@@ -0,0 +1,43 @@
+use command_prelude::*;
+
+use cargo::ops;
+
+pub fn cli() -> App {
+    subcommand("audit-policy")
+        .about("Check the dependencies against the policy in the `[policy]` config table")
+        .arg_manifest_path()
+        .after_help(
+            "\
+Checks every package in the lock file, other than the members of the workspace,
+against the `[policy]` table of the Cargo configuration, and fails with a report
+of each package which violates it. The table may contain:
+
+    [policy]
+    # Licenses which may be used. Each package's `license` must be possible to
+    # comply with using only these, e.g. `MIT OR GPL-3.0` is fine if `MIT` is
+    # allowed. If unset, any license not denied is allowed.
+    allow-licenses = [\"MIT\", \"Apache-2.0\"]
+    # Licenses which may not be used.
+    deny-licenses = [\"GPL-2.0\", \"GPL-3.0\"]
+    # Sources packages may come from: `registry`, `git`, `path`, `directory`,
+    # or a URL, which also allows anything below it. If unset, any source is
+    # allowed.
+    allow-sources = [\"registry\", \"https://github.com/my-org\"]
+
+    # Versions of crates which may not be used.
+    [policy.deny-crates]
+    openssl = \"<0.10.10\"
+
+Licenses are SPDX expressions, and identifiers are compared without regard to
+case or the `-only` and `-or-later` suffixes, so denying `GPL-3.0` also denies
+`GPL-3.0-or-later`. When a license policy is set, packages without a `license`
+field violate it.
+",
+        )
+}
+
+pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
+    let ws = args.workspace(config)?;
+    ops::audit_policy(&ws)?;
+    Ok(())
+}
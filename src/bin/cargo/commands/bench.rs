@@ -40,6 +40,8 @@ pub fn cli() -> App {
         .arg_target_dir()
         .arg_manifest_path()
         .arg_message_format()
+        .arg_profile("Build artifacts with the specified profile")
+        .arg_timings()
         .arg(opt(
             "no-fail-fast",
             "Run all benchmarks regardless of failure",
@@ -65,7 +67,9 @@ Note that `--exclude` has to be specified in conjunction with the `--all` flag.
 The --jobs argument affects the building of the benchmark executable but does
 not affect how many jobs are used when running the benchmarks.
 
-Compilation can be customized with the `bench` profile in the manifest.
+Compilation can be customized with the `bench` profile in the manifest. With
+`--profile NAME` the custom profile defined by `[profile.NAME]` is used
+instead.
 ",
         )
 }
@@ -73,12 +77,14 @@ Compilation can be customized with the `bench` profile in the manifest.
 pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
     let ws = args.workspace(config)?;
     let mut compile_opts = args.compile_options(config, CompileMode::Bench)?;
-    compile_opts.build_config.release = true;
+    compile_opts.build_config.profile = args.profile_kind()?.unwrap_or(ProfileKind::Release);
 
     let ops = TestOptions {
         no_run: args.is_present("no-run"),
         no_fail_fast: args.is_present("no-fail-fast"),
         only_doc: false,
+        partition: None,
+        test_jobs: None,
         compile_opts,
     };
 
@@ -25,12 +25,14 @@ pub fn cli() -> App {
             "Build all targets (lib and bin targets by default)",
         )
         .arg_release("Build artifacts in release mode, with optimizations")
+        .arg_profile("Build artifacts with the specified profile")
         .arg_features()
         .arg_target_triple("Build for the target triple")
         .arg_target_dir()
         .arg(opt("out-dir", "Copy final artifacts to this directory").value_name("PATH"))
         .arg_manifest_path()
         .arg_message_format()
+        .arg_timings()
         .arg_build_plan()
         .after_help(
             "\
@@ -45,7 +47,8 @@ Note that `--exclude` has to be specified in conjunction with the `--all` flag.
 
 Compilation can be configured via the use of profiles which are configured in
 the manifest. The default profile for this command is `dev`, but passing
-the --release flag will use the `release` profile instead.
+the --release flag will use the `release` profile instead, and `--profile NAME`
+will use the custom profile defined by `[profile.NAME]`.
 ",
         )
 }
@@ -53,6 +56,9 @@ the --release flag will use the `release` profile instead.
 pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
     let ws = args.workspace(config)?;
     let mut compile_opts = args.compile_options(config, CompileMode::Build)?;
+    if let Some(profile) = args.profile_kind()? {
+        compile_opts.build_config.profile = profile;
+    }
     compile_opts.export_dir = args.value_of_path("out-dir", config);
     if compile_opts.export_dir.is_some() && !config.cli_unstable().unstable_options {
         Err(format_err!(
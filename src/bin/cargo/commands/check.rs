@@ -24,12 +24,13 @@ pub fn cli() -> App {
             "Check all targets (lib and bin targets by default)",
         )
         .arg_release("Check artifacts in release mode, with optimizations")
-        .arg(opt("profile", "Profile to build the selected target for").value_name("PROFILE"))
+        .arg_profile("Check artifacts with the specified profile")
         .arg_features()
         .arg_target_triple("Check for the target triple")
         .arg_target_dir()
         .arg_manifest_path()
         .arg_message_format()
+        .arg_timings()
         .after_help(
             "\
 If the --package argument is given, then SPEC is a package id specification
@@ -43,7 +44,8 @@ Note that `--exclude` has to be specified in conjunction with the `--all` flag.
 
 Compilation can be configured via the use of profiles which are configured in
 the manifest. The default profile for this command is `dev`, but passing
-the --release flag will use the `release` profile instead.
+the --release flag will use the `release` profile instead, and `--profile NAME`
+will use the custom profile defined by `[profile.NAME]`.
 
 The `--profile test` flag can be used to check unit tests with the
 `#[cfg(test)]` attribute.
@@ -53,20 +55,14 @@ The `--profile test` flag can be used to check unit tests with the
 
 pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
     let ws = args.workspace(config)?;
-    let test = match args.value_of("profile") {
-        Some("test") => true,
-        None => false,
-        Some(profile) => {
-            let err = format_err!(
-                "unknown profile: `{}`, only `test` is \
-                 currently supported",
-                profile
-            );
-            return Err(CliError::new(err, 101));
-        }
-    };
+    let test = args.value_of("profile") == Some("test");
     let mode = CompileMode::Check { test };
-    let compile_opts = args.compile_options(config, mode)?;
+    let mut compile_opts = args.compile_options(config, mode)?;
+    if !test {
+        if let Some(profile) = args.profile_kind()? {
+            compile_opts.build_config.profile = profile;
+        }
+    }
     ops::compile(&ws, &compile_opts)?;
     Ok(())
 }
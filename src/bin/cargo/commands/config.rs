@@ -0,0 +1,54 @@
+use command_prelude::*;
+
+use cargo::ops::{self, ConfigFormat, ConfigGetOptions};
+
+pub fn cli() -> App {
+    subcommand("config")
+        .about("Inspect the configuration")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            subcommand("get")
+                .about("Print the configuration, or the value of a single key")
+                .arg(Arg::with_name("key").help("The config key to print, like `build.rustflags`"))
+                .arg(
+                    opt("format", "Format to print the values in")
+                        .value_name("FMT")
+                        .possible_values(&["toml", "json"])
+                        .default_value("toml"),
+                )
+                .arg(opt(
+                    "show-origin",
+                    "Show the file or environment variable each value is from",
+                ))
+                .after_help(
+                    "\
+Prints the configuration Cargo uses in the current directory, merged from every
+`.cargo/config` file as well as from the `CARGO_*` environment variables, which
+take precedence. If a key is given only the values below it are printed.
+
+With --show-origin every value is followed by a comment naming the config file
+or the environment variable it comes from. The elements of a list may each come
+from a different file.
+",
+                ),
+        )
+}
+
+pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
+    match args.subcommand() {
+        ("get", Some(args)) => {
+            let format = match args.value_of("format") {
+                Some("json") => ConfigFormat::Json,
+                _ => ConfigFormat::Toml,
+            };
+            let opts = ConfigGetOptions {
+                key: args.value_of("key"),
+                format,
+                show_origin: args.is_present("show-origin"),
+            };
+            ops::config_get(config, &opts)?;
+        }
+        _ => unreachable!(),
+    }
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use command_prelude::*;
+
+use cargo::core::compiler::MessageFormat;
+use cargo::ops::{self, DuplicatesOptions, Packages};
+
+pub fn cli() -> App {
+    subcommand("duplicates")
+        .about("Report packages which are used in more than one version")
+        .arg_package_spec(
+            "Package whose dependencies to check",
+            "Check the dependencies of all packages in the workspace",
+            "Exclude specific workspace members",
+        )
+        .arg_features()
+        .arg_message_format()
+        .arg(opt(
+            "deny-duplicates",
+            "Exit with an error if any package is used in more than one version",
+        ))
+        .arg_manifest_path()
+        .after_help(
+            "\
+Lists every package which appears in the resolved dependency graph in more than
+one version. For each version it shows the path through which every package
+depending on it is pulled in, along with that package's version requirement
+and which of the other versions the requirement rules out. Those requirements
+are what keeps Cargo from unifying the package onto a single version.
+
+With --deny-duplicates the command fails if any duplicate is found, which is
+useful to keep them from creeping into a project in CI.
+",
+        )
+}
+
+pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
+    let ws = args.workspace(config)?;
+
+    let message_format = match args.value_of("message-format") {
+        Some(f) if f.eq_ignore_ascii_case("json") => MessageFormat::Json,
+        Some(f) if f.eq_ignore_ascii_case("short") => MessageFormat::Short,
+        _ => MessageFormat::Human,
+    };
+    let opts = DuplicatesOptions {
+        features: values(args, "features"),
+        all_features: args.is_present("all-features"),
+        no_default_features: args.is_present("no-default-features"),
+        spec: Packages::from_flags(
+            args.is_present("all"),
+            values(args, "exclude"),
+            values(args, "package"),
+        )?,
+        message_format,
+        deny: args.is_present("deny-duplicates"),
+    };
+    ops::duplicates(&ws, &opts)?;
+    Ok(())
+}
@@ -26,6 +26,7 @@ pub fn cli() -> App {
         .arg(opt("force", "Force overwriting existing crates or binaries").short("f"))
         .arg_features()
         .arg(opt("debug", "Build in debug mode instead of release mode"))
+        .arg_profile("Build with the specified profile instead of release mode")
         .arg_targets_bins_examples(
             "Install only the specified binary",
             "Install all binaries",
@@ -74,7 +75,11 @@ continuous integration systems.",
 
 pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
     let mut compile_opts = args.compile_options(config, CompileMode::Build)?;
-    compile_opts.build_config.release = !args.is_present("debug");
+    compile_opts.build_config.profile = match args.profile_kind()? {
+        Some(profile) => profile,
+        None if args.is_present("debug") => ProfileKind::Dev,
+        None => ProfileKind::Release,
+    };
 
     let krates = args.values_of("crate")
         .unwrap_or_default()
@@ -2,11 +2,16 @@ use command_prelude::*;
 
 pub fn builtin() -> Vec<App> {
     vec![
+        add::cli(),
+        advisories::cli(),
+        audit_policy::cli(),
         bench::cli(),
         build::cli(),
         check::cli(),
         clean::cli(),
+        config::cli(),
         doc::cli(),
+        duplicates::cli(),
         fetch::cli(),
         fix::cli(),
         generate_lockfile::cli(),
@@ -17,18 +22,22 @@ pub fn builtin() -> Vec<App> {
         login::cli(),
         metadata::cli(),
         new::cli(),
+        outdated::cli(),
         owner::cli(),
         package::cli(),
         pkgid::cli(),
         publish::cli(),
         read_manifest::cli(),
+        rm::cli(),
         run::cli(),
         rustc::cli(),
         rustdoc::cli(),
         search::cli(),
         test::cli(),
+        tree::cli(),
         uninstall::cli(),
         update::cli(),
+        vendor::cli(),
         verify_project::cli(),
         version::cli(),
         yank::cli(),
@@ -37,11 +46,16 @@ pub fn builtin() -> Vec<App> {
 
 pub fn builtin_exec(cmd: &str) -> Option<fn(&mut Config, &ArgMatches) -> CliResult> {
     let f = match cmd {
+        "add" => add::exec,
+        "advisories" => advisories::exec,
+        "audit-policy" => audit_policy::exec,
         "bench" => bench::exec,
         "build" => build::exec,
         "check" => check::exec,
         "clean" => clean::exec,
+        "config" => config::exec,
         "doc" => doc::exec,
+        "duplicates" => duplicates::exec,
         "fetch" => fetch::exec,
         "fix" => fix::exec,
         "generate-lockfile" => generate_lockfile::exec,
@@ -52,18 +66,22 @@ pub fn builtin_exec(cmd: &str) -> Option<fn(&mut Config, &ArgMatches) -> CliResu
         "login" => login::exec,
         "metadata" => metadata::exec,
         "new" => new::exec,
+        "outdated" => outdated::exec,
         "owner" => owner::exec,
         "package" => package::exec,
         "pkgid" => pkgid::exec,
         "publish" => publish::exec,
         "read-manifest" => read_manifest::exec,
+        "rm" => rm::exec,
         "run" => run::exec,
         "rustc" => rustc::exec,
         "rustdoc" => rustdoc::exec,
         "search" => search::exec,
         "test" => test::exec,
+        "tree" => tree::exec,
         "uninstall" => uninstall::exec,
         "update" => update::exec,
+        "vendor" => vendor::exec,
         "verify-project" => verify_project::exec,
         "version" => version::exec,
         "yank" => yank::exec,
@@ -72,11 +90,16 @@ pub fn builtin_exec(cmd: &str) -> Option<fn(&mut Config, &ArgMatches) -> CliResu
     Some(f)
 }
 
+pub mod add;
+pub mod advisories;
+pub mod audit_policy;
 pub mod bench;
 pub mod build;
 pub mod check;
 pub mod clean;
+pub mod config;
 pub mod doc;
+pub mod duplicates;
 pub mod fetch;
 pub mod fix;
 pub mod generate_lockfile;
@@ -87,18 +110,22 @@ pub mod locate_project;
 pub mod login;
 pub mod metadata;
 pub mod new;
+pub mod outdated;
 pub mod owner;
 pub mod package;
 pub mod pkgid;
 pub mod publish;
 pub mod read_manifest;
+pub mod rm;
 pub mod run;
 pub mod rustc;
 pub mod rustdoc;
 pub mod search;
 pub mod test;
+pub mod tree;
 pub mod uninstall;
 pub mod update;
+pub mod vendor;
 pub mod verify_project;
 pub mod version;
 pub mod yank;
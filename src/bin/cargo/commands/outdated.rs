@@ -0,0 +1,43 @@
+use command_prelude::*;
+
+use cargo::core::compiler::MessageFormat;
+use cargo::ops::{self, OutdatedOptions};
+
+pub fn cli() -> App {
+    subcommand("outdated")
+        .about("Show dependencies which have newer versions available")
+        .arg(opt(
+            "transitive",
+            "Also show the dependencies of dependencies",
+        ))
+        .arg_message_format()
+        .arg_manifest_path()
+        .after_help(
+            "\
+Lists the dependencies from a registry for which a newer version than the one in
+Cargo.lock is available. For each one the locked version is shown, along with
+the newest version which the version requirements of every package depending on
+it accept, which `cargo update` can move to, and the newest version of all,
+which may need a requirement to be raised.
+
+Only the direct dependencies of the workspace members are shown, unless
+--transitive is given.
+",
+        )
+}
+
+pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
+    let ws = args.workspace(config)?;
+
+    let message_format = match args.value_of("message-format") {
+        Some(f) if f.eq_ignore_ascii_case("json") => MessageFormat::Json,
+        Some(f) if f.eq_ignore_ascii_case("short") => MessageFormat::Short,
+        _ => MessageFormat::Human,
+    };
+    let opts = OutdatedOptions {
+        transitive: args.is_present("transitive"),
+        message_format,
+    };
+    ops::outdated(&ws, &opts)?;
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use command_prelude::*;
+
+use cargo::core::dependency::Kind;
+use cargo::ops::{self, Packages, RemoveOptions};
+
+pub fn cli() -> App {
+    subcommand("rm")
+        .about("Remove dependencies from a Cargo.toml manifest file")
+        .arg(
+            Arg::with_name("crate")
+                .multiple(true)
+                .required(true)
+                .help("Dependencies to remove"),
+        )
+        .arg(opt("dev", "Remove from the development dependencies").short("D"))
+        .arg(opt("build", "Remove from the build dependencies").short("B"))
+        .arg_target_triple("Remove from the dependencies for the given target triple or `cfg` expression")
+        .arg_package("Package in the workspace to remove dependencies from")
+        .arg_manifest_path()
+}
+
+pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
+    let ws = args.workspace(config)?;
+    let manifest_path = match args.value_of("package") {
+        Some(name) => Packages::Packages(vec![name.to_string()]).get_packages(&ws)?[0]
+            .manifest_path()
+            .to_path_buf(),
+        None => ws.current()?.manifest_path().to_path_buf(),
+    };
+
+    let kind = match (args.is_present("dev"), args.is_present("build")) {
+        (false, false) => Kind::Normal,
+        (true, false) => Kind::Development,
+        (false, true) => Kind::Build,
+        (true, true) => {
+            return Err(format_err!("the `--dev` and `--build` flags cannot be used together").into())
+        }
+    };
+
+    let opts = RemoveOptions {
+        config,
+        crates: values(args, "crate"),
+        kind,
+        target: args.target(),
+    };
+    ops::remove(&manifest_path, &opts)?;
+    Ok(())
+}
@@ -21,6 +21,7 @@ pub fn cli() -> App {
         .arg_target_dir()
         .arg_manifest_path()
         .arg_message_format()
+        .arg_timings()
         .after_help(
             "\
 If neither `--bin` nor `--example` are given, then if the project only has one
@@ -32,6 +32,16 @@ pub fn cli() -> App {
         .arg(opt("doc", "Test only this library's documentation"))
         .arg(opt("no-run", "Compile, but don't run tests"))
         .arg(opt("no-fail-fast", "Run all tests regardless of failure"))
+        .arg(
+            opt(
+                "partition",
+                "Only run the test targets in partition M of N, split by `count` or `hash`",
+            ).value_name("KIND:M/N"),
+        )
+        .arg(
+            opt("test-jobs", "Number of test binaries to run at once (default 1)")
+                .value_name("N"),
+        )
         .arg_package_spec(
             "Package to run tests for",
             "Test all packages in the workspace",
@@ -39,11 +49,13 @@ pub fn cli() -> App {
         )
         .arg_jobs()
         .arg_release("Build artifacts in release mode, with optimizations")
+        .arg_profile("Build artifacts with the specified profile")
         .arg_features()
         .arg_target_triple("Build for the target triple")
         .arg_target_dir()
         .arg_manifest_path()
         .arg_message_format()
+        .arg_timings()
         .after_help(
             "\
 The test filtering argument `TESTNAME` and all the arguments following the
@@ -73,7 +85,15 @@ to the test binaries:
 
     cargo test -- --test-threads=1
 
-Compilation can be configured via the `test` profile in the manifest.
+The --test-jobs argument runs up to N test binaries at once, rather than one
+after the other. The output of each binary is held back until it finishes and
+then printed in one piece, so binaries are reported in the order they finish.
+When Cargo is run by a jobserver, such as `make`, it also waits for a token
+from the jobserver before starting each extra binary.
+
+Compilation can be configured via the `test` profile in the manifest. With
+`--profile NAME` the custom profile defined by `[profile.NAME]` is used
+instead.
 
 By default the rust test harness hides output from test execution to
 keep results readable. Test output can be recovered (e.g. for debugging)
@@ -84,6 +104,15 @@ by passing `--nocapture` to the test binaries:
 To get the list of all options available for the test binaries use this:
 
     cargo test -- --help
+
+The --partition flag splits the test targets (each test binary, and the doc
+tests of each library) into N parts and runs only the M-th, so the tests can be
+spread across N machines. With `count:M/N` targets are dealt out to the parts
+in turn, which keeps the parts even; with `hash:M/N` a target's part depends
+only on its name, so adding or removing a target doesn't move any others. Both
+give the same split every time for the same set of targets:
+
+    cargo test --partition hash:1/3
 ",
         )
 }
@@ -92,6 +121,9 @@ pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
     let ws = args.workspace(config)?;
 
     let mut compile_opts = args.compile_options(config, CompileMode::Test)?;
+    if let Some(profile) = args.profile_kind()? {
+        compile_opts.build_config.profile = profile;
+    }
     let doc = args.is_present("doc");
     if doc {
         compile_opts.build_config.mode = CompileMode::Doctest;
@@ -109,10 +141,22 @@ pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
         );
     }
 
+    let partition = match args.value_of("partition") {
+        Some(s) => Some(s.parse::<ops::Partition>()?),
+        None => None,
+    };
+
+    let test_jobs = args.value_of_u32("test-jobs")?;
+    if test_jobs == Some(0) {
+        return Err(format_err!("`--test-jobs` must be at least 1").into());
+    }
+
     let ops = ops::TestOptions {
         no_run: args.is_present("no-run"),
         no_fail_fast: args.is_present("no-fail-fast"),
         only_doc: doc,
+        partition,
+        test_jobs,
         compile_opts,
     };
 
@@ -0,0 +1,97 @@
+use command_prelude::*;
+
+use cargo::core::dependency::Kind;
+use cargo::ops::{self, Packages, TreeOptions};
+
+pub fn cli() -> App {
+    subcommand("tree")
+        .about("Display a tree visualization of a dependency graph")
+        .arg_package_spec(
+            "Package to be used as the root of the tree",
+            "Display the tree for all packages in the workspace",
+            "Exclude specific workspace members",
+        )
+        .arg_features()
+        .arg_target_triple(
+            "Filter dependencies matching the given target triple \
+             (defaults to the host, use `all` to include all targets)",
+        )
+        .arg(
+            opt(
+                "invert",
+                "Invert the tree direction, showing what depends on the given package",
+            ).short("i")
+                .value_name("SPEC"),
+        )
+        .arg(
+            opt(
+                "duplicates",
+                "Show only dependencies which come in multiple versions (implies --invert)",
+            ).short("d"),
+        )
+        .arg(
+            opt(
+                "edges",
+                "The kinds of dependencies to display, separated by commas \
+                 (normal, build, dev)",
+            ).short("e")
+                .value_name("KINDS"),
+        )
+        .arg_manifest_path()
+        .after_help(
+            "\
+Displays the resolved dependency graph of the current package as a tree.
+Packages which have already been displayed once are marked with `(*)` and
+their dependencies are not repeated.
+
+If the --package argument is given, then SPEC is a package id specification
+which indicates which package should be used as the root of the tree. For more
+information on SPEC and its format, see the `cargo help pkgid` command.
+
+The --invert flag takes a package id specification as well, and displays the
+packages which depend on that package instead.
+",
+        )
+}
+
+pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
+    let ws = args.workspace(config)?;
+
+    let kinds = match args.value_of("edges") {
+        None => vec![Kind::Normal, Kind::Build, Kind::Development],
+        Some(edges) => {
+            let mut kinds = Vec::new();
+            for edge in edges.split(',').map(|s| s.trim()) {
+                let kind = match edge {
+                    "normal" => Kind::Normal,
+                    "build" => Kind::Build,
+                    "dev" => Kind::Development,
+                    _ => Err(format_err!(
+                        "unknown edge kind `{}`, expected one of \
+                         `normal`, `build` or `dev`",
+                        edge
+                    ))?,
+                };
+                kinds.push(kind);
+            }
+            kinds
+        }
+    };
+
+    let opts = TreeOptions {
+        features: values(args, "features"),
+        all_features: args.is_present("all-features"),
+        no_default_features: args.is_present("no-default-features"),
+        spec: Packages::from_flags(
+            args.is_present("all"),
+            values(args, "exclude"),
+            values(args, "package"),
+        )?,
+        target: args.target(),
+        invert: args.value_of("invert").map(|s| s.to_string()),
+        duplicates: args.is_present("duplicates"),
+        kinds,
+    };
+    ops::tree(&ws, &opts)?;
+    Ok(())
+}
@@ -11,6 +11,14 @@ pub fn cli() -> App {
             "Force updating all dependencies of <name> as well",
         ))
         .arg(opt("precise", "Update a single dependency to exactly PRECISE").value_name("PRECISE"))
+        .arg(opt(
+            "dry-run",
+            "Show what would be updated without writing the lock file",
+        ))
+        .arg(opt(
+            "breaking",
+            "Raise version requirements in Cargo.toml to allow incompatible versions",
+        ))
         .arg_manifest_path()
         .after_help(
             "\
@@ -32,6 +40,16 @@ be updated to.
 If SPEC is not given, then all dependencies will be re-resolved and
 updated.
 
+With --dry-run nothing is written. Instead every change is listed as an
+upgrade, a downgrade, an addition or a removal, and changes to a version which
+isn't semver compatible with the locked one are marked as breaking.
+
+Updates normally stay within the version requirements in the manifests. With
+--breaking, any requirement of a workspace member on a package from a registry
+which doesn't accept the newest version is changed in Cargo.toml to require
+that version, for the packages given by SPEC or for all of them. Combined with
+--dry-run the manifests are left as they are.
+
 For more information about package id specifications, see `cargo help pkgid`.
 ",
         )
@@ -44,6 +62,8 @@ pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
         aggressive: args.is_present("aggressive"),
         precise: args.value_of("precise"),
         to_update: values(args, "package"),
+        dry_run: args.is_present("dry-run"),
+        breaking: args.is_present("breaking"),
         config,
     };
     ops::update_lockfile(&ws, &update_opts)?;
@@ -0,0 +1,75 @@
+use command_prelude::*;
+
+use std::path::PathBuf;
+
+use cargo::ops::{self, VendorOptions};
+use toml;
+
+pub fn cli() -> App {
+    subcommand("vendor")
+        .about("Vendor all dependencies for a project locally")
+        .arg_manifest_path()
+        .arg(Arg::with_name("path").help("Where to vendor crates (`vendor` by default)"))
+        .arg(opt(
+            "no-delete",
+            "Don't delete older crates in the vendor directory",
+        ))
+        .arg(
+            multi_opt(
+                "sync",
+                "TOML",
+                "Additional `Cargo.toml` to sync and vendor",
+            ).short("s"),
+        )
+        .arg(opt(
+            "respect-source-config",
+            "Respect `[source]` config in `.cargo/config`",
+        ))
+        .arg(opt(
+            "versioned-dirs",
+            "Always include version in subdir name",
+        ))
+        .after_help(
+            "\
+This cargo subcommand will vendor all crates.io and git dependencies for a
+project into the specified directory at `<path>`. After this command completes
+the vendor directory specified by `<path>` will contain all remote sources from
+dependencies specified. Additional manifests beyond the default one can be
+specified with the `-s` option.
+
+The `cargo vendor` command will also print out the configuration necessary
+to use the vendored sources, which you will need to add to `.cargo/config`.
+",
+        )
+}
+
+pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
+    // By default `cargo vendor` ignores any existing source replacement, so
+    // that re-vendoring fetches from the original sources rather than from the
+    // vendor directory itself.
+    if !args.is_present("respect-source-config") {
+        config.values_mut()?.remove("source");
+    }
+
+    let ws = args.workspace(config)?;
+    let path = args.value_of_os("path")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("vendor"));
+    let vendor_config = ops::vendor(
+        &ws,
+        &VendorOptions {
+            no_delete: args.is_present("no-delete"),
+            versioned_dirs: args.is_present("versioned-dirs"),
+            destination: &path,
+            extra: values(args, "sync").into_iter().map(PathBuf::from).collect(),
+        },
+    )?;
+
+    let toml = toml::to_string(&vendor_config).map_err(|e| format_err!("{}", e))?;
+    config.shell().status(
+        "Vendored",
+        "add the following to `.cargo/config` to use the vendored sources",
+    )?;
+    print!("{}", toml);
+    Ok(())
+}
@@ -1,6 +1,8 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::cell::RefCell;
 
+use serde::ser;
+
 use util::{CargoResult, CargoResultExt, Config, RustfixDiagnosticServer};
 
 /// Configuration information for a rustc build.
@@ -10,14 +12,22 @@ pub struct BuildConfig {
     pub requested_target: Option<String>,
     /// How many rustc jobs to run in parallel
     pub jobs: u32,
-    /// Whether we are building for release
-    pub release: bool,
+    /// The profile the build uses
+    pub profile: ProfileKind,
     /// In what mode we are compiling
     pub mode: CompileMode,
     /// Whether to print std output in json format (for machine reading)
     pub message_format: MessageFormat,
     /// Output a build plan to stdout instead of actually compiling.
     pub build_plan: bool,
+    /// Start building a library's dependents as soon as its metadata is
+    /// ready, rather than waiting for it to finish.
+    pub pipelining: bool,
+    /// Record how long each unit takes and write a report of it to
+    /// `target/cargo-timings`.
+    pub timings: bool,
+    /// Directory of the build artifact cache shared between projects, if any.
+    pub shared_cache: Option<PathBuf>,
     /// Use Cargo itself as the wrapper around rustc, only used for `cargo fix`
     pub cargo_as_rustc_wrapper: bool,
     /// Extra env vars to inject into rustc commands
@@ -32,6 +42,8 @@ impl BuildConfig {
     /// configured options are:
     ///
     /// * build.jobs
+    /// * build.pipelining
+    /// * build.shared-cache
     /// * build.target
     /// * target.$target.ar
     /// * target.$target.linker
@@ -73,13 +85,18 @@ impl BuildConfig {
         }
         let cfg_jobs: Option<u32> = config.get("build.jobs")?;
         let jobs = jobs.or(cfg_jobs).unwrap_or(::num_cpus::get() as u32);
+        let pipelining = config.get_bool("build.pipelining")?.map(|t| t.val).unwrap_or(false);
+        let shared_cache = config.get_path("build.shared-cache")?.map(|p| p.val);
         Ok(BuildConfig {
             requested_target: target,
             jobs,
-            release: false,
+            profile: ProfileKind::Dev,
             mode,
             message_format: MessageFormat::Human,
             build_plan: false,
+            pipelining,
+            timings: false,
+            shared_cache,
             cargo_as_rustc_wrapper: false,
             extra_rustc_env: Vec::new(),
             extra_rustc_args: Vec::new(),
@@ -96,6 +113,42 @@ impl BuildConfig {
     }
 }
 
+/// The profile selected for a build, with `--release` or `--profile`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ProfileKind {
+    Dev,
+    Release,
+    /// A custom profile, defined by a `[profile.<name>]` table.
+    Custom(String),
+}
+
+impl ProfileKind {
+    pub fn new(name: &str) -> ProfileKind {
+        match name {
+            "dev" => ProfileKind::Dev,
+            "release" => ProfileKind::Release,
+            _ => ProfileKind::Custom(name.to_string()),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match *self {
+            ProfileKind::Dev => "dev",
+            ProfileKind::Release => "release",
+            ProfileKind::Custom(ref name) => name,
+        }
+    }
+
+    /// The name of the directory in `target` that the build's artifacts go
+    /// in.
+    pub fn dest(&self) -> &str {
+        match *self {
+            ProfileKind::Dev => "debug",
+            _ => self.name(),
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum MessageFormat {
     Human,
@@ -186,3 +239,20 @@ impl CompileMode {
         &ALL
     }
 }
+
+impl ser::Serialize for CompileMode {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        s.serialize_str(match *self {
+            CompileMode::Test => "test",
+            CompileMode::Build => "build",
+            CompileMode::Check { .. } => "check",
+            CompileMode::Bench => "bench",
+            CompileMode::Doc { .. } => "doc",
+            CompileMode::Doctest => "doctest",
+            CompileMode::RunCustomBuild => "run-custom-build",
+        })
+    }
+}
@@ -1,15 +1,16 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::env;
 use std::path::{Path, PathBuf};
 use std::str::{self, FromStr};
 
 use core::profiles::Profiles;
+use core::resolver::{FeatureResolver, FeaturesFor, Method, ResolveBehavior, ResolvedFeatures};
 use core::{Dependency, Workspace};
 use core::{Package, PackageId, PackageSet, Resolve};
 use util::errors::CargoResult;
 use util::{profile, Cfg, CfgExpr, Config, Rustc};
 
-use super::{BuildConfig, BuildOutput, Kind, Unit};
+use super::{BuildConfig, BuildOutput, CompileMode, Kind, Unit};
 
 mod target_info;
 pub use self::target_info::{FileFlavor, TargetInfo};
@@ -31,6 +32,9 @@ pub struct BuildContext<'a, 'cfg: 'a> {
     /// the `Unit` corresponding to the top-level target.
     pub extra_compiler_args: Option<(Unit<'a>, Vec<String>)>,
     pub packages: &'a PackageSet<'cfg>,
+    /// The features each package is built with. These are the ones in
+    /// `resolve` unless `resolve_features` has split them up.
+    pub features: ResolvedFeatures,
 
     /// Information about the compiler
     pub rustc: Rustc,
@@ -78,6 +82,7 @@ impl<'a, 'cfg> BuildContext<'a, 'cfg> {
             ws,
             resolve,
             packages,
+            features: ResolvedFeatures::unified(),
             config,
             rustc,
             target_config,
@@ -111,6 +116,64 @@ impl<'a, 'cfg> BuildContext<'a, 'cfg> {
         platform.matches(name, info.cfg())
     }
 
+    /// Resolves features separately for the host and the target if the
+    /// workspace uses `resolver = "2"`, for building `roots` with the
+    /// features requested by `method`.
+    pub fn resolve_features(&mut self, roots: &[Unit<'a>], method: Method) -> CargoResult<()> {
+        if self.ws.resolve_behavior() == ResolveBehavior::V1 {
+            return Ok(());
+        }
+
+        // Each root package, and whether any of its units use its
+        // dev-dependencies.
+        let mut root_ids: Vec<(&PackageId, bool)> = Vec::new();
+        for unit in roots {
+            let dev =
+                unit.mode.is_any_test() || unit.target.is_test() || unit.target.is_example();
+            let id = unit.pkg.package_id();
+            match root_ids.iter().position(|&(root, _)| root == id) {
+                Some(i) => root_ids[i].1 |= dev,
+                None => root_ids.push((id, dev)),
+            }
+        }
+
+        let features = {
+            let platform_activated = |dep: &Dependency, features_for: FeaturesFor| {
+                let kind = match features_for {
+                    FeaturesFor::Host => Kind::Host,
+                    FeaturesFor::Target => Kind::Target,
+                };
+                self.dep_platform_activated(dep, kind)
+            };
+            FeatureResolver::resolve(
+                self.resolve,
+                self.packages,
+                &root_ids,
+                method,
+                &platform_activated,
+            )?
+        };
+        self.features = features;
+        Ok(())
+    }
+
+    /// The features enabled when building `unit`.
+    pub fn unit_features(&self, unit: &Unit<'a>) -> &HashSet<String> {
+        self.features
+            .activated(self.resolve, unit.pkg.package_id(), unit.features)
+    }
+
+    /// The features enabled when building `unit`, sorted so they can be
+    /// hashed or passed to rustc deterministically.
+    pub fn unit_features_sorted(&self, unit: &Unit<'a>) -> Vec<&str> {
+        let mut features = self.unit_features(unit)
+            .iter()
+            .map(|s| s.as_str())
+            .collect::<Vec<_>>();
+        features.sort_unstable();
+        features
+    }
+
     /// Gets a package for the given package id.
     pub fn get_package(&self, id: &PackageId) -> CargoResult<&'a Package> {
         self.packages.get(id)
@@ -199,6 +262,16 @@ impl<'a, 'cfg> BuildContext<'a, 'cfg> {
         }
     }
 
+    /// Whether `unit` emits its metadata separately, reporting when it's
+    /// ready so that its dependents can start early. This is only done with
+    /// `build.pipelining`.
+    pub fn rmeta_required(&self, unit: &Unit<'a>) -> bool {
+        self.build_config.pipelining
+            && !self.build_config.build_plan
+            && unit.mode == CompileMode::Build
+            && !unit.requires_upstream_objects()
+    }
+
     pub fn extra_args_for(&self, unit: &Unit<'a>) -> Option<&Vec<String>> {
         if let Some((ref args_unit, ref args)) = self.extra_compiler_args {
             if args_unit == unit {
@@ -212,8 +285,7 @@ impl<'a, 'cfg> BuildContext<'a, 'cfg> {
     /// (all Cargo.toml, etc).
     pub fn inputs(&self) -> CargoResult<Vec<PathBuf>> {
         let mut inputs = Vec::new();
-        for id in self.packages.package_ids() {
-            let pkg = self.get_package(id)?;
+        for pkg in self.packages.get_many(self.packages.package_ids())? {
             inputs.push(pkg.manifest_path().to_path_buf());
         }
         inputs.sort();
@@ -271,7 +343,7 @@ impl TargetConfig {
                 match &k[..] {
                     "rustc-flags" => {
                         let (flags, definition) = value.string(k)?;
-                        let whence = format!("in `{}` (in {})", key, definition.display());
+                        let whence = format!("in `{}` (in {})", key, definition);
                         let (paths, links) = BuildOutput::parse_rustc_flags(flags, &whence)?;
                         output.library_paths.extend(paths);
                         output.library_links.extend(links);
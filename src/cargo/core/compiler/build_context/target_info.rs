@@ -23,6 +23,9 @@ pub enum FileFlavor {
     Normal,
     /// It is something you can link against (e.g. a library)
     Linkable,
+    /// The metadata of a library, which is all that's needed to compile
+    /// against it, emitted alongside the library itself when pipelining
+    Rmeta,
     /// It is a piece of external debug information (e.g. *.dSYM and *.pdb)
     DebugInfo,
 }
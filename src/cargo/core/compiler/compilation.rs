@@ -202,6 +202,9 @@ impl<'cfg> Compilation<'cfg> {
         let search_path = join_paths(&search_path, util::dylib_path_envvar())?;
 
         cmd.env(util::dylib_path_envvar(), &search_path);
+        for (k, v) in self.config.env_config()?.iter() {
+            cmd.env(k, v);
+        }
         if let Some(env) = self.extra_env.get(pkg.package_id()) {
             for &(ref k, ref v) in env {
                 cmd.env(k, v);
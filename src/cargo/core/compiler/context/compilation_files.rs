@@ -8,6 +8,7 @@ use std::sync::Arc;
 use lazycell::LazyCell;
 
 use super::{BuildContext, Context, FileFlavor, Kind, Layout, Unit};
+use core::resolver::FeaturesFor;
 use core::{TargetKind, Workspace};
 use util::{self, CargoResult};
 
@@ -313,6 +314,15 @@ impl<'a, 'cfg: 'a> CompilationFiles<'a, 'cfg> {
                                 },
                             )?;
                         }
+                        if bcx.rmeta_required(unit) {
+                            // Dependents which only need the metadata get
+                            // pointed at this, rather than the rlib.
+                            ret.push(OutputFile {
+                                path: out_dir.join(format!("lib{}.rmeta", file_stem)),
+                                hardlink: None,
+                                flavor: FileFlavor::Rmeta,
+                            });
+                        }
                     }
                 }
             }
@@ -409,9 +419,7 @@ fn compute_metadata<'a, 'cfg>(
 
     // Also mix in enabled features to our metadata. This'll ensure that
     // when changing feature sets each lib is separately cached.
-    bcx.resolve
-        .features_sorted(unit.pkg.package_id())
-        .hash(&mut hasher);
+    bcx.unit_features_sorted(unit).hash(&mut hasher);
 
     // Mix in the target-metadata of all the dependencies of this target
     {
@@ -438,6 +446,13 @@ fn compute_metadata<'a, 'cfg>(
     // the unit's `kind` as well
     unit.kind.hash(&mut hasher);
 
+    // With `resolver = "2"` a package may be built both for the target and
+    // for the host, so keep those apart even when their features match.
+    // Units using the unified features keep the metadata they always had.
+    if unit.features == FeaturesFor::Host {
+        unit.features.hash(&mut hasher);
+    }
+
     // Finally throw in the target name/kind. This ensures that concurrent
     // compiles of targets in the same crate don't collide.
     unit.target.name().hash(&mut hasher);
@@ -11,7 +11,9 @@ use jobserver::Client;
 use core::{Package, PackageId, Resolve, Target};
 use core::compiler::compilation;
 use core::profiles::Profile;
+use core::resolver::FeaturesFor;
 use util::errors::{CargoResult, CargoResultExt};
+use util::to_semver::rust_version_satisfied;
 use util::{internal, profile, Config, short_hash};
 
 use super::custom_build::{self, BuildDeps, BuildScripts, BuildState};
@@ -64,12 +66,22 @@ pub struct Unit<'a> {
     /// The "mode" this unit is being compiled for.  See `CompileMode` for
     /// more details.
     pub mode: CompileMode,
+    /// Which of its package's feature sets this unit is built with. This is
+    /// always `Target` unless the workspace uses `resolver = "2"`, in which
+    /// case build scripts, their dependencies and proc-macros use `Host`.
+    pub features: FeaturesFor,
 }
 
 impl<'a> Unit<'a> {
     pub fn buildkey(&self) -> String {
         format!("{}-{}", self.pkg.name(), short_hash(self))
 	}
+
+    /// Whether building this unit needs the compiled code of its
+    /// dependencies, rather than just their metadata.
+    pub fn requires_upstream_objects(&self) -> bool {
+        self.mode.is_any_test() || self.target.requires_upstream_objects()
+    }
 }
 
 impl<'a> Ord for Unit<'a> {
@@ -144,10 +156,11 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
         export_dir: Option<PathBuf>,
         exec: &Arc<Executor>,
     ) -> CargoResult<Compilation<'cfg>> {
-        let mut queue = JobQueue::new(self.bcx);
+        let mut queue = JobQueue::new(self.bcx, units);
         let mut plan = BuildPlan::new();
         let build_plan = self.bcx.build_config.build_plan;
         self.prepare_units(export_dir, units)?;
+        self.check_rust_version()?;
         self.prepare()?;
         custom_build::build_map(&mut self, units)?;
 
@@ -170,7 +183,7 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
 
         for unit in units.iter() {
             for output in self.outputs(unit)?.iter() {
-                if output.flavor == FileFlavor::DebugInfo {
+                if output.flavor == FileFlavor::DebugInfo || output.flavor == FileFlavor::Rmeta {
                     continue;
                 }
 
@@ -227,6 +240,7 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
                     .extend(
                         outputs
                             .iter()
+                            .filter(|output| output.flavor != FileFlavor::Rmeta)
                             .map(|output| (dep.target.clone(), output.path.clone())),
                     );
             }
@@ -265,7 +279,7 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
                 });
             }
 
-            let feats = self.bcx.resolve.features(unit.pkg.package_id());
+            let feats = self.bcx.unit_features(unit);
             if !feats.is_empty() {
                 self.compilation
                     .cfgs
@@ -308,16 +322,42 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
         Ok(self.compilation)
     }
 
+    /// Checks that the active rustc is at least the `rust-version` of every
+    /// package which is about to be built.
+    fn check_rust_version(&self) -> CargoResult<()> {
+        let mut pkgs = self.unit_dependencies
+            .keys()
+            .map(|unit| unit.pkg)
+            .filter(|pkg| pkg.rust_version().is_some())
+            .collect::<Vec<_>>();
+        if pkgs.is_empty() {
+            return Ok(());
+        }
+        pkgs.sort_by(|a, b| a.package_id().cmp(b.package_id()));
+        pkgs.dedup_by(|a, b| a.package_id() == b.package_id());
+
+        let rustc = self.bcx.rustc.version()?;
+        for pkg in pkgs {
+            let rust_version = pkg.rust_version().unwrap();
+            if !rust_version_satisfied(rust_version, &rustc) {
+                bail!(
+                    "package `{}` cannot be built because it requires rustc {} \
+                     or newer, while the currently active rustc version is {}",
+                    pkg.package_id(),
+                    rust_version,
+                    rustc
+                );
+            }
+        }
+        Ok(())
+    }
+
     pub fn prepare_units(
         &mut self,
         export_dir: Option<PathBuf>,
         units: &[Unit<'a>],
     ) -> CargoResult<()> {
-        let dest = if self.bcx.build_config.release {
-            "release"
-        } else {
-            "debug"
-        };
+        let dest = self.bcx.build_config.profile.dest();
         let host_layout = Layout::new(self.bcx.ws, None, dest)?;
         let target_layout = match self.bcx.build_config.requested_target.as_ref() {
             Some(target) => Some(Layout::new(self.bcx.ws, Some(target), dest)?),
@@ -412,6 +452,14 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
         self.files.as_ref().unwrap().outputs(unit, self.bcx)
     }
 
+    /// Whether `parent` can start building once the metadata of `dep` is
+    /// ready, without waiting for `dep` to finish.
+    pub fn only_requires_rmeta(&self, parent: &Unit<'a>, dep: &Unit<'a>) -> bool {
+        self.bcx.rmeta_required(dep)
+            && !parent.requires_upstream_objects()
+            && (parent.mode == CompileMode::Build || parent.mode.is_check())
+    }
+
     /// For a package, return all targets which are registered as dependencies
     /// for that package.
     // TODO: this ideally should be `-> &[Unit<'a>]`
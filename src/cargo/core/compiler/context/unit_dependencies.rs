@@ -20,6 +20,7 @@ use std::collections::{HashMap, HashSet};
 use CargoResult;
 use core::dependency::Kind as DepKind;
 use core::profiles::ProfileFor;
+use core::resolver::FeaturesFor;
 use core::{Package, Target};
 use super::{BuildContext, CompileMode, Kind, Unit};
 
@@ -30,6 +31,8 @@ pub fn build_unit_dependencies<'a, 'cfg>(
 ) -> CargoResult<()> {
     assert!(deps.is_empty(), "can only build unit deps once");
 
+    download_deps(roots, bcx)?;
+
     for unit in roots.iter() {
         // Dependencies of tests/benches should not have `panic` set.
         // We check the global test mode to see if we are running in `cargo
@@ -51,6 +54,46 @@ pub fn build_unit_dependencies<'a, 'cfg>(
     Ok(())
 }
 
+/// Downloads the packages which building `roots` may need, one level of
+/// the dependency graph at a time, so that each level is fetched
+/// concurrently instead of one package at a time as units are created.
+///
+/// This errs on the side of fetching too much, as a dependency which turns
+/// out not to be used has only been downloaded early. Any package missed
+/// here is still downloaded when `compute_deps` asks for it.
+fn download_deps<'a, 'cfg>(roots: &[Unit<'a>], bcx: &BuildContext<'a, 'cfg>) -> CargoResult<()> {
+    let mut seen = HashSet::new();
+    let mut level: Vec<&Package> = roots.iter().map(|unit| unit.pkg).collect();
+    while !level.is_empty() {
+        let mut ids = Vec::new();
+        for pkg in level {
+            let id = pkg.package_id();
+            // Dev-dependencies are only used by tests and examples of the
+            // packages being built.
+            let dev = roots.iter().any(|unit| {
+                unit.pkg.package_id() == id
+                    && (unit.mode.is_any_test() || unit.target.is_test()
+                        || unit.target.is_example())
+            });
+            let build_script = pkg.targets().iter().any(|t| t.is_custom_build());
+            for (dep_id, deps) in bcx.resolve.deps(id) {
+                let used = deps.iter().any(|dep| {
+                    (dep.is_transitive() || dev) && (!dep.is_build() || build_script)
+                        && (bcx.dep_platform_activated(dep, Kind::Host)
+                            || bcx.dep_platform_activated(dep, Kind::Target))
+                        && (!dep.is_optional()
+                            || bcx.resolve.features(id).contains(&*dep.name_in_toml()))
+                });
+                if used && seen.insert(dep_id) {
+                    ids.push(dep_id);
+                }
+            }
+        }
+        level = bcx.packages.get_many(ids)?;
+    }
+    Ok(())
+}
+
 fn deps_of<'a, 'cfg>(
     unit: &Unit<'a>,
     bcx: &BuildContext<'a, 'cfg>,
@@ -118,7 +161,7 @@ fn compute_deps<'a, 'cfg>(
 
             // If the dependency is optional, then we're only activating it
             // if the corresponding feature was activated
-            if dep.is_optional() && !bcx.resolve.features(id).contains(&*dep.name_in_toml()) {
+            if dep.is_optional() && !bcx.unit_features(unit).contains(&*dep.name_in_toml()) {
                 return false;
             }
 
@@ -129,7 +172,20 @@ fn compute_deps<'a, 'cfg>(
     }).filter_map(|(id, _)| match bcx.get_package(id) {
             Ok(pkg) => pkg.targets().iter().find(|t| t.is_lib()).map(|t| {
                 let mode = check_or_build_mode(unit.mode, t);
-                let unit = new_unit(bcx, pkg, t, profile_for, unit.kind.for_target(t), mode);
+                let features = bcx.features.dep_features_for(
+                    unit.features,
+                    unit.target.is_custom_build(),
+                    t.for_host(),
+                );
+                let unit = new_unit(
+                    bcx,
+                    pkg,
+                    t,
+                    profile_for,
+                    unit.kind.for_target(t),
+                    mode,
+                    features,
+                );
                 Ok((unit, profile_for))
             }),
             Err(e) => Some(Err(e)),
@@ -168,7 +224,7 @@ fn compute_deps<'a, 'cfg>(
                     t.is_bin() &&
                         // Skip binaries with required features that have not been selected.
                         t.required_features().unwrap_or(&no_required_features).iter().all(|f| {
-                            bcx.resolve.features(id).contains(f)
+                            bcx.unit_features(unit).contains(f)
                         })
                 })
                 .map(|t| {
@@ -180,6 +236,7 @@ fn compute_deps<'a, 'cfg>(
                             ProfileFor::Any,
                             unit.kind.for_target(t),
                             CompileMode::Build,
+                            unit.features,
                         ),
                         ProfileFor::Any,
                     )
@@ -214,6 +271,9 @@ fn compute_deps_custom_build<'a, 'cfg>(
         ProfileFor::CustomBuild,
         Kind::Host, // build scripts always compiled for the host
         CompileMode::Build,
+        // The script is built with its package's features, it's only its
+        // dependencies which are built for the host.
+        unit.features,
     );
     // All dependencies of this unit should use profiles for custom
     // builds.
@@ -248,6 +308,7 @@ fn compute_deps_doc<'a, 'cfg>(
         // rustdoc only needs rmeta files for regular dependencies.
         // However, for plugins/proc-macros, deps should be built like normal.
         let mode = check_or_build_mode(unit.mode, lib);
+        let features = bcx.features.dep_features_for(unit.features, false, lib.for_host());
         let lib_unit = new_unit(
             bcx,
             dep,
@@ -255,6 +316,7 @@ fn compute_deps_doc<'a, 'cfg>(
             ProfileFor::Any,
             unit.kind.for_target(lib),
             mode,
+            features,
         );
         ret.push((lib_unit, ProfileFor::Any));
         if let CompileMode::Doc { deps: true } = unit.mode {
@@ -266,6 +328,7 @@ fn compute_deps_doc<'a, 'cfg>(
                 ProfileFor::Any,
                 unit.kind.for_target(lib),
                 unit.mode,
+                features,
             );
             ret.push((doc_unit, ProfileFor::Any));
         }
@@ -288,7 +351,15 @@ fn maybe_lib<'a>(
 ) -> Option<(Unit<'a>, ProfileFor)> {
     unit.pkg.targets().iter().find(|t| t.linkable()).map(|t| {
         let mode = check_or_build_mode(unit.mode, t);
-        let unit = new_unit(bcx, unit.pkg, t, profile_for, unit.kind.for_target(t), mode);
+        let unit = new_unit(
+            bcx,
+            unit.pkg,
+            t,
+            profile_for,
+            unit.kind.for_target(t),
+            mode,
+            unit.features,
+        );
         (unit, profile_for)
     })
 }
@@ -315,6 +386,7 @@ fn dep_build_script<'a>(unit: &Unit<'a>, bcx: &BuildContext) -> Option<(Unit<'a>
                     profile: bcx.profiles.get_profile_run_custom_build(&unit.profile),
                     kind: unit.kind,
                     mode: CompileMode::RunCustomBuild,
+                    features: unit.features,
                 },
                 ProfileFor::CustomBuild,
             )
@@ -346,13 +418,14 @@ fn new_unit<'a>(
     profile_for: ProfileFor,
     kind: Kind,
     mode: CompileMode,
+    features: FeaturesFor,
 ) -> Unit<'a> {
     let profile = bcx.profiles.get_profile(
         &pkg.package_id(),
         bcx.ws.is_member(pkg),
         profile_for,
         mode,
-        bcx.build_config.release,
+        &bcx.build_config.profile,
     );
     Unit {
         pkg,
@@ -360,6 +433,7 @@ fn new_unit<'a>(
         profile,
         kind,
         mode,
+        features,
     }
 }
 
@@ -161,7 +161,7 @@ fn build_work<'a, 'cfg>(cx: &mut Context<'a, 'cfg>, unit: &Unit<'a>) -> CargoRes
         .env("OPT_LEVEL", &unit.profile.opt_level.to_string())
         .env(
             "PROFILE",
-            if bcx.build_config.release {
+            if bcx.profiles.inherits_release(&bcx.build_config.profile) {
                 "release"
             } else {
                 "debug"
@@ -182,7 +182,7 @@ fn build_work<'a, 'cfg>(cx: &mut Context<'a, 'cfg>, unit: &Unit<'a>) -> CargoRes
 
     // Be sure to pass along all enabled features for this package, this is the
     // last piece of statically known information that we have.
-    for feat in bcx.resolve.features(unit.pkg.package_id()).iter() {
+    for feat in bcx.unit_features(unit).iter() {
         cmd.env(&format!("CARGO_FEATURE_{}", super::envify(feat)), "1");
     }
 
@@ -125,6 +125,32 @@ pub fn prepare_target<'a, 'cfg>(
     ))
 }
 
+/// Prepares the work to run once the metadata of `unit` is ready, when its
+/// dependents are going to start building before it's finished.
+///
+/// Those dependents record the fingerprint of `unit` in their own, so it's
+/// settled here from the dep-info file rather than when `unit` finishes, or
+/// they'd record a stale one and get rebuilt next time.
+pub fn prepare_metadata_ready<'a, 'cfg>(
+    cx: &mut Context<'a, 'cfg>,
+    unit: &Unit<'a>,
+) -> CargoResult<Work> {
+    let fingerprint = calculate(cx, unit)?;
+    let allow_failure = cx.bcx.extra_args_for(unit).is_some();
+    let target_root = cx.files().target_root().to_path_buf();
+    Ok(Work::new(move |_| match fingerprint.update_local(&target_root) {
+        Ok(()) => Ok(()),
+        Err(..) if allow_failure => Ok(()),
+        Err(e) => Err(e),
+    }))
+}
+
+/// Returns the hash of the fingerprint of `unit`, which keys its outputs in
+/// the shared build cache.
+pub fn unit_hash<'a, 'cfg>(cx: &mut Context<'a, 'cfg>, unit: &Unit<'a>) -> CargoResult<u64> {
+    Ok(calculate(cx, unit)?.hash())
+}
+
 /// A compilation unit dependency has a fingerprint that is comprised of:
 /// * its package id
 /// * its extern crate name
@@ -166,6 +192,8 @@ pub struct Fingerprint {
     memoized_hash: Mutex<Option<u64>>,
     rustflags: Vec<String>,
     edition: Edition,
+    /// The hash of the variables set by the `[env]` config table.
+    config_env: u64,
 }
 
 fn serialize_deps<S>(deps: &[DepFingerprint], ser: S) -> Result<S::Ok, S::Error>
@@ -198,6 +226,7 @@ where
                     memoized_hash: Mutex::new(Some(hash)),
                     edition: Edition::Edition2015,
                     rustflags: Vec::new(),
+                    config_env: 0,
                 }),
             )
         })
@@ -281,6 +310,9 @@ impl Fingerprint {
         if self.edition != old.edition {
             bail!("edition changed")
         }
+        if self.config_env != old.config_env {
+            bail!("the `[env]` config table has changed")
+        }
         for (new, old) in self.local.iter().zip(&old.local) {
             match (new, old) {
                 (
@@ -359,10 +391,11 @@ impl hash::Hash for Fingerprint {
             ref local,
             edition,
             ref rustflags,
+            config_env,
             ..
         } = *self;
         (
-            rustc, features, target, path, profile, local, edition, rustflags,
+            rustc, features, target, path, profile, local, edition, rustflags, config_env,
         ).hash(h);
 
         h.write_usize(deps.len());
@@ -475,12 +508,13 @@ fn calculate<'a, 'cfg>(
         // Note that .0 is hashed here, not .1 which is the cwd. That doesn't
         // actually affect the output artifact so there's no need to hash it.
         path: util::hash_u64(&super::path_args(&cx.bcx, unit).0),
-        features: format!("{:?}", bcx.resolve.features_sorted(unit.pkg.package_id())),
+        features: format!("{:?}", bcx.unit_features_sorted(unit)),
         deps,
         local: vec![local],
         memoized_hash: Mutex::new(None),
         edition: unit.target.edition(),
         rustflags: extra_flags,
+        config_env: util::hash_u64(bcx.config.env_config()?),
     });
     cx.fingerprints.insert(*unit, Arc::clone(&fingerprint));
     Ok(fingerprint)
@@ -534,6 +568,7 @@ pub fn prepare_build_cmd<'a, 'cfg>(
         memoized_hash: Mutex::new(None),
         edition: Edition::Edition2015,
         rustflags: Vec::new(),
+        config_env: util::hash_u64(cx.bcx.config.env_config()?),
     };
     let compare = compare_old_fingerprint(&loc, &fingerprint);
     log_compare(unit, &compare);
@@ -3,15 +3,17 @@ use std::collections::HashSet;
 use std::fmt;
 use std::io;
 use std::mem;
-use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
 use std::sync::Arc;
 use std::process::Output;
+use std::time::Duration;
 
 use crossbeam_utils;
 use crossbeam_utils::thread::Scope;
 use jobserver::{Acquired, HelperThread};
 
 use core::profiles::Profile;
+use core::resolver::FeaturesFor;
 use core::{PackageId, Target, TargetKind};
 use handle_error;
 use util::{internal, profile, CargoResult, CargoResultExt, ProcessBuilder};
@@ -20,7 +22,8 @@ use util::{Progress, ProgressStyle};
 use util::diagnostic_server::{self, DiagnosticPrinter};
 
 use super::job::Job;
-use super::{BuildContext, BuildPlan, CompileMode, Context, Kind, Unit};
+use super::timings::Timings;
+use super::{BuildContext, BuildPlan, CompileMode, Context, Kind, ProfileKind, Unit};
 use super::context::OutputFile;
 
 /// A management structure of the entire dependency graph to compile.
@@ -29,7 +32,7 @@ use super::context::OutputFile;
 /// actual compilation step of each package. Packages enqueue units of work and
 /// then later on the entire graph is processed and compiled.
 pub struct JobQueue<'a> {
-    queue: DependencyQueue<Key<'a>, Vec<(Job, Freshness)>>,
+    queue: DependencyQueue<Key<'a>, Artifact, Vec<(Job, Freshness)>>,
     tx: Sender<Message<'a>>,
     rx: Receiver<Message<'a>>,
     active: Vec<Key<'a>>,
@@ -37,7 +40,10 @@ pub struct JobQueue<'a> {
     compiled: HashSet<&'a PackageId>,
     documented: HashSet<&'a PackageId>,
     counts: HashMap<&'a PackageId, usize>,
-    is_release: bool,
+    profile_kind: ProfileKind,
+    /// The unit each key was enqueued for.
+    units: HashMap<Key<'a>, Unit<'a>>,
+    timings: Timings<'a>,
 }
 
 /// A helper structure for metadata about the state of a building package.
@@ -49,6 +55,16 @@ struct PendingBuild {
     fresh: Freshness,
 }
 
+/// Which of a unit's outputs its dependents wait for.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+enum Artifact {
+    /// Everything, once the unit has finished building.
+    All,
+    /// Just the metadata of a library, which is all that's needed to compile
+    /// another library against it. This is only used with pipelining.
+    Metadata,
+}
+
 #[derive(Clone, Copy, Eq, PartialEq, Hash)]
 struct Key<'a> {
     pkg: &'a PackageId,
@@ -56,6 +72,7 @@ struct Key<'a> {
     profile: Profile,
     kind: Kind,
     mode: CompileMode,
+    features: FeaturesFor,
 }
 
 impl<'a> Key<'a> {
@@ -81,6 +98,7 @@ impl<'a> Key<'a> {
 
 pub struct JobState<'a> {
     tx: Sender<Message<'a>>,
+    key: Key<'a>,
 }
 
 enum Message<'a> {
@@ -90,7 +108,7 @@ enum Message<'a> {
     Stderr(String),
     FixDiagnostic(diagnostic_server::Message),
     Token(io::Result<Acquired>),
-    Finish(Key<'a>, CargoResult<()>),
+    Finish(Key<'a>, Artifact, CargoResult<()>),
 }
 
 impl<'a> JobState<'a> {
@@ -108,6 +126,18 @@ impl<'a> JobState<'a> {
             .send(Message::BuildPlanMsg(module_name, cmd, filenames));
     }
 
+    /// Reports that the metadata of the library being built is ready, so
+    /// that its dependents which only need that can start.
+    pub fn rmeta_produced(&self) {
+        let _ = self.tx
+            .send(Message::Finish(self.key, Artifact::Metadata, Ok(())));
+    }
+
+    /// Prints `msg` to stderr through the main thread's shell.
+    pub fn stderr(&self, msg: &str) {
+        let _ = self.tx.send(Message::Stderr(msg.to_string()));
+    }
+
     pub fn capture_output(
         &self,
         cmd: &ProcessBuilder,
@@ -128,7 +158,7 @@ impl<'a> JobState<'a> {
 }
 
 impl<'a> JobQueue<'a> {
-    pub fn new<'cfg>(bcx: &BuildContext<'a, 'cfg>) -> JobQueue<'a> {
+    pub fn new<'cfg>(bcx: &BuildContext<'a, 'cfg>, root_units: &[Unit<'a>]) -> JobQueue<'a> {
         let (tx, rx) = channel();
         JobQueue {
             queue: DependencyQueue::new(),
@@ -139,7 +169,9 @@ impl<'a> JobQueue<'a> {
             compiled: HashSet::new(),
             documented: HashSet::new(),
             counts: HashMap::new(),
-            is_release: bcx.build_config.release,
+            profile_kind: bcx.build_config.profile.clone(),
+            units: HashMap::new(),
+            timings: Timings::new(bcx, root_units),
         }
     }
 
@@ -151,11 +183,34 @@ impl<'a> JobQueue<'a> {
         fresh: Freshness,
     ) -> CargoResult<()> {
         let key = Key::new(unit);
-        let deps = key.dependencies(cx)?;
+        let deps = dependencies(cx, unit);
+        let mut dep_keys = deps.iter()
+            .map(|dep| {
+                let artifact = if cx.only_requires_rmeta(unit, dep) {
+                    Artifact::Metadata
+                } else {
+                    Artifact::All
+                };
+                (Key::new(dep), artifact)
+            })
+            .collect::<HashMap<_, _>>();
+        // Anything linking its dependencies needs the code of all of them,
+        // not only of the direct ones. A direct dependency may have been
+        // started off the metadata of its own dependencies, and finish before
+        // their code is ready. Without pipelining every dependency finishes
+        // before its dependents start, so the direct ones are enough.
+        if cx.bcx.build_config.pipelining && unit.requires_upstream_objects() {
+            for dep in deps.iter() {
+                depend_on_deps_of_deps(cx, &mut dep_keys, dep);
+            }
+        }
+        let dep_keys = dep_keys.into_iter().collect::<Vec<_>>();
         self.queue
-            .queue(Fresh, &key, Vec::new(), &deps)
+            .queue(Fresh, &key, Vec::new(), &dep_keys)
             .push((job, fresh));
         *self.counts.entry(key.pkg).or_insert(0) += 1;
+        self.units.insert(key, *unit);
+        self.timings.add_unit(unit, deps);
         Ok(())
     }
 
@@ -289,7 +344,22 @@ impl<'a> JobQueue<'a> {
                 .map(Key::name_for_progress)
                 .collect::<Vec<_>>();
             drop(progress.tick_now(count, total, &format!(": {}", active_names.join(", "))));
-            let event = self.rx.recv().unwrap();
+            self.timings
+                .mark_concurrency(self.active.len(), queue.len(), self.queue.len());
+            let event = if self.timings.enabled() {
+                loop {
+                    // Wake up every so often to keep track of CPU usage while
+                    // waiting on long-running jobs.
+                    match self.rx.recv_timeout(Duration::from_millis(500)) {
+                        Ok(event) => break event,
+                        Err(RecvTimeoutError::Timeout) => self.timings.record_cpu(),
+                        Err(RecvTimeoutError::Disconnected) => unreachable!(),
+                    }
+                }
+            } else {
+                self.rx.recv().unwrap()
+            };
+            self.timings.record_cpu();
             progress.clear();
 
             match event {
@@ -313,7 +383,15 @@ impl<'a> JobQueue<'a> {
                 Message::FixDiagnostic(msg) => {
                     print.print(&msg)?;
                 }
-                Message::Finish(key, result) => {
+                Message::Finish(key, Artifact::Metadata, _) => {
+                    info!("metadata: {:?}", key);
+
+                    // The unit is still being built, but anything which only
+                    // needs its metadata can start now.
+                    let fresh = self.pending[&key].fresh;
+                    self.queue.finish_edge(&key, &Artifact::Metadata, fresh);
+                }
+                Message::Finish(key, Artifact::All, result) => {
                     info!("end: {:?}", key);
 
                     // self.active.remove_item(&key); // <- switch to this when stabilized.
@@ -328,7 +406,10 @@ impl<'a> JobQueue<'a> {
                         drop(tokens.pop());
                     }
                     match result {
-                        Ok(()) => self.finish(key, cx)?,
+                        Ok(()) => {
+                            self.timings.unit_finished(&self.units[&key]);
+                            self.finish(key, cx)?
+                        }
                         Err(e) => {
                             let msg = "The following warnings were emitted during compilation:";
                             self.emit_warnings(Some(msg), &key, cx)?;
@@ -353,7 +434,7 @@ impl<'a> JobQueue<'a> {
         }
         drop(progress);
 
-        let build_type = if self.is_release { "release" } else { "dev" };
+        let build_type = self.profile_kind.name();
         // NOTE: This may be a bit inaccurate, since this may not display the
         // profile for what was actually built.  Profile overrides can change
         // these settings, and in some cases different targets are built with
@@ -361,7 +442,7 @@ impl<'a> JobQueue<'a> {
         // list of Units built, and maybe display a list of the different
         // profiles used.  However, to keep it simple and compatible with old
         // behavior, we just display what the base profile is.
-        let profile = cx.bcx.profiles.base_profile(self.is_release);
+        let profile = cx.bcx.profiles.base_profile(&self.profile_kind);
         let mut opt_type = String::from(if profile.opt_level.as_str() == "0" {
             "unoptimized"
         } else {
@@ -389,6 +470,7 @@ impl<'a> JobQueue<'a> {
             );
             if !build_plan {
                 cx.bcx.config.shell().status("Finished", message)?;
+                self.timings.finished(cx.bcx)?;
             }
             Ok(())
         } else if let Some(e) = error {
@@ -414,11 +496,12 @@ impl<'a> JobQueue<'a> {
 
         self.active.push(key);
         *self.counts.get_mut(key.pkg).unwrap() -= 1;
+        self.timings.unit_start(&self.units[&key], fresh == Fresh);
 
         let my_tx = self.tx.clone();
         let doit = move || {
-            let res = job.run(fresh, &JobState { tx: my_tx.clone() });
-            my_tx.send(Message::Finish(key, res)).unwrap();
+            let res = job.run(fresh, &JobState { tx: my_tx.clone(), key });
+            my_tx.send(Message::Finish(key, Artifact::All, res)).unwrap();
         };
         match fresh {
             Freshness::Fresh => doit(),
@@ -533,30 +616,32 @@ impl<'a> Key<'a> {
             profile: unit.profile,
             kind: unit.kind,
             mode: unit.mode,
+            features: unit.features,
         }
     }
+}
 
-    fn dependencies<'cfg>(&self, cx: &Context<'a, 'cfg>) -> CargoResult<Vec<Key<'a>>> {
-        let unit = Unit {
-            pkg: cx.bcx.get_package(self.pkg)?,
-            target: self.target,
-            profile: self.profile,
-            kind: self.kind,
-            mode: self.mode,
-        };
-        let targets = cx.dep_targets(&unit);
-        Ok(targets
-            .iter()
-            .filter_map(|unit| {
-                // Binaries aren't actually needed to *compile* tests, just to run
-                // them, so we don't include this dependency edge in the job graph.
-                if self.target.is_test() && unit.target.is_bin() {
-                    None
-                } else {
-                    Some(Key::new(unit))
-                }
-            })
-            .collect())
+/// The units `unit` has to wait for before it can be built.
+fn dependencies<'a, 'cfg>(cx: &Context<'a, 'cfg>, unit: &Unit<'a>) -> Vec<Unit<'a>> {
+    cx.dep_targets(unit)
+        .into_iter()
+        // Binaries aren't actually needed to *compile* tests, just to run
+        // them, so we don't include this dependency edge in the job graph.
+        .filter(|dep| !(unit.target.is_test() && dep.target.is_bin()))
+        .collect()
+}
+
+/// Makes `deps` wait for all of the output of every transitive dependency of
+/// `unit`.
+fn depend_on_deps_of_deps<'a, 'cfg>(
+    cx: &Context<'a, 'cfg>,
+    deps: &mut HashMap<Key<'a>, Artifact>,
+    unit: &Unit<'a>,
+) {
+    for dep in dependencies(cx, unit) {
+        if deps.insert(Key::new(&dep), Artifact::All).is_none() {
+            depend_on_deps_of_deps(cx, deps, &dep);
+        }
     }
 }
 
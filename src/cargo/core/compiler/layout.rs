@@ -83,7 +83,8 @@ impl Layout {
     /// This function will block if the directory is already locked.
     ///
     /// Differs from `at` in that this calculates the root path from the workspace target directory,
-    /// adding the target triple and the profile (debug, release, ...).
+    /// adding the target triple and the profile (debug, release, or
+    /// the name of a custom profile).
     pub fn new(ws: &Workspace, triple: Option<&str>, dest: &str) -> CargoResult<Layout> {
         let mut path = ws.target_dir();
         // Flexible target specifications often point at filenames, so interpret
@@ -23,7 +23,7 @@ use self::job_queue::JobQueue;
 use self::output_depinfo::output_depinfo;
 
 pub use self::build_context::{BuildContext, FileFlavor, TargetConfig, TargetInfo};
-pub use self::build_config::{BuildConfig, CompileMode, MessageFormat};
+pub use self::build_config::{BuildConfig, CompileMode, MessageFormat, ProfileKind};
 pub use self::compilation::{Compilation, Doctest};
 pub use self::context::{Context, Unit};
 pub use self::custom_build::{BuildMap, BuildOutput, BuildScripts};
@@ -40,6 +40,8 @@ mod job;
 mod job_queue;
 mod layout;
 mod output_depinfo;
+mod shared_cache;
+mod timings;
 
 /// Whether an object is for the host arch, or the target arch.
 ///
@@ -157,7 +159,11 @@ fn compile<'a, 'cfg: 'a>(
         let work = if unit.mode.is_doc() {
             rustdoc(cx, unit)?
         } else {
-            rustc(cx, unit, exec)?
+            let work = rustc(cx, unit, exec)?;
+            match shared_cache::entry(cx, unit)? {
+                Some(entry) => entry.wrap(work),
+                None => work,
+            }
         };
         // Need to link targets on both the dirty and fresh
         let dirty = work.then(link_targets(cx, unit, false)?).then(dirty);
@@ -240,6 +246,11 @@ fn rustc<'a, 'cfg>(
         .to_path_buf();
 
     let should_capture_output = cx.bcx.config.cli_unstable().compile_progress;
+    let mut metadata_ready = if cx.bcx.rmeta_required(unit) {
+        Some(fingerprint::prepare_metadata_ready(cx, unit)?)
+    } else {
+        None
+    };
 
     return Ok(Work::new(move |state| {
         // Only at runtime have we discovered what the extra -L and -l
@@ -277,7 +288,38 @@ fn rustc<'a, 'cfg>(
         }
 
         state.running(&rustc);
-        if json_messages {
+        let mut dep_info_translated = false;
+        if metadata_ready.is_some() {
+            exec.exec_json(
+                rustc,
+                &package_id,
+                &target,
+                mode,
+                &mut assert_is_empty,
+                &mut |line| {
+                    if !pipelined_stderr(line, &package_id, &target, json_messages)? {
+                        return Ok(());
+                    }
+                    // The dep-info is written before the metadata, so the
+                    // fingerprint can be settled now.
+                    if rustc_dep_info_loc.exists() {
+                        fingerprint::translate_dep_info(
+                            &rustc_dep_info_loc,
+                            &dep_info_loc,
+                            &pkg_root,
+                            &cwd,
+                        )?;
+                        dep_info_translated = true;
+                    }
+                    if let Some(work) = metadata_ready.take() {
+                        work.call(state)?;
+                    }
+                    state.rmeta_produced();
+                    Ok(())
+                },
+            ).map_err(Internal::new)
+            .chain_err(|| format!("Could not compile `{}`.", name))?;
+        } else if json_messages {
             exec.exec_json(
                 rustc,
                 &package_id,
@@ -315,7 +357,7 @@ fn rustc<'a, 'cfg>(
             }
         }
 
-        if rustc_dep_info_loc.exists() {
+        if rustc_dep_info_loc.exists() && !dep_info_translated {
             fingerprint::translate_dep_info(&rustc_dep_info_loc, &dep_info_loc, &pkg_root, &cwd)
                 .chain_err(|| {
                     internal(format!(
@@ -393,8 +435,7 @@ fn link_targets<'a, 'cfg>(
     let target = unit.target.clone();
     let profile = unit.profile;
     let unit_mode = unit.mode;
-    let features = bcx.resolve
-        .features_sorted(&package_id)
+    let features = bcx.unit_features_sorted(unit)
         .into_iter()
         .map(|s| s.to_owned())
         .collect();
@@ -601,11 +642,11 @@ fn rustdoc<'a, 'cfg>(cx: &mut Context<'a, 'cfg>, unit: &Unit<'a>) -> CargoResult
 
     rustdoc.arg("-o").arg(doc_dir);
 
-    for feat in bcx.resolve.features_sorted(unit.pkg.package_id()) {
+    for feat in bcx.unit_features_sorted(unit) {
         rustdoc.arg("--cfg").arg(&format!("feature=\"{}\"", feat));
     }
 
-    add_error_format(bcx, &mut rustdoc);
+    add_error_format(bcx, unit, &mut rustdoc);
 
     if let Some(ref args) = bcx.extra_args_for(unit) {
         rustdoc.args(args);
@@ -704,7 +745,24 @@ fn add_color(bcx: &BuildContext, cmd: &mut ProcessBuilder) {
     }
 }
 
-fn add_error_format(bcx: &BuildContext, cmd: &mut ProcessBuilder) {
+fn add_error_format(bcx: &BuildContext, unit: &Unit, cmd: &mut ProcessBuilder) {
+    // When pipelining, rustc reports when the metadata is ready with a JSON
+    // artifact notification, which means its diagnostics have to be JSON too.
+    // They're rendered again in `pipelined_stderr` if that's not what was
+    // asked for.
+    if bcx.rmeta_required(unit) {
+        cmd.arg("--error-format").arg("json");
+        let mut json = String::from("--json=artifacts");
+        match bcx.build_config.message_format {
+            MessageFormat::Human => if bcx.config.shell().supports_color() {
+                json.push_str(",diagnostic-rendered-ansi");
+            },
+            MessageFormat::Json => (),
+            MessageFormat::Short => json.push_str(",diagnostic-short"),
+        }
+        cmd.arg(json);
+        return;
+    }
     match bcx.build_config.message_format {
         MessageFormat::Human => (),
         MessageFormat::Json => { cmd.arg("--error-format").arg("json"); },
@@ -738,7 +796,7 @@ fn build_base_args<'a, 'cfg>(
 
     add_path_args(bcx, unit, cmd);
     add_color(bcx, cmd);
-    add_error_format(bcx, cmd);
+    add_error_format(bcx, unit, cmd);
 
     if !test {
         for crate_type in crate_types.iter() {
@@ -748,6 +806,8 @@ fn build_base_args<'a, 'cfg>(
 
     if unit.mode.is_check() {
         cmd.arg("--emit=dep-info,metadata");
+    } else if bcx.rmeta_required(unit) {
+        cmd.arg("--emit=dep-info,metadata,link");
     } else {
         cmd.arg("--emit=dep-info,link");
     }
@@ -836,7 +896,7 @@ fn build_base_args<'a, 'cfg>(
     // We ideally want deterministic invocations of rustc to ensure that
     // rustc-caching strategies like sccache are able to cache more, so sort the
     // feature list here.
-    for feat in bcx.resolve.features_sorted(unit.pkg.package_id()) {
+    for feat in bcx.unit_features_sorted(unit) {
         cmd.arg("--cfg").arg(&format!("feature=\"{}\"", feat));
     }
 
@@ -954,8 +1014,15 @@ fn build_deps_args<'a, 'cfg>(
         dep: &Unit<'a>,
     ) -> CargoResult<()> {
         let bcx = cx.bcx;
+        // With pipelining, libraries only need the metadata of their
+        // dependencies, which is ready before the rlib is.
+        let flavor = if cx.only_requires_rmeta(current, dep) {
+            FileFlavor::Rmeta
+        } else {
+            FileFlavor::Linkable
+        };
         for output in cx.outputs(dep)?.iter() {
-            if output.flavor != FileFlavor::Linkable {
+            if output.flavor != flavor {
                 continue;
             }
             let mut v = OsString::new();
@@ -1002,6 +1069,38 @@ fn assert_is_empty(line: &str) -> CargoResult<()> {
     }
 }
 
+/// Handles a line of stderr from a rustc run which reports when its metadata
+/// is ready, returning whether this line was that report.
+///
+/// Diagnostics from such a run are always JSON, so they're printed as rustc
+/// rendered them unless `--message-format json` was asked for.
+fn pipelined_stderr(
+    line: &str,
+    package_id: &PackageId,
+    target: &Target,
+    json_messages: bool,
+) -> CargoResult<bool> {
+    if !line.starts_with('{') {
+        writeln!(io::stderr(), "{}", line)?;
+        return Ok(false);
+    }
+    let message: serde_json::Value = serde_json::from_str(line)
+        .map_err(|_| internal(&format!("compiler produced invalid json: `{}`", line)))?;
+    if message.get("artifact").is_some() {
+        return Ok(message.get("emit").and_then(|e| e.as_str()) == Some("metadata"));
+    }
+    if json_messages {
+        machine_message::emit(&machine_message::FromCompiler {
+            package_id,
+            target,
+            message,
+        });
+    } else if let Some(rendered) = message.get("rendered").and_then(|r| r.as_str()) {
+        write!(io::stderr(), "{}", rendered)?;
+    }
+    Ok(false)
+}
+
 fn json_stderr(line: &str, package_id: &PackageId, target: &Target) -> CargoResult<()> {
     // stderr from rustc/rustdoc can have a mix of JSON and non-JSON output
     if line.starts_with('{') {
@@ -0,0 +1,167 @@
+//! A cache of build artifacts shared between projects.
+//!
+//! When `build.shared-cache` is set, the outputs of libraries which come from
+//! a registry are stored in that directory once they're built, keyed by the
+//! fingerprint of their unit. Any later build, from any project, which needs
+//! a unit with the same key copies the outputs from the cache into its own
+//! `deps` directory instead of invoking rustc.
+//!
+//! Only units whose outputs are fully determined by their fingerprint are
+//! cached: libraries from a registry without a build script, all of whose
+//! dependencies can be cached too. Anything else, e.g. a build script's
+//! output, isn't tracked by the fingerprint of a library.
+//!
+//! Every entry has a lock file next to it, which is held exclusively while
+//! an entry is published and shared while one is restored, so the cache can
+//! be used by several Cargo processes at once.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use util::errors::{CargoResult, CargoResultExt};
+use util::{self, paths, Filesystem};
+
+use super::context::{Context, Unit};
+use super::fingerprint;
+use super::job::Work;
+use super::job_queue::JobState;
+use super::{CompileMode, FileFlavor};
+
+/// An entry of the shared cache, for the outputs of one unit.
+pub struct Entry {
+    root: Filesystem,
+    key: String,
+    outputs: Vec<PathBuf>,
+}
+
+/// Returns the entry of the shared cache for `unit`, or `None` if there is no
+/// shared cache or `unit` can't be cached.
+pub fn entry<'a, 'cfg>(cx: &mut Context<'a, 'cfg>, unit: &Unit<'a>) -> CargoResult<Option<Entry>> {
+    let root = match cx.bcx.build_config.shared_cache {
+        Some(ref root) => Filesystem::new(root.clone()),
+        None => return Ok(None),
+    };
+    if !cacheable(cx, unit, &mut HashMap::new()) {
+        return Ok(None);
+    }
+
+    let outputs = cx.outputs(unit)?
+        .iter()
+        .filter(|output| output.flavor != FileFlavor::DebugInfo)
+        .map(|output| output.path.clone())
+        .collect::<Vec<_>>();
+    // The file names carry the metadata hash, which dependents refer to, so
+    // they're part of the key as well as everything in the fingerprint.
+    let names = outputs
+        .iter()
+        .map(|path| path.file_name().unwrap().to_os_string())
+        .collect::<Vec<_>>();
+    let key = util::short_hash(&(
+        fingerprint::unit_hash(cx, unit)?,
+        unit.kind,
+        cx.bcx.target_triple(),
+        names,
+    ));
+    Ok(Some(Entry { root, key, outputs }))
+}
+
+fn cacheable<'a, 'cfg>(
+    cx: &mut Context<'a, 'cfg>,
+    unit: &Unit<'a>,
+    memo: &mut HashMap<Unit<'a>, bool>,
+) -> bool {
+    if let Some(&cacheable) = memo.get(unit) {
+        return cacheable;
+    }
+    let mut cacheable = unit.mode == CompileMode::Build
+        && unit.target.is_lib()
+        && unit.pkg.package_id().source_id().is_registry()
+        && !unit.pkg.has_custom_build()
+        && cx.bcx.extra_args_for(unit).is_none();
+    if cacheable {
+        let deps = cx.dep_targets(unit);
+        cacheable = deps.iter()
+            .filter(|dep| !dep.target.is_bin())
+            .all(|dep| self::cacheable(cx, dep, memo));
+    }
+    memo.insert(*unit, cacheable);
+    cacheable
+}
+
+impl Entry {
+    /// Wraps `work`, which builds the unit, to restore the outputs from the
+    /// cache instead if they're there, or to publish them once it's done.
+    pub fn wrap(self, work: Work) -> Work {
+        Work::new(move |state| {
+            if self.restore(state)? {
+                return Ok(());
+            }
+            work.call(state)?;
+            self.publish(state)
+                .chain_err(|| "failed to publish outputs to the shared build cache")
+        })
+    }
+
+    fn restore(&self, state: &JobState) -> CargoResult<bool> {
+        let dir = self.root.join(&self.key).into_path_unlocked();
+        if !dir.exists() {
+            return Ok(false);
+        }
+        let _lock = self.root
+            .open_ro_with(self.lock_name(), &|| blocking(state))?;
+        let files = self.outputs
+            .iter()
+            .map(|dst| (dir.join(dst.file_name().unwrap()), dst))
+            .collect::<Vec<_>>();
+        if files.iter().any(|&(ref src, _)| !src.exists()) {
+            return Ok(false);
+        }
+        for (src, dst) in files {
+            debug!("restoring {} from the shared cache", dst.display());
+            super::hardlink_or_copy(&src, dst)?;
+        }
+        Ok(true)
+    }
+
+    fn publish(&self, state: &JobState) -> CargoResult<()> {
+        let _lock = self.root
+            .open_rw_with(self.lock_name(), &|| blocking(state))?;
+        let dir = self.root.join(&self.key).into_path_unlocked();
+        if dir.exists() {
+            return Ok(());
+        }
+        // Nobody else can be writing this entry while we hold the lock, but
+        // a previous attempt may have been interrupted, so the outputs are
+        // gathered in a scratch directory which is only moved into place
+        // once it's complete.
+        let tmp = self.root
+            .join(format!("{}.tmp", self.key))
+            .into_path_unlocked();
+        if tmp.exists() {
+            paths::remove_dir_all(&tmp)?;
+        }
+        fs::create_dir_all(&tmp)?;
+        for src in self.outputs.iter() {
+            if !src.exists() {
+                continue;
+            }
+            super::hardlink_or_copy(src, &tmp.join(src.file_name().unwrap()))?;
+        }
+        fs::rename(&tmp, &dir)
+            .chain_err(|| format!("failed to rename `{}`", tmp.display()))?;
+        Ok(())
+    }
+
+    fn lock_name(&self) -> String {
+        format!("{}.lock", self.key)
+    }
+}
+
+fn blocking(state: &JobState) -> CargoResult<()> {
+    state.stderr(&format!(
+        "{:>12} waiting for file lock on the shared build cache",
+        "Blocking"
+    ));
+    Ok(())
+}
@@ -0,0 +1,700 @@
+//! Timing information for `--timings`.
+//!
+//! While the job queue runs, this records when each unit starts and finishes,
+//! how many units are building or waiting over time, and how busy the CPU is.
+//! Once the build is done it's written out to `target/cargo-timings/` as an
+//! HTML report, with a Gantt chart of the units, the critical path through
+//! them and charts of concurrency and CPU usage, along with the same data as
+//! JSON for other tools to consume.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::fs;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde_json;
+
+use core::PackageId;
+use util::errors::{CargoResult, CargoResultExt};
+use util::{machine_message, paths};
+
+use super::{BuildContext, CompileMode, Kind, Unit};
+
+pub struct Timings<'a> {
+    /// Whether `--timings` was passed. Nothing is recorded otherwise.
+    enabled: bool,
+    /// Whether to emit a machine message as each unit finishes.
+    json_messages: bool,
+    start: Instant,
+    /// When the build started, like `2018-08-28T17:10:42Z`.
+    start_str: String,
+    /// The packages and targets that were asked for.
+    root_targets: Vec<String>,
+    /// `dev` or `release`.
+    profile: String,
+    jobs: u32,
+    /// How many units were fresh, and so never show up in `unit_times`.
+    total_fresh: u32,
+    unit_times: Vec<UnitTime<'a>>,
+    /// The index in `unit_times` of each unit which is running.
+    active: HashMap<Unit<'a>, usize>,
+    /// The units each unit had to wait for before it could start.
+    deps: HashMap<Unit<'a>, Vec<Unit<'a>>>,
+    concurrency: Vec<Concurrency>,
+    last_cpu_state: Option<cpu::State>,
+    last_cpu_recording: Instant,
+    /// Samples of `(seconds since start, percent of the CPU busy)`.
+    cpu_usage: Vec<(f64, f64)>,
+}
+
+/// When a unit was built, and how long it took.
+struct UnitTime<'a> {
+    unit: Unit<'a>,
+    /// Seconds since the start of the build.
+    start: f64,
+    duration: f64,
+}
+
+/// How many units were in each state at some point during the build.
+#[derive(Serialize)]
+struct Concurrency {
+    /// Seconds since the start of the build.
+    t: f64,
+    /// Units being built.
+    active: usize,
+    /// Units whose dependencies are done, but which are waiting for a job
+    /// slot to free up.
+    waiting: usize,
+    /// Units still waiting on their dependencies.
+    inactive: usize,
+}
+
+#[derive(Serialize)]
+struct Report<'a> {
+    start_time: &'a str,
+    total_time: f64,
+    profile: &'a str,
+    jobs: u32,
+    rustc: &'a str,
+    targets: &'a [String],
+    fresh_units: u32,
+    units: Vec<ReportUnit<'a>>,
+    /// Indices into `units`, from the first unit of the critical path to the
+    /// last.
+    critical_path: Vec<usize>,
+    concurrency: &'a [Concurrency],
+    cpu_usage: &'a [(f64, f64)],
+}
+
+#[derive(Serialize)]
+struct ReportUnit<'a> {
+    package_id: &'a PackageId,
+    target: &'a str,
+    description: String,
+    mode: CompileMode,
+    kind: Kind,
+    start: f64,
+    duration: f64,
+    /// Indices into `units` of the dependencies which had to be built.
+    deps: Vec<usize>,
+}
+
+impl<'a> Timings<'a> {
+    pub fn new<'cfg>(bcx: &BuildContext<'a, 'cfg>, root_units: &[Unit<'a>]) -> Timings<'a> {
+        let mut root_targets: Vec<String> = Vec::new();
+        for unit in root_units {
+            let target = format!(
+                "{} v{}{}",
+                unit.pkg.name(),
+                unit.pkg.version(),
+                target_description(unit)
+            );
+            if !root_targets.contains(&target) {
+                root_targets.push(target);
+            }
+        }
+        let start_secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let profile = bcx.build_config.profile.name();
+        let enabled = bcx.build_config.timings;
+        // Reading the CPU state isn't free, so it's only done when it's used.
+        let last_cpu_state = if enabled { cpu::current() } else { None };
+        Timings {
+            enabled,
+            json_messages: bcx.build_config.json_messages(),
+            start: bcx.config.creation_time(),
+            start_str: format_timestamp(start_secs),
+            root_targets,
+            profile: profile.to_string(),
+            jobs: bcx.build_config.jobs,
+            total_fresh: 0,
+            unit_times: Vec::new(),
+            active: HashMap::new(),
+            deps: HashMap::new(),
+            concurrency: Vec::new(),
+            last_cpu_state,
+            last_cpu_recording: Instant::now(),
+            cpu_usage: Vec::new(),
+        }
+    }
+
+    /// Whether `--timings` was passed, so anything is recorded at all.
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Records which units `unit` has to wait for, to work out the critical
+    /// path later.
+    pub fn add_unit(&mut self, unit: &Unit<'a>, deps: Vec<Unit<'a>>) {
+        if !self.enabled {
+            return;
+        }
+        self.deps.insert(*unit, deps);
+    }
+
+    /// Marks that `unit` has started. Fresh units finish immediately, so
+    /// they're only counted.
+    pub fn unit_start(&mut self, unit: &Unit<'a>, fresh: bool) {
+        if !self.enabled {
+            return;
+        }
+        if fresh {
+            self.total_fresh += 1;
+            return;
+        }
+        self.active.insert(*unit, self.unit_times.len());
+        let start = secs(self.start.elapsed());
+        self.unit_times.push(UnitTime {
+            unit: *unit,
+            start,
+            duration: 0.0,
+        });
+    }
+
+    /// Marks that `unit` has finished building.
+    pub fn unit_finished(&mut self, unit: &Unit<'a>) {
+        if !self.enabled {
+            return;
+        }
+        let index = match self.active.remove(unit) {
+            Some(index) => index,
+            None => return,
+        };
+        let unit_time = &mut self.unit_times[index];
+        unit_time.duration = secs(self.start.elapsed()) - unit_time.start;
+        if self.json_messages {
+            machine_message::emit(&machine_message::TimingInfo {
+                package_id: unit.pkg.package_id(),
+                target: unit.target,
+                mode: unit.mode,
+                duration: unit_time.duration,
+            });
+        }
+    }
+
+    /// Records how many units are building, waiting for a job slot, and
+    /// waiting on dependencies.
+    pub fn mark_concurrency(&mut self, active: usize, waiting: usize, inactive: usize) {
+        if !self.enabled {
+            return;
+        }
+        self.concurrency.push(Concurrency {
+            t: secs(self.start.elapsed()),
+            active,
+            waiting,
+            inactive,
+        });
+    }
+
+    /// Takes a sample of the CPU usage, if enough time has passed since the
+    /// last one.
+    pub fn record_cpu(&mut self) {
+        if !self.enabled {
+            return;
+        }
+        if self.last_cpu_recording.elapsed() < Duration::from_millis(100) {
+            return;
+        }
+        let current = match cpu::current() {
+            Some(current) => current,
+            None => return,
+        };
+        if let Some(ref prev) = self.last_cpu_state {
+            let pct = cpu::pct_busy(prev, &current);
+            self.cpu_usage.push((secs(self.start.elapsed()), pct));
+        }
+        self.last_cpu_state = Some(current);
+        self.last_cpu_recording = Instant::now();
+    }
+
+    /// Writes out the report, once everything has been built.
+    pub fn finished(&mut self, bcx: &BuildContext) -> CargoResult<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+        self.record_cpu();
+        let total_time = secs(self.start.elapsed());
+        let rustc = bcx.rustc.verbose_version.lines().next().unwrap_or("");
+        let report = Report {
+            start_time: &self.start_str,
+            total_time,
+            profile: &self.profile,
+            jobs: self.jobs,
+            rustc,
+            targets: &self.root_targets,
+            fresh_units: self.total_fresh,
+            units: self.report_units(),
+            critical_path: self.critical_path(),
+            concurrency: &self.concurrency,
+            cpu_usage: &self.cpu_usage,
+        };
+
+        let dir = bcx.ws.target_dir().join("cargo-timings").into_path_unlocked();
+        fs::create_dir_all(&dir)
+            .chain_err(|| format!("failed to create directory `{}`", dir.display()))?;
+        let stamp = self.start_str.replace("-", "").replace(":", "");
+        let html = render_html(&report);
+        let json = serde_json::to_string(&report)?;
+        let html_path = dir.join(format!("cargo-timing-{}.html", stamp));
+        paths::write(&html_path, html.as_bytes())?;
+        paths::write(&dir.join(format!("cargo-timing-{}.json", stamp)), json.as_bytes())?;
+        // Also keep a copy under a fixed name, so the latest report is easy
+        // to find.
+        paths::write(&dir.join("cargo-timing.html"), html.as_bytes())?;
+        paths::write(&dir.join("cargo-timing.json"), json.as_bytes())?;
+
+        bcx.config.shell().status(
+            "Timing",
+            format!("report saved to {}", html_path.display()),
+        )?;
+        Ok(())
+    }
+
+    fn report_units(&self) -> Vec<ReportUnit<'a>> {
+        let indices = self.indices();
+        self.unit_times
+            .iter()
+            .map(|ut| ReportUnit {
+                package_id: ut.unit.pkg.package_id(),
+                target: ut.unit.target.name(),
+                description: format!(
+                    "{} v{}{}",
+                    ut.unit.pkg.name(),
+                    ut.unit.pkg.version(),
+                    target_description(&ut.unit)
+                ),
+                mode: ut.unit.mode,
+                kind: ut.unit.kind,
+                start: ut.start,
+                duration: ut.duration,
+                deps: self.built_deps(&ut.unit, &indices),
+            })
+            .collect()
+    }
+
+    fn indices(&self) -> HashMap<Unit<'a>, usize> {
+        self.unit_times
+            .iter()
+            .enumerate()
+            .map(|(i, ut)| (ut.unit, i))
+            .collect()
+    }
+
+    /// The indices of the dependencies of `unit` which weren't fresh.
+    fn built_deps(&self, unit: &Unit<'a>, indices: &HashMap<Unit<'a>, usize>) -> Vec<usize> {
+        let mut deps = self.deps
+            .get(unit)
+            .map(|deps| {
+                deps.iter()
+                    .filter_map(|dep| indices.get(dep).cloned())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        deps.sort();
+        deps.dedup();
+        deps
+    }
+
+    /// The chain of units, each waiting on the one before, which took the
+    /// longest to build. Making any of these faster makes the whole build
+    /// faster, assuming enough parallelism.
+    fn critical_path(&self) -> Vec<usize> {
+        let indices = self.indices();
+        // Dependencies always start before the units waiting on them, so
+        // going by start time visits them first.
+        let mut order = (0..self.unit_times.len()).collect::<Vec<_>>();
+        order.sort_by(|&a, &b| {
+            self.unit_times[a]
+                .start
+                .partial_cmp(&self.unit_times[b].start)
+                .unwrap()
+        });
+        // For each unit, the time at which the longest chain ending with it
+        // finishes, and the unit before it in that chain.
+        let mut longest: Vec<(f64, Option<usize>)> = vec![(0.0, None); self.unit_times.len()];
+        for &i in order.iter() {
+            let (before, prev) = self.built_deps(&self.unit_times[i].unit, &indices)
+                .into_iter()
+                .map(|dep| (longest[dep].0, Some(dep)))
+                .fold((0.0, None), |a, b| if b.0 > a.0 { b } else { a });
+            longest[i] = (before + self.unit_times[i].duration, prev);
+        }
+
+        let mut path = Vec::new();
+        let mut cur = (0..longest.len()).fold(None, |max: Option<usize>, i| match max {
+            Some(m) if longest[m].0 >= longest[i].0 => Some(m),
+            _ => Some(i),
+        });
+        while let Some(i) = cur {
+            path.push(i);
+            cur = longest[i].1;
+        }
+        path.reverse();
+        path
+    }
+}
+
+fn secs(d: Duration) -> f64 {
+    d.as_secs() as f64 + f64::from(d.subsec_nanos()) / 1_000_000_000.0
+}
+
+/// Describes which part of its package a unit builds, like ` bin "foo"` or
+/// ` build script (run)`. Libraries are left undescribed.
+fn target_description(unit: &Unit) -> String {
+    let target = unit.target;
+    let mut desc = if unit.mode.is_run_custom_build() {
+        " build script (run)".to_string()
+    } else if target.is_custom_build() {
+        " build script".to_string()
+    } else if target.is_lib() {
+        String::new()
+    } else if target.is_bin() {
+        format!(" bin \"{}\"", target.name())
+    } else if target.is_test() {
+        format!(" test \"{}\"", target.name())
+    } else if target.is_bench() {
+        format!(" bench \"{}\"", target.name())
+    } else {
+        format!(" example \"{}\"", target.name())
+    };
+    match unit.mode {
+        CompileMode::Test | CompileMode::Bench if target.is_lib() || target.is_bin() => {
+            desc.push_str(" (test)")
+        }
+        CompileMode::Check { .. } => desc.push_str(" (check)"),
+        CompileMode::Doc { .. } => desc.push_str(" (doc)"),
+        CompileMode::Doctest => desc.push_str(" (doctest)"),
+        _ => {}
+    }
+    desc
+}
+
+/// Formats seconds since the Unix epoch as a UTC timestamp, like
+/// `2018-08-28T17:10:42Z`.
+fn format_timestamp(secs: u64) -> String {
+    // Converts days since the epoch to a date in the proleptic Gregorian
+    // calendar, see http://howardhinnant.github.io/date_algorithms.html.
+    let z = (secs / 86_400) as i64 + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    let rem = secs % 86_400;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+const CHART_WIDTH: f64 = 800.0;
+const LABEL_WIDTH: f64 = 300.0;
+const ROW_HEIGHT: f64 = 20.0;
+const GRAPH_HEIGHT: f64 = 200.0;
+
+fn render_html(report: &Report) -> String {
+    let mut html = String::new();
+    let total = report.total_time.max(0.01);
+    let critical_time = report
+        .critical_path
+        .iter()
+        .map(|&i| report.units[i].duration)
+        .sum::<f64>();
+    let max_concurrency = report
+        .concurrency
+        .iter()
+        .map(|c| c.active)
+        .max()
+        .unwrap_or(0);
+
+    html.push_str(HTML_HEADER);
+    let _ = write!(
+        html,
+        "<h1>Cargo Build Timings</h1>\n\
+         <table class=\"summary\">\n\
+         <tr><td>Targets:</td><td>{}</td></tr>\n\
+         <tr><td>Profile:</td><td>{}</td></tr>\n\
+         <tr><td>Started:</td><td>{}</td></tr>\n\
+         <tr><td>Total time:</td><td>{:.1}s</td></tr>\n\
+         <tr><td>Fresh units:</td><td>{}</td></tr>\n\
+         <tr><td>Dirty units:</td><td>{}</td></tr>\n\
+         <tr><td>Jobs:</td><td>{}</td></tr>\n\
+         <tr><td>Max concurrency:</td><td>{}</td></tr>\n\
+         <tr><td>Critical path:</td><td>{:.1}s</td></tr>\n\
+         <tr><td>rustc:</td><td>{}</td></tr>\n\
+         </table>\n",
+        escape(&report.targets.join("<br>")).replace("&lt;br&gt;", "<br>"),
+        report.profile,
+        report.start_time,
+        report.total_time,
+        report.fresh_units,
+        report.units.len(),
+        report.jobs,
+        max_concurrency,
+        critical_time,
+        escape(report.rustc),
+    );
+
+    // A Gantt chart of every unit which was built, in the order they
+    // started, with the critical path highlighted.
+    let mut order = (0..report.units.len()).collect::<Vec<_>>();
+    order.sort_by(|&a, &b| {
+        report.units[a]
+            .start
+            .partial_cmp(&report.units[b].start)
+            .unwrap()
+    });
+    let height = ROW_HEIGHT * order.len() as f64 + 30.0;
+    let _ = write!(
+        html,
+        "<h2>Units</h2>\n<svg width=\"{}\" height=\"{}\">\n",
+        LABEL_WIDTH + CHART_WIDTH + 10.0,
+        height
+    );
+    render_time_axis(&mut html, total, height - 20.0);
+    for (row, &i) in order.iter().enumerate() {
+        let unit = &report.units[i];
+        let y = ROW_HEIGHT * row as f64;
+        let class = if report.critical_path.contains(&i) {
+            "critical"
+        } else if unit.mode == CompileMode::RunCustomBuild {
+            "build-script"
+        } else {
+            "unit"
+        };
+        let _ = write!(
+            html,
+            "<text x=\"0\" y=\"{:.1}\">{}</text>\n\
+             <rect class=\"{}\" x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\">\
+             <title>{} {:.2}s</title></rect>\n",
+            y + 14.0,
+            escape(&unit.description),
+            class,
+            LABEL_WIDTH + unit.start / total * CHART_WIDTH,
+            y + 2.0,
+            (unit.duration / total * CHART_WIDTH).max(1.0),
+            ROW_HEIGHT - 4.0,
+            escape(&unit.description),
+            unit.duration,
+        );
+    }
+    html.push_str("</svg>\n");
+
+    // How busy the build was over time.
+    let max_units = report
+        .concurrency
+        .iter()
+        .map(|c| c.active.max(c.waiting).max(c.inactive))
+        .max()
+        .unwrap_or(0)
+        .max(1) as f64;
+    let _ = write!(
+        html,
+        "<h2>Concurrency</h2>\n\
+         <p><span class=\"active\">&#9632;</span> building \
+         <span class=\"waiting\">&#9632;</span> waiting for a job slot \
+         <span class=\"inactive\">&#9632;</span> waiting on dependencies \
+         <span class=\"cpu\">&#9632;</span> CPU usage (0-100%)</p>\n\
+         <svg width=\"{}\" height=\"{}\">\n",
+        LABEL_WIDTH + CHART_WIDTH + 10.0,
+        GRAPH_HEIGHT + 30.0
+    );
+    render_time_axis(&mut html, total, GRAPH_HEIGHT + 10.0);
+    let line = |class: &str, points: Vec<(f64, f64)>, max: f64| {
+        let points = points
+            .iter()
+            .map(|&(t, v)| {
+                format!(
+                    "{:.1},{:.1}",
+                    LABEL_WIDTH + t / total * CHART_WIDTH,
+                    GRAPH_HEIGHT - v / max * GRAPH_HEIGHT + 10.0
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        format!("<polyline class=\"{}\" points=\"{}\"/>\n", class, points)
+    };
+    let series = |f: &Fn(&Concurrency) -> usize| {
+        report
+            .concurrency
+            .iter()
+            .map(|c| (c.t, f(c) as f64))
+            .collect::<Vec<_>>()
+    };
+    html.push_str(&line("active", series(&|c| c.active), max_units));
+    html.push_str(&line("waiting", series(&|c| c.waiting), max_units));
+    html.push_str(&line("inactive", series(&|c| c.inactive), max_units));
+    html.push_str(&line("cpu", report.cpu_usage.to_vec(), 100.0));
+    let _ = write!(
+        html,
+        "<text x=\"{}\" y=\"20\">{} units</text>\n</svg>\n",
+        LABEL_WIDTH - 60.0,
+        max_units
+    );
+
+    // Every unit, slowest first.
+    let mut by_duration = (0..report.units.len()).collect::<Vec<_>>();
+    by_duration.sort_by(|&a, &b| {
+        report.units[b]
+            .duration
+            .partial_cmp(&report.units[a].duration)
+            .unwrap()
+    });
+    html.push_str(
+        "<h2>Unit times</h2>\n<table class=\"units\">\n\
+         <tr><th></th><th>Unit</th><th>Total</th><th>Critical path</th></tr>\n",
+    );
+    for (n, &i) in by_duration.iter().enumerate() {
+        let unit = &report.units[i];
+        let _ = write!(
+            html,
+            "<tr><td>{}.</td><td>{}</td><td>{:.1}s</td><td>{}</td></tr>\n",
+            n + 1,
+            escape(&unit.description),
+            unit.duration,
+            if report.critical_path.contains(&i) {
+                "yes"
+            } else {
+                ""
+            }
+        );
+    }
+    html.push_str("</table>\n</body>\n</html>\n");
+    html
+}
+
+/// Draws a vertical line and label every so many seconds.
+fn render_time_axis(html: &mut String, total: f64, height: f64) {
+    let step = [1.0, 2.0, 5.0, 10.0, 20.0, 30.0, 60.0, 120.0, 300.0, 600.0]
+        .iter()
+        .cloned()
+        .find(|step| total / step <= 10.0)
+        .unwrap_or(1200.0);
+    let mut t = 0.0;
+    while t <= total {
+        let x = LABEL_WIDTH + t / total * CHART_WIDTH;
+        let _ = write!(
+            html,
+            "<line class=\"tick\" x1=\"{0:.1}\" y1=\"0\" x2=\"{0:.1}\" y2=\"{1:.1}\"/>\n\
+             <text x=\"{0:.1}\" y=\"{2:.1}\">{3}s</text>\n",
+            x,
+            height,
+            height + 15.0,
+            t
+        );
+        t += step;
+    }
+}
+
+const HTML_HEADER: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Cargo Build Timings</title>
+<style>
+body { font-family: sans-serif; }
+table.summary td:first-child { font-weight: bold; padding-right: 1em; }
+table.units td, table.units th { padding: 0 1em 0 0; text-align: left; }
+svg text { font-size: 12px; }
+line.tick { stroke: #ddd; }
+rect.unit { fill: #4e91d1; }
+rect.build-script { fill: #e8b93f; }
+rect.critical { fill: #e05d44; }
+polyline { fill: none; stroke-width: 2; }
+polyline.active, span.active { stroke: #4e91d1; color: #4e91d1; }
+polyline.waiting, span.waiting { stroke: #e8b93f; color: #e8b93f; }
+polyline.inactive, span.inactive { stroke: #999; color: #999; }
+polyline.cpu, span.cpu { stroke: #5cb85c; color: #5cb85c; }
+</style>
+</head>
+<body>
+"#;
+
+#[cfg(target_os = "linux")]
+mod cpu {
+    use util::paths;
+
+    /// Cumulative CPU time from `/proc/stat`, in clock ticks.
+    pub struct State {
+        idle: u64,
+        total: u64,
+    }
+
+    pub fn current() -> Option<State> {
+        let stat = paths::read("/proc/stat".as_ref()).ok()?;
+        let line = stat.lines().find(|line| line.starts_with("cpu "))?;
+        let fields = line.split_whitespace()
+            .skip(1)
+            .map(|field| field.parse::<u64>().ok())
+            .collect::<Option<Vec<_>>>()?;
+        // The fields are user, nice, system, idle, iowait, and then time
+        // spent on interrupts and virtualization.
+        if fields.len() < 5 {
+            return None;
+        }
+        Some(State {
+            idle: fields[3] + fields[4],
+            total: fields.iter().sum(),
+        })
+    }
+
+    pub fn pct_busy(prev: &State, next: &State) -> f64 {
+        let total = next.total.saturating_sub(prev.total);
+        if total == 0 {
+            return 0.0;
+        }
+        let idle = next.idle.saturating_sub(prev.idle);
+        100.0 - idle as f64 / total as f64 * 100.0
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod cpu {
+    /// CPU usage isn't recorded on this platform.
+    pub struct State;
+
+    pub fn current() -> Option<State> {
+        None
+    }
+
+    pub fn pct_busy(_prev: &State, _next: &State) -> f64 {
+        0.0
+    }
+}
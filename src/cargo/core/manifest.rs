@@ -11,6 +11,7 @@ use url::Url;
 
 use core::interning::InternedString;
 use core::profiles::Profiles;
+use core::resolver::ResolveBehavior;
 use core::{Dependency, PackageId, PackageIdSpec, SourceId, Summary};
 use core::{Edition, Feature, Features, WorkspaceConfig};
 use util::errors::*;
@@ -44,6 +45,7 @@ pub struct Manifest {
     edition: Edition,
     im_a_teapot: Option<bool>,
     default_run: Option<String>,
+    resolve_behavior: Option<ResolveBehavior>,
 }
 
 /// When parsing `Cargo.toml`, some warnings should silenced
@@ -65,6 +67,7 @@ pub struct VirtualManifest {
     workspace: WorkspaceConfig,
     profiles: Profiles,
     warnings: Warnings,
+    resolve_behavior: Option<ResolveBehavior>,
 }
 
 /// General metadata about a package which is just blindly uploaded to the
@@ -118,6 +121,15 @@ impl LibKind {
             LibKind::Other(..) => false,
         }
     }
+
+    /// Whether building this kind of library needs the compiled code of its
+    /// dependencies, rather than just their metadata.
+    pub fn requires_upstream_objects(&self) -> bool {
+        match *self {
+            LibKind::Lib | LibKind::Rlib => false,
+            LibKind::Dylib | LibKind::ProcMacro | LibKind::Other(..) => true,
+        }
+    }
 }
 
 impl fmt::Debug for LibKind {
@@ -315,6 +327,7 @@ impl Manifest {
         im_a_teapot: Option<bool>,
         default_run: Option<String>,
         original: Rc<TomlManifest>,
+        resolve_behavior: Option<ResolveBehavior>,
     ) -> Manifest {
         Manifest {
             summary,
@@ -336,6 +349,7 @@ impl Manifest {
             im_a_teapot,
             default_run,
             publish_lockfile,
+            resolve_behavior,
         }
     }
 
@@ -458,6 +472,11 @@ impl Manifest {
     pub fn default_run(&self) -> Option<&str> {
         self.default_run.as_ref().map(|s| &s[..])
     }
+
+    /// The feature resolver explicitly requested with the `resolver` key.
+    pub fn resolve_behavior(&self) -> Option<ResolveBehavior> {
+        self.resolve_behavior
+    }
 }
 
 impl VirtualManifest {
@@ -466,6 +485,7 @@ impl VirtualManifest {
         patch: HashMap<Url, Vec<Dependency>>,
         workspace: WorkspaceConfig,
         profiles: Profiles,
+        resolve_behavior: Option<ResolveBehavior>,
     ) -> VirtualManifest {
         VirtualManifest {
             replace,
@@ -473,6 +493,7 @@ impl VirtualManifest {
             workspace,
             profiles,
             warnings: Warnings::new(),
+            resolve_behavior,
         }
     }
 
@@ -492,6 +513,10 @@ impl VirtualManifest {
         &self.profiles
     }
 
+    pub fn resolve_behavior(&self) -> Option<ResolveBehavior> {
+        self.resolve_behavior
+    }
+
     pub fn warnings_mut(&mut self) -> &mut Warnings {
         &mut self.warnings
     }
@@ -702,6 +727,17 @@ impl Target {
         }
     }
 
+    /// Whether building this target needs the compiled code of its
+    /// dependencies, as anything which gets linked does.
+    pub fn requires_upstream_objects(&self) -> bool {
+        match self.kind {
+            TargetKind::Lib(ref kinds) | TargetKind::ExampleLib(ref kinds) => {
+                kinds.iter().any(|k| k.requires_upstream_objects())
+            }
+            _ => true,
+        }
+    }
+
     pub fn is_bin(&self) -> bool {
         self.kind == TargetKind::Bin
     }
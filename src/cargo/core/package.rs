@@ -1,19 +1,28 @@
 use std::cell::{Ref, RefCell};
-use std::collections::HashMap;
+use std::cmp;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::hash;
+use std::mem;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
+use curl;
+use curl::easy::HttpVersion;
+use curl::multi::{EasyHandle, Multi};
 use semver::Version;
 use serde::ser;
 use toml;
 use lazycell::LazyCell;
 
 use core::{Dependency, Manifest, PackageId, SourceId, Target};
-use core::{FeatureMap, SourceMap, Summary};
+use core::{FeatureMap, MaybePackage, SourceMap, Summary};
 use core::interning::InternedString;
-use util::{internal, lev_distance, Config};
-use util::errors::{CargoResult, CargoResultExt};
+use ops;
+use util::network::Retry;
+use util::{internal, lev_distance, Config, Progress, ProgressStyle};
+use util::errors::{CargoResult, CargoResultExt, HttpNot200};
 
 /// Information about a package that is available somewhere in the file system.
 ///
@@ -107,6 +116,10 @@ impl Package {
     pub fn manifest(&self) -> &Manifest {
         &self.manifest
     }
+    /// Get the manifest, mutably
+    pub fn manifest_mut(&mut self) -> &mut Manifest {
+        &mut self.manifest
+    }
     /// Get the path to the manifest
     pub fn manifest_path(&self) -> &Path {
         &self.manifest_path
@@ -139,6 +152,10 @@ impl Package {
     pub fn authors(&self) -> &Vec<String> {
         &self.manifest.metadata().authors
     }
+    /// Get the oldest version of Rust the package supports, if it declares one
+    pub fn rust_version(&self) -> Option<&Version> {
+        self.summary().rust_version()
+    }
     /// Whether the package is set to publish
     pub fn publish(&self) -> &Option<Vec<String>> {
         self.manifest.publish()
@@ -172,7 +189,7 @@ impl Package {
     }
 
     pub fn to_registry_toml(&self, config: &Config) -> CargoResult<String> {
-        let manifest = self.manifest().original().prepare_for_publish(config)?;
+        let manifest = self.manifest().original().prepare_for_publish(config, self.root())?;
         let toml = toml::to_string(&manifest)?;
         Ok(format!(
             "\
@@ -224,21 +241,54 @@ impl hash::Hash for Package {
     }
 }
 
-#[derive(Debug)]
 pub struct PackageSet<'cfg> {
     packages: HashMap<PackageId, LazyCell<Package>>,
     sources: RefCell<SourceMap<'cfg>>,
+    config: &'cfg Config,
+    /// Drives all package downloads, so that connections are shared and
+    /// reused between them.
+    multi: Multi,
+    /// Whether transfers to the same host are multiplexed over one HTTP/2
+    /// connection, configured by `http.multiplexing`.
+    multiplexing: bool,
+}
+
+impl<'cfg> fmt::Debug for PackageSet<'cfg> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PackageSet")
+            .field("packages", &self.packages)
+            .field("sources", &self.sources)
+            .finish()
+    }
 }
 
 impl<'cfg> PackageSet<'cfg> {
-    pub fn new(package_ids: &[PackageId], sources: SourceMap<'cfg>) -> PackageSet<'cfg> {
-        PackageSet {
+    pub fn new(
+        package_ids: &[PackageId],
+        sources: SourceMap<'cfg>,
+        config: &'cfg Config,
+    ) -> CargoResult<PackageSet<'cfg>> {
+        let multiplexing = config
+            .get::<Option<bool>>("http.multiplexing")?
+            .unwrap_or(true);
+        let mut multi = Multi::new();
+        multi
+            .pipelining(false, multiplexing)
+            .chain_err(|| "failed to enable multiplexing in curl")?;
+        // Don't open more than a couple of connections to any one server,
+        // with multiplexing that's plenty for all our transfers.
+        multi.set_max_host_connections(2)?;
+
+        Ok(PackageSet {
             packages: package_ids
                 .iter()
                 .map(|id| (id.clone(), LazyCell::new()))
                 .collect(),
             sources: RefCell::new(sources),
-        }
+            config,
+            multi,
+            multiplexing,
+        })
     }
 
     pub fn package_ids<'a>(&'a self) -> Box<Iterator<Item = &'a PackageId> + 'a> {
@@ -246,24 +296,314 @@ impl<'cfg> PackageSet<'cfg> {
     }
 
     pub fn get(&self, id: &PackageId) -> CargoResult<&Package> {
-        let slot = self.packages
-            .get(id)
-            .ok_or_else(|| internal(format!("couldn't find `{}` in package set", id)))?;
-        if let Some(pkg) = slot.borrow() {
-            return Ok(pkg);
+        Ok(self.get_many(Some(id))?.remove(0))
+    }
+
+    /// Returns the packages for each of `ids`, first downloading all of those
+    /// which aren't available locally yet, concurrently.
+    pub fn get_many<'a, I>(&self, ids: I) -> CargoResult<Vec<&Package>>
+    where
+        I: IntoIterator<Item = &'a PackageId>,
+    {
+        let ids = ids.into_iter().collect::<Vec<_>>();
+        {
+            let mut downloads = Downloads::new(self);
+            for id in ids.iter() {
+                downloads.start(id)?;
+            }
+            if !downloads.missing.is_empty() {
+                let missing = downloads
+                    .missing
+                    .iter()
+                    .map(|id| format!("  {}", id))
+                    .collect::<Vec<_>>();
+                bail!(
+                    "the following packages are not in the local cache and can't be \
+                     downloaded in offline mode (--offline):\n{}",
+                    missing.join("\n")
+                );
+            }
+            downloads.wait()?;
+        }
+        let mut pkgs = Vec::new();
+        for id in ids {
+            pkgs.push(self.slot(id)?.borrow().expect("package should be downloaded"));
         }
-        let mut sources = self.sources.borrow_mut();
-        let source = sources
-            .get_mut(id.source_id())
-            .ok_or_else(|| internal(format!("couldn't find source for `{}`", id)))?;
-        let pkg = source
-            .download(id)
-            .chain_err(|| format_err!("unable to get packages from source"))?;
-        assert!(slot.fill(pkg).is_ok());
-        Ok(slot.borrow().unwrap())
+        Ok(pkgs)
     }
 
     pub fn sources(&self) -> Ref<SourceMap<'cfg>> {
         self.sources.borrow()
     }
+
+    fn slot(&self, id: &PackageId) -> CargoResult<&LazyCell<Package>> {
+        self.packages
+            .get(id)
+            .ok_or_else(|| internal(format!("couldn't find `{}` in package set", id)))
+    }
+}
+
+/// The downloads started by one call to `PackageSet::get_many`.
+struct Downloads<'a, 'cfg: 'a> {
+    set: &'a PackageSet<'cfg>,
+    /// Transfers in progress, keyed by the token of their curl handle.
+    pending: HashMap<usize, (Download<'cfg>, EasyHandle)>,
+    /// Packages in `pending`, so a package requested twice is only
+    /// downloaded once.
+    pending_ids: HashSet<PackageId>,
+    /// Packages which need downloading in offline mode, which are reported
+    /// all together rather than failing on the first.
+    missing: Vec<PackageId>,
+    next_token: usize,
+    progress: Progress<'cfg>,
+    /// How many packages have needed downloading, and how many of them are
+    /// done.
+    started: usize,
+    finished: usize,
+    /// The size of all finished downloads.
+    downloaded_bytes: u64,
+}
+
+struct Download<'cfg> {
+    id: PackageId,
+    url: String,
+    /// Filled in by curl's callbacks while the transfer is running.
+    transfer: Arc<Mutex<Transfer>>,
+    /// Remaining attempts if this download fails spuriously.
+    retry: Retry<'cfg>,
+}
+
+#[derive(Default)]
+struct Transfer {
+    data: Vec<u8>,
+    /// The size of the file if the server has told us yet, and how much of
+    /// it has been received.
+    total: u64,
+    current: u64,
+}
+
+impl<'a, 'cfg> Downloads<'a, 'cfg> {
+    fn new(set: &'a PackageSet<'cfg>) -> Downloads<'a, 'cfg> {
+        Downloads {
+            set,
+            pending: HashMap::new(),
+            pending_ids: HashSet::new(),
+            missing: Vec::new(),
+            next_token: 0,
+            progress: Progress::with_style("Downloading", ProgressStyle::Ratio, set.config),
+            started: 0,
+            finished: 0,
+            downloaded_bytes: 0,
+        }
+    }
+
+    /// Gets `id` from its source, starting a download for it if the source
+    /// doesn't have it locally.
+    fn start(&mut self, id: &PackageId) -> CargoResult<()> {
+        let set = self.set;
+        let slot = set.slot(id)?;
+        if slot.borrow().is_some() || self.pending_ids.contains(id) {
+            return Ok(());
+        }
+
+        let pkg = {
+            let mut sources = set.sources.borrow_mut();
+            let source = sources
+                .get_mut(id.source_id())
+                .ok_or_else(|| internal(format!("couldn't find source for `{}`", id)))?;
+            source
+                .download(id)
+                .chain_err(|| format_err!("unable to get packages from source"))?
+        };
+        let (url, descriptor) = match pkg {
+            MaybePackage::Ready(pkg) => {
+                assert!(slot.fill(pkg).is_ok());
+                return Ok(());
+            }
+            MaybePackage::Download { url, descriptor } => (url, descriptor),
+        };
+        if set.config.offline() {
+            self.missing.push(id.clone());
+            return Ok(());
+        }
+
+        set.config.shell().status("Downloading", &descriptor)?;
+        let dl = Download {
+            id: id.clone(),
+            url,
+            transfer: Arc::new(Mutex::new(Transfer::default())),
+            retry: Retry::new(set.config)?,
+        };
+        self.pending_ids.insert(id.clone());
+        self.started += 1;
+        self.enqueue(dl)
+            .chain_err(|| format_err!("unable to get packages from source"))
+    }
+
+    /// Adds a transfer for `dl` to the multi handle.
+    fn enqueue(&mut self, dl: Download<'cfg>) -> CargoResult<()> {
+        *dl.transfer.lock().unwrap() = Transfer::default();
+
+        let mut handle = ops::http_handle(self.set.config)?;
+        handle.get(true)?;
+        handle.url(&dl.url)?;
+        handle.follow_location(true)?;
+        if self.set.multiplexing {
+            // Not every libcurl is built with HTTP/2 support, in which case
+            // we just stick with HTTP/1.1.
+            if let Err(e) = handle.http_version(HttpVersion::V2) {
+                debug!("HTTP/2 is not available: {}", e);
+            }
+            // Have transfers to the same host wait to find out whether the
+            // first connection can be multiplexed, instead of each opening
+            // a connection of its own.
+            if let Err(e) = handle.pipewait(true) {
+                debug!("failed to set pipewait: {}", e);
+            }
+        }
+
+        let transfer = dl.transfer.clone();
+        handle.write_function(move |buf| {
+            transfer.lock().unwrap().data.extend_from_slice(buf);
+            Ok(buf.len())
+        })?;
+        let transfer = dl.transfer.clone();
+        handle.progress(true)?;
+        handle.progress_function(move |dl_total, dl_cur, _, _| {
+            let mut transfer = transfer.lock().unwrap();
+            transfer.total = dl_total as u64;
+            transfer.current = dl_cur as u64;
+            true
+        })?;
+
+        let token = self.next_token;
+        self.next_token += 1;
+        let mut handle = self.set.multi.add(handle)?;
+        handle.set_token(token)?;
+        self.pending.insert(token, (dl, handle));
+        Ok(())
+    }
+
+    /// Runs all transfers until every package has been downloaded.
+    fn wait(&mut self) -> CargoResult<()> {
+        while !self.pending.is_empty() {
+            self.set
+                .multi
+                .perform()
+                .chain_err(|| "failed to perform http requests")?;
+
+            let mut results = Vec::new();
+            self.set.multi.messages(|msg| {
+                let token = msg.token().expect("failed to read token");
+                if let Some(result) = msg.result() {
+                    results.push((token, result));
+                }
+            });
+            for (token, result) in results {
+                self.finish(token, result)?;
+            }
+            self.tick()?;
+
+            if !self.pending.is_empty() {
+                let max = Duration::from_secs(1);
+                let timeout = self.set.multi.get_timeout()?.unwrap_or(max);
+                self.set.multi.wait(&mut [], cmp::min(timeout, max))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Handles the transfer for `token` finishing with `result`, either
+    /// handing the data to its source or retrying it.
+    fn finish(&mut self, token: usize, result: Result<(), curl::Error>) -> CargoResult<()> {
+        let (dl, handle) = self.pending
+            .remove(&token)
+            .expect("got a token for a transfer that isn't in progress");
+        let mut handle = self.set.multi.remove(handle)?;
+        let Download {
+            id,
+            url,
+            transfer,
+            mut retry,
+        } = dl;
+
+        let outcome = (|| -> CargoResult<()> {
+            result?;
+            let code = handle.response_code()?;
+            if code != 200 && code != 0 {
+                let url = handle.effective_url()?.unwrap_or(&url);
+                return Err(HttpNot200 {
+                    code,
+                    url: url.to_string(),
+                }.into());
+            }
+            Ok(())
+        })();
+        if outcome.is_err() {
+            // Make sure a retry warning isn't drawn over the progress bar.
+            self.progress.clear();
+        }
+        let done = retry
+            .attempt(|| outcome)
+            .chain_err(|| format!("failed to download from `{}`", url))?;
+        if done.is_none() {
+            return self.enqueue(Download {
+                id,
+                url,
+                transfer,
+                retry,
+            });
+        }
+
+        let data = mem::replace(&mut transfer.lock().unwrap().data, Vec::new());
+        self.downloaded_bytes += data.len() as u64;
+        self.finished += 1;
+        self.pending_ids.remove(&id);
+
+        let set = self.set;
+        let pkg = {
+            let mut sources = set.sources.borrow_mut();
+            let source = sources
+                .get_mut(id.source_id())
+                .ok_or_else(|| internal(format!("couldn't find source for `{}`", id)))?;
+            source
+                .finish_download(&id, data)
+                .chain_err(|| format_err!("unable to get packages from source"))?
+        };
+        assert!(set.slot(&id)?.fill(pkg).is_ok());
+        Ok(())
+    }
+
+    /// Updates the progress bar with the number of packages and bytes
+    /// downloaded so far.
+    fn tick(&mut self) -> CargoResult<()> {
+        let mut current = self.downloaded_bytes;
+        let mut total = self.downloaded_bytes;
+        for &(ref dl, _) in self.pending.values() {
+            let transfer = dl.transfer.lock().unwrap();
+            current += transfer.current;
+            total += transfer.total;
+        }
+        let msg = format!(
+            " crates, {} of {}",
+            human_readable_bytes(current),
+            human_readable_bytes(total)
+        );
+        self.progress.tick_with_msg(self.finished, self.started, &msg)
+    }
+}
+
+fn human_readable_bytes(bytes: u64) -> String {
+    let units = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < units.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, units[0])
+    } else {
+        format!("{:.1} {}", size, units[unit])
+    }
 }
@@ -1,9 +1,10 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::{cmp, fmt, hash};
 
-use core::compiler::CompileMode;
+use core::compiler::{CompileMode, ProfileKind};
 use core::interning::InternedString;
 use core::{Features, PackageId, PackageIdSpec, PackageSet, Shell};
+use util::config::Definition;
 use util::errors::CargoResultExt;
 use util::lev_distance::lev_distance;
 use util::toml::{ProfilePackageSpec, StringOrBool, TomlProfile, TomlProfiles, U32OrBool};
@@ -17,6 +18,8 @@ pub struct Profiles {
     test: ProfileMaker,
     bench: ProfileMaker,
     doc: ProfileMaker,
+    /// Custom profiles, selected with `--profile`, by name.
+    custom: BTreeMap<String, ProfileMaker>,
 }
 
 impl Profiles {
@@ -33,32 +36,71 @@ impl Profiles {
         let config_profiles = config.profiles()?;
         config_profiles.validate(features, warnings)?;
 
+        let dev = ProfileMaker {
+            default: Profile::default_dev(),
+            toml: profiles.and_then(|p| p.dev.clone()),
+            config: config_profiles.dev.clone(),
+            parent: None,
+        };
+        let release = ProfileMaker {
+            default: Profile::default_release(),
+            toml: profiles.and_then(|p| p.release.clone()),
+            config: config_profiles.release.clone(),
+            parent: None,
+        };
+
+        let empty = BTreeMap::new();
+        let toml_custom = profiles.map(|p| &p.custom).unwrap_or(&empty);
+        let mut config_custom = BTreeMap::new();
+        for (name, &(ref profile, ref definition)) in config_profiles.custom.iter() {
+            // A table in a config file with neither `inherits` nor a
+            // profile in the manifest to add to doesn't define anything.
+            if profile.inherits.is_none() && !toml_custom.contains_key(name) {
+                warnings.push(format!(
+                    "unused key `profile.{}` in config file `{}`",
+                    name, definition
+                ));
+                continue;
+            }
+            config_custom.insert(name.clone(), profile.clone());
+        }
+        let mut custom = BTreeMap::new();
+        for name in toml_custom.keys().chain(config_custom.keys()) {
+            if !custom.contains_key(name) {
+                let maker = custom_profile_maker(
+                    name,
+                    toml_custom,
+                    &config_custom,
+                    &dev,
+                    &release,
+                    &mut Vec::new(),
+                )?;
+                custom.insert(name.clone(), maker);
+            }
+        }
+
         Ok(Profiles {
-            dev: ProfileMaker {
-                default: Profile::default_dev(),
-                toml: profiles.and_then(|p| p.dev.clone()),
-                config: config_profiles.dev.clone(),
-            },
-            release: ProfileMaker {
-                default: Profile::default_release(),
-                toml: profiles.and_then(|p| p.release.clone()),
-                config: config_profiles.release.clone(),
-            },
+            dev,
+            release,
             test: ProfileMaker {
                 default: Profile::default_test(),
                 toml: profiles.and_then(|p| p.test.clone()),
                 config: None,
+                parent: None,
             },
             bench: ProfileMaker {
                 default: Profile::default_bench(),
                 toml: profiles.and_then(|p| p.bench.clone()),
                 config: None,
+                parent: None,
             },
             doc: ProfileMaker {
                 default: Profile::default_doc(),
                 toml: profiles.and_then(|p| p.doc.clone()),
                 config: None,
+                parent: None,
             },
+            custom,
         })
     }
 
@@ -71,20 +113,24 @@ impl Profiles {
         is_member: bool,
         profile_for: ProfileFor,
         mode: CompileMode,
-        release: bool,
+        kind: &ProfileKind,
     ) -> Profile {
-        let maker = match mode {
-            CompileMode::Test => {
+        let release = *kind == ProfileKind::Release;
+        let maker = match (mode, kind) {
+            // A custom profile is used for everything but documentation.
+            (CompileMode::Doc { .. }, _) => &self.doc,
+            (_, &ProfileKind::Custom(ref name)) => &self.custom[name],
+            (CompileMode::Test, _) => {
                 if release {
                     &self.bench
                 } else {
                     &self.test
                 }
             }
-            CompileMode::Build
-            | CompileMode::Check { .. }
-            | CompileMode::Doctest
-            | CompileMode::RunCustomBuild => {
+            (CompileMode::Build, _)
+            | (CompileMode::Check { .. }, _)
+            | (CompileMode::Doctest, _)
+            | (CompileMode::RunCustomBuild, _) => {
                 // Note: RunCustomBuild doesn't normally use this code path.
                 // `build_unit_profiles` normally ensures that it selects the
                 // ancestor's profile.  However `cargo clean -p` can hit this
@@ -95,8 +141,7 @@ impl Profiles {
                     &self.dev
                 }
             }
-            CompileMode::Bench => &self.bench,
-            CompileMode::Doc { .. } => &self.doc,
+            (CompileMode::Bench, _) => &self.bench,
         };
         let mut profile = maker.get_profile(Some(pkg_id), is_member, profile_for);
         // `panic` should not be set for tests/benches, or any of their
@@ -122,11 +167,46 @@ impl Profiles {
     /// This returns a generic base profile. This is currently used for the
     /// `[Finished]` line.  It is not entirely accurate, since it doesn't
     /// select for the package that was actually built.
-    pub fn base_profile(&self, release: bool) -> Profile {
-        if release {
-            self.release.get_profile(None, true, ProfileFor::Any)
-        } else {
-            self.dev.get_profile(None, true, ProfileFor::Any)
+    pub fn base_profile(&self, kind: &ProfileKind) -> Profile {
+        let maker = match *kind {
+            ProfileKind::Dev => &self.dev,
+            ProfileKind::Release => &self.release,
+            ProfileKind::Custom(ref name) => &self.custom[name],
+        };
+        maker.get_profile(None, true, ProfileFor::Any)
+    }
+
+    /// Whether `kind` is `release` or a custom profile inheriting from it,
+    /// which build scripts are told with the `PROFILE` environment variable.
+    pub fn inherits_release(&self, kind: &ProfileKind) -> bool {
+        match *kind {
+            ProfileKind::Dev => false,
+            ProfileKind::Release => true,
+            ProfileKind::Custom(ref name) => {
+                self.custom[name].root().default.name.as_str() == "release"
+            }
+        }
+    }
+
+    /// Checks that the profile selected with `--profile` is defined.
+    pub fn validate_kind(&self, kind: &ProfileKind) -> CargoResult<()> {
+        let name = match *kind {
+            ProfileKind::Custom(ref name) => name,
+            _ => return Ok(()),
+        };
+        match name.as_str() {
+            "test" | "bench" | "doc" => bail!(
+                "the `{}` profile can't be selected with `--profile`, \
+                 only `dev`, `release` or a custom profile can",
+                name
+            ),
+            _ if !self.custom.contains_key(name) => bail!(
+                "profile `{}` is not defined, custom profiles are defined \
+                 with a `[profile.{}]` table in the manifest",
+                name,
+                name
+            ),
+            _ => Ok(()),
         }
     }
 
@@ -137,6 +217,9 @@ impl Profiles {
         self.test.validate_packages(shell, packages)?;
         self.bench.validate_packages(shell, packages)?;
         self.doc.validate_packages(shell, packages)?;
+        for maker in self.custom.values() {
+            maker.validate_packages(shell, packages)?;
+        }
         Ok(())
     }
 }
@@ -150,6 +233,7 @@ impl Profiles {
 /// - [profile.dev.build-override] - This can only apply to `build.rs` scripts
 ///   and their dependencies.
 /// - [profile.dev]
+/// - The profile a custom profile `inherits` from, with all of the above.
 /// - Default (hard-coded) values.
 #[derive(Debug, Clone)]
 struct ProfileMaker {
@@ -159,6 +243,9 @@ struct ProfileMaker {
     toml: Option<TomlProfile>,
     /// Profile loaded from `.cargo/config` files.
     config: Option<TomlProfile>,
+    /// The profile a custom profile inherits from, which takes the place of
+    /// `default`.
+    parent: Option<Box<ProfileMaker>>,
 }
 
 impl ProfileMaker {
@@ -168,7 +255,13 @@ impl ProfileMaker {
         is_member: bool,
         profile_for: ProfileFor,
     ) -> Profile {
-        let mut profile = self.default;
+        let mut profile = match self.parent {
+            Some(ref parent) => Profile {
+                name: self.default.name,
+                ..parent.get_profile(pkg_id, is_member, profile_for)
+            },
+            None => self.default,
+        };
         if let Some(ref toml) = self.toml {
             merge_toml(pkg_id, is_member, profile_for, &mut profile, toml);
         }
@@ -178,6 +271,14 @@ impl ProfileMaker {
         profile
     }
 
+    /// The built-in profile a custom profile ultimately inherits from.
+    fn root(&self) -> &ProfileMaker {
+        match self.parent {
+            Some(ref parent) => parent.root(),
+            None => self,
+        }
+    }
+
     fn validate_packages(&self, shell: &mut Shell, packages: &PackageSet) -> CargoResult<()> {
         self.validate_packages_toml(shell, packages, &self.toml, true)?;
         self.validate_packages_toml(shell, packages, &self.config, false)?;
@@ -290,6 +391,67 @@ impl ProfileMaker {
     }
 }
 
+/// Creates the maker for the custom profile `name`, following its chain of
+/// `inherits` back to `dev` or `release`. `stack` holds the profiles which
+/// inherit from `name`, to detect loops.
+fn custom_profile_maker(
+    name: &str,
+    toml: &BTreeMap<String, TomlProfile>,
+    config: &BTreeMap<String, TomlProfile>,
+    dev: &ProfileMaker,
+    release: &ProfileMaker,
+    stack: &mut Vec<String>,
+) -> CargoResult<ProfileMaker> {
+    stack.push(name.to_string());
+    if stack[..stack.len() - 1].iter().any(|s| s == name) {
+        bail!("profile inheritance loop detected: {}", stack.join(" -> "));
+    }
+    let toml_profile = toml.get(name).cloned();
+    let config_profile = config.get(name).cloned();
+    // The config files can change what a profile inherits from, like any
+    // other setting.
+    let inherits = config_profile
+        .as_ref()
+        .and_then(|p| p.inherits.clone())
+        .or_else(|| toml_profile.as_ref().and_then(|p| p.inherits.clone()));
+    let parent = match inherits {
+        Some(ref parent) if parent == "dev" => dev.clone(),
+        Some(ref parent) if parent == "release" => release.clone(),
+        Some(ref parent) if toml.contains_key(parent) || config.contains_key(parent) => {
+            custom_profile_maker(parent, toml, config, dev, release, stack)?
+        }
+        Some(ref parent) => match parent.as_str() {
+            "test" | "bench" | "doc" => bail!(
+                "profile `{}` inherits from `{}`, but only `dev`, `release` \
+                 and custom profiles can be inherited from",
+                name,
+                parent
+            ),
+            _ => bail!(
+                "profile `{}` inherits from `{}`, but that profile is not defined",
+                name,
+                parent
+            ),
+        },
+        None => bail!(
+            "profile `{}` is missing an `inherits` key, which is required \
+             for every profile other than `dev`, `release`, `test`, `bench` \
+             and `doc`",
+            name
+        ),
+    };
+    stack.pop();
+    Ok(ProfileMaker {
+        default: Profile {
+            name: InternedString::new(name),
+            ..parent.default
+        },
+        toml: toml_profile,
+        config: config_profile,
+        parent: Some(Box::new(parent)),
+    })
+}
+
 fn merge_toml(
     pkg_id: Option<&PackageId>,
     is_member: bool,
@@ -373,7 +535,7 @@ fn merge_profile(profile: &mut Profile, toml: &TomlProfile) {
 /// target.
 #[derive(Clone, Copy, Eq)]
 pub struct Profile {
-    pub name: &'static str,
+    pub name: InternedString,
     pub opt_level: InternedString,
     pub lto: Lto,
     // None = use rustc default
@@ -389,7 +551,7 @@ pub struct Profile {
 impl Default for Profile {
     fn default() -> Profile {
         Profile {
-            name: "",
+            name: InternedString::new(""),
             opt_level: InternedString::new("0"),
             lto: Lto::Bool(false),
             codegen_units: None,
@@ -406,7 +568,7 @@ impl Default for Profile {
 compact_debug! {
     impl fmt::Debug for Profile {
         fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            let (default, default_name) = match self.name {
+            let (default, default_name) = match self.name.as_str() {
                 "dev" => (Profile::default_dev(), "default_dev()"),
                 "release" => (Profile::default_release(), "default_release()"),
                 "test" => (Profile::default_test(), "default_test()"),
@@ -454,7 +616,7 @@ impl cmp::PartialEq for Profile {
 impl Profile {
     fn default_dev() -> Profile {
         Profile {
-            name: "dev",
+            name: InternedString::new("dev"),
             debuginfo: Some(2),
             debug_assertions: true,
             overflow_checks: true,
@@ -465,7 +627,7 @@ impl Profile {
 
     fn default_release() -> Profile {
         Profile {
-            name: "release",
+            name: InternedString::new("release"),
             opt_level: InternedString::new("3"),
             ..Profile::default()
         }
@@ -473,21 +635,21 @@ impl Profile {
 
     fn default_test() -> Profile {
         Profile {
-            name: "test",
+            name: InternedString::new("test"),
             ..Profile::default_dev()
         }
     }
 
     fn default_bench() -> Profile {
         Profile {
-            name: "bench",
+            name: InternedString::new("bench"),
             ..Profile::default_release()
         }
     }
 
     fn default_doc() -> Profile {
         Profile {
-            name: "doc",
+            name: InternedString::new("doc"),
             ..Profile::default_dev()
         }
     }
@@ -557,13 +719,51 @@ impl ProfileFor {
 }
 
 /// Profiles loaded from .cargo/config files.
-#[derive(Clone, Debug, Deserialize, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct ConfigProfiles {
     dev: Option<TomlProfile>,
     release: Option<TomlProfile>,
+    /// Custom profiles, along with the file defining each of them.
+    custom: BTreeMap<String, (TomlProfile, Definition)>,
 }
 
 impl ConfigProfiles {
+    /// Loads the `[profile]` table, returning `None` if it isn't set.
+    pub fn load(config: &Config) -> CargoResult<Option<ConfigProfiles>> {
+        let table = config.get_table("profile")?;
+        let dev = config.get::<Option<TomlProfile>>("profile.dev")?;
+        let release = config.get::<Option<TomlProfile>>("profile.release")?;
+        let mut custom = BTreeMap::new();
+        if let Some(ref table) = table {
+            for (name, value) in table.val.iter() {
+                match name.as_str() {
+                    "dev" | "release" => {}
+                    "test" | "bench" | "doc" => {
+                        config.shell().warn(format!(
+                            "unused key `profile.{}` in config file `{}`",
+                            name,
+                            value.definition()
+                        ))?;
+                    }
+                    _ => {
+                        let key = format!("profile.{}", name);
+                        if let Some(profile) = config.get::<Option<TomlProfile>>(&key)? {
+                            custom.insert(name.clone(), (profile, value.definition().clone()));
+                        }
+                    }
+                }
+            }
+        }
+        if table.is_none() && dev.is_none() && release.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(ConfigProfiles {
+            dev,
+            release,
+            custom,
+        }))
+    }
+
     pub fn validate(&self, features: &Features, warnings: &mut Vec<String>) -> CargoResult<()> {
         if let Some(ref profile) = self.dev {
             profile
@@ -575,6 +775,11 @@ impl ConfigProfiles {
                 .validate("release", features, warnings)
                 .chain_err(|| format_err!("config profile `profile.release` is not valid"))?;
         }
+        for (name, &(ref profile, _)) in self.custom.iter() {
+            profile
+                .validate(name, features, warnings)
+                .chain_err(|| format_err!("config profile `profile.{}` is not valid", name))?;
+        }
         Ok(())
     }
 }
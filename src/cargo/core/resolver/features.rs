@@ -0,0 +1,322 @@
+//! Resolution of the features each package is built with.
+//!
+//! The main resolver unifies features across every use of a package, so a
+//! feature which a build-dependency enables is also enabled when the same
+//! package is a normal dependency, and dev-dependencies enable features even
+//! when nothing that uses them is being built. That's fine for most projects,
+//! but not when, say, a build script wants `std` and the target is `no_std`.
+//!
+//! With `resolver = "2"` in the root manifest, features are instead computed
+//! here once the units to build are known. Each package gets one set of
+//! features for when it's built for the target and another for when it's
+//! built for the host, as a build script, a dependency of one, or a
+//! proc-macro. Dev-dependencies are only followed for the packages whose
+//! tests, benches or examples are being built.
+
+use std::collections::{HashMap, HashSet};
+
+use core::interning::InternedString;
+use core::{Dependency, FeatureValue, Package, PackageId, PackageSet, Resolve, Summary};
+use util::errors::CargoResult;
+
+use super::Method;
+
+/// Which feature resolver to use, as set by the `resolver` key in the
+/// `[package]` or `[workspace]` table of the root manifest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResolveBehavior {
+    /// Features are unified across every use of a package.
+    V1,
+    /// Features are resolved separately for the host and the target.
+    V2,
+}
+
+impl ResolveBehavior {
+    pub fn from_manifest(resolver: &str) -> CargoResult<ResolveBehavior> {
+        match resolver {
+            "1" => Ok(ResolveBehavior::V1),
+            "2" => Ok(ResolveBehavior::V2),
+            s => bail!(
+                "`resolver` setting `{}` is not valid, valid options are \"1\" or \"2\"",
+                s
+            ),
+        }
+    }
+}
+
+/// Whether a package is built for the target, or for the host as a build
+/// script, a dependency of one, or a proc-macro.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum FeaturesFor {
+    Target,
+    Host,
+}
+
+impl FeaturesFor {
+    pub fn all_values() -> &'static [FeaturesFor] {
+        static ALL: [FeaturesFor; 2] = [FeaturesFor::Target, FeaturesFor::Host];
+        &ALL
+    }
+}
+
+/// The features enabled for each package.
+#[derive(Debug)]
+pub struct ResolvedFeatures {
+    /// `None` when features are unified, in which case they come straight
+    /// from the `Resolve`.
+    activated: Option<HashMap<(PackageId, FeaturesFor), HashSet<String>>>,
+    empty_features: HashSet<String>,
+}
+
+impl ResolvedFeatures {
+    /// The features as unified by the main resolver.
+    pub fn unified() -> ResolvedFeatures {
+        ResolvedFeatures {
+            activated: None,
+            empty_features: HashSet::new(),
+        }
+    }
+
+    /// Whether host and target features are kept apart.
+    pub fn is_split(&self) -> bool {
+        self.activated.is_some()
+    }
+
+    /// The features a dependency should be built with, given those of its
+    /// parent, whether it's a build-dependency, and whether its library is
+    /// built for the host.
+    pub fn dep_features_for(
+        &self,
+        parent: FeaturesFor,
+        build_dep: bool,
+        for_host: bool,
+    ) -> FeaturesFor {
+        if self.is_split() && (parent == FeaturesFor::Host || build_dep || for_host) {
+            FeaturesFor::Host
+        } else {
+            FeaturesFor::Target
+        }
+    }
+
+    pub fn activated<'a>(
+        &'a self,
+        resolve: &'a Resolve,
+        pkg: &PackageId,
+        features_for: FeaturesFor,
+    ) -> &'a HashSet<String> {
+        match self.activated {
+            Some(ref activated) => activated
+                .get(&(pkg.clone(), features_for))
+                .unwrap_or(&self.empty_features),
+            None => resolve.features(pkg),
+        }
+    }
+}
+
+/// A request to enable features on a package. Requests are handled a batch
+/// at a time so that all the packages they're for can be downloaded at once.
+struct Request {
+    id: PackageId,
+    features_for: FeaturesFor,
+    /// Whether this comes from a dependency, rather than being a root, in
+    /// which case a proc-macro is switched over to the host.
+    is_dep: bool,
+    features: Vec<InternedString>,
+    all_features: bool,
+    uses_default_features: bool,
+}
+
+pub struct FeatureResolver<'a> {
+    resolve: &'a Resolve,
+    /// Whether a platform-specific dependency is used when building for the
+    /// host or for the target.
+    platform_activated: &'a Fn(&Dependency, FeaturesFor) -> bool,
+    /// The packages whose dev-dependencies are used.
+    dev_roots: HashSet<PackageId>,
+    activated: HashMap<(PackageId, FeaturesFor), HashSet<InternedString>>,
+    /// The features enabled on dependencies with `dep/feature`, keyed by the
+    /// dependency's name in the manifest.
+    dep_features:
+        HashMap<(PackageId, FeaturesFor), HashMap<InternedString, HashSet<InternedString>>>,
+}
+
+impl<'a> FeatureResolver<'a> {
+    /// Resolves the features of `roots` and everything they depend on.
+    ///
+    /// Each root is paired with whether its dev-dependencies are used, and
+    /// gets the features requested by `method`.
+    pub fn resolve<'cfg>(
+        resolve: &'a Resolve,
+        packages: &'a PackageSet<'cfg>,
+        roots: &[(&PackageId, bool)],
+        method: Method,
+        platform_activated: &'a Fn(&Dependency, FeaturesFor) -> bool,
+    ) -> CargoResult<ResolvedFeatures> {
+        let mut r = FeatureResolver {
+            resolve,
+            platform_activated,
+            dev_roots: HashSet::new(),
+            activated: HashMap::new(),
+            dep_features: HashMap::new(),
+        };
+
+        let (features, all_features, uses_default_features) = match method {
+            Method::Everything => (&[][..], true, true),
+            Method::Required {
+                features,
+                all_features,
+                uses_default_features,
+                ..
+            } => (features, all_features, uses_default_features),
+        };
+        let mut pending = Vec::new();
+        for &(id, dev) in roots {
+            if dev {
+                r.dev_roots.insert(id.clone());
+            }
+            pending.push(Request {
+                id: id.clone(),
+                features_for: FeaturesFor::Target,
+                is_dep: false,
+                features: features.to_vec(),
+                all_features,
+                uses_default_features,
+            });
+        }
+
+        while !pending.is_empty() {
+            let pkgs = packages.get_many(pending.iter().map(|req| &req.id))?;
+            let mut next = Vec::new();
+            for (req, pkg) in pending.into_iter().zip(pkgs) {
+                r.activate_pkg(req, pkg, &mut next);
+            }
+            pending = next;
+        }
+
+        let activated = r.activated
+            .into_iter()
+            .map(|(key, features)| {
+                let features = features.iter().map(|f| f.to_string()).collect();
+                (key, features)
+            })
+            .collect();
+        Ok(ResolvedFeatures {
+            activated: Some(activated),
+            empty_features: HashSet::new(),
+        })
+    }
+
+    /// Enables the features of `req` on `pkg`, and if that changed anything,
+    /// queues up requests for the dependencies which are now used.
+    fn activate_pkg(&mut self, req: Request, pkg: &Package, next: &mut Vec<Request>) {
+        let for_host = req.is_dep && pkg.targets().iter().any(|t| t.is_lib() && t.for_host());
+        let features_for = if for_host {
+            FeaturesFor::Host
+        } else {
+            req.features_for
+        };
+        let key = (req.id.clone(), features_for);
+        let summary = pkg.summary();
+
+        let mut values = Vec::new();
+        if req.all_features {
+            values.extend(summary.features().keys().map(|f| FeatureValue::Feature(*f)));
+            values.extend(
+                summary
+                    .dependencies()
+                    .iter()
+                    .filter(|d| d.is_optional())
+                    .map(|d| FeatureValue::Crate(d.name_in_toml())),
+            );
+        }
+        values.extend(req.features.iter().map(|f| FeatureValue::new(*f, summary)));
+        if req.uses_default_features && summary.features().contains_key("default") {
+            values.push(FeatureValue::Feature(InternedString::new("default")));
+        }
+
+        let mut changed = !self.activated.contains_key(&key);
+        self.activated.entry(key.clone()).or_insert_with(HashSet::new);
+        for value in values.iter() {
+            changed |= self.activate_fv(&key, summary, value);
+        }
+        if !changed {
+            return;
+        }
+
+        let dev = features_for == FeaturesFor::Target && self.dev_roots.contains(&req.id);
+        let build_script = pkg.targets().iter().any(|t| t.is_custom_build());
+        let activated = &self.activated[&key];
+        let no_dep_features = HashMap::new();
+        let dep_features = self.dep_features.get(&key).unwrap_or(&no_dep_features);
+        for (dep_id, deps) in self.resolve.deps(&req.id) {
+            for dep in deps {
+                if !dep.is_transitive() && !dev {
+                    continue;
+                }
+                if dep.is_build() && !build_script {
+                    continue;
+                }
+                // Build-dependencies are used by the build script, which
+                // always runs on the host.
+                let edge_for = if dep.is_build() {
+                    FeaturesFor::Host
+                } else {
+                    features_for
+                };
+                if !(self.platform_activated)(dep, edge_for) {
+                    continue;
+                }
+                if dep.is_optional() && !activated.contains(&dep.name_in_toml()) {
+                    continue;
+                }
+
+                let mut features = dep.features().to_vec();
+                if let Some(extra) = dep_features.get(&dep.name_in_toml()) {
+                    features.extend(extra.iter().cloned());
+                }
+                next.push(Request {
+                    id: dep_id.clone(),
+                    features_for: edge_for,
+                    is_dep: true,
+                    features,
+                    all_features: false,
+                    uses_default_features: dep.uses_default_features(),
+                });
+            }
+        }
+    }
+
+    /// Enables a single feature value, returning whether anything new was
+    /// enabled.
+    fn activate_fv(
+        &mut self,
+        key: &(PackageId, FeaturesFor),
+        summary: &Summary,
+        value: &FeatureValue,
+    ) -> bool {
+        match *value {
+            FeatureValue::Feature(feature) => {
+                if !self.activated.get_mut(key).unwrap().insert(feature) {
+                    return false;
+                }
+                if let Some(values) = summary.features().get(&feature) {
+                    for value in values {
+                        self.activate_fv(key, summary, value);
+                    }
+                }
+                true
+            }
+            FeatureValue::Crate(dep) => self.activated.get_mut(key).unwrap().insert(dep),
+            FeatureValue::CrateFeature(dep, dep_feature) => {
+                let new_dep = self.activated.get_mut(key).unwrap().insert(dep);
+                let new_feature = self.dep_features
+                    .entry(key.clone())
+                    .or_insert_with(HashMap::new)
+                    .entry(dep)
+                    .or_insert_with(HashSet::new)
+                    .insert(dep_feature);
+                new_dep || new_feature
+            }
+        }
+    }
+}
@@ -68,12 +68,14 @@ use self::types::{RcVecIter, RegistryQueryer};
 
 pub use self::encode::{EncodableDependency, EncodablePackageId, EncodableResolve};
 pub use self::encode::{Metadata, WorkspaceResolve};
-pub use self::resolve::{Deps, DepsNotReplaced, Resolve};
+pub use self::features::{FeatureResolver, FeaturesFor, ResolveBehavior, ResolvedFeatures};
+pub use self::resolve::{Deps, DepsNotReplaced, Resolve, ShortestPaths};
 pub use self::types::Method;
 
 mod conflict_cache;
 mod context;
 mod encode;
+mod features;
 mod resolve;
 mod types;
 
@@ -101,6 +103,10 @@ mod types;
 ///   when sorting candidates to activate, but otherwise this isn't used
 ///   anywhere else.
 ///
+/// * `rust_version` - the version of Rust dependencies should preferably be
+///   compatible with. Candidates whose `rust-version` is newer are only tried
+///   after all of those which are compatible.
+///
 /// * `config` - a location to print warnings and such, or `None` if no warnings
 ///   should be printed
 ///
@@ -111,6 +117,7 @@ pub fn resolve(
     replacements: &[(PackageIdSpec, Dependency)],
     registry: &mut Registry,
     try_to_use: &HashSet<&PackageId>,
+    rust_version: Option<&semver::Version>,
     config: Option<&Config>,
     print_warnings: bool,
 ) -> CargoResult<Resolve> {
@@ -120,7 +127,13 @@ pub fn resolve(
         Some(config) => config.cli_unstable().minimal_versions,
         None => false,
     };
-    let mut registry = RegistryQueryer::new(registry, replacements, try_to_use, minimal_versions);
+    let mut registry = RegistryQueryer::new(
+        registry,
+        replacements,
+        try_to_use,
+        minimal_versions,
+        rust_version,
+    );
     let cx = activate_deps_loop(cx, &mut registry, summaries, config)?;
 
     let mut cksums = HashMap::new();
@@ -776,12 +789,12 @@ impl RemainingCandidates {
     }
 }
 
-// Returns if `a` and `b` are compatible in the semver sense. This is a
-// commutative operation.
-//
-// Versions `a` and `b` are compatible if their left-most nonzero digit is the
-// same.
-fn compatible(a: &semver::Version, b: &semver::Version) -> bool {
+/// Returns if `a` and `b` are compatible in the semver sense. This is a
+/// commutative operation.
+///
+/// Versions `a` and `b` are compatible if their left-most nonzero digit is the
+/// same.
+pub fn compatible(a: &semver::Version, b: &semver::Version) -> bool {
     if a.major != b.major {
         return false;
     }
@@ -1023,9 +1036,9 @@ fn activation_error(
     };
 
     if let Some(config) = config {
-        if config.cli_unstable().offline {
+        if config.offline() {
             msg.push_str(
-                "\nAs a reminder, you're using offline mode (-Z offline) \
+                "\nAs a reminder, you're using offline mode (--offline) \
                  which can sometimes cause surprising resolution failures, \
                  if this error is too confusing you may with to retry \
                  without the offline flag.",
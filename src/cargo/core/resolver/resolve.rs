@@ -1,4 +1,4 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt;
 use std::iter::FromIterator;
 
@@ -179,6 +179,30 @@ unable to verify that `{0}` is the same as when the lockfile was generated
         }
     }
 
+    /// Walks the dependency graph breadth-first from `roots`, so that the path
+    /// recorded to each package reachable from them is one of the shortest.
+    pub fn shortest_paths<'a>(&'a self, roots: &[&'a PackageId]) -> ShortestPaths<'a> {
+        let mut parents = HashMap::new();
+        let mut queue = VecDeque::new();
+        for &root in roots {
+            if !parents.contains_key(root) {
+                parents.insert(root, None);
+                queue.push_back(root);
+            }
+        }
+        while let Some(id) = queue.pop_front() {
+            let mut deps = self.deps(id).map(|(dep, _)| dep).collect::<Vec<_>>();
+            deps.sort();
+            for dep in deps {
+                if !parents.contains_key(dep) {
+                    parents.insert(dep, Some(id));
+                    queue.push_back(dep);
+                }
+            }
+        }
+        ShortestPaths { parents }
+    }
+
     pub fn replacement(&self, pkg: &PackageId) -> Option<&PackageId> {
         self.replacements.get(pkg)
     }
@@ -317,3 +341,28 @@ impl<'a> Iterator for DepsNotReplaced<'a> {
 }
 
 impl<'a> ExactSizeIterator for DepsNotReplaced<'a> {}
+
+/// The packages reachable from some packages of a `Resolve`, along with the
+/// shortest path to each of them, as found by `Resolve::shortest_paths`.
+pub struct ShortestPaths<'a> {
+    parents: HashMap<&'a PackageId, Option<&'a PackageId>>,
+}
+
+impl<'a> ShortestPaths<'a> {
+    /// The reachable packages, including the roots themselves.
+    pub fn packages<'s>(&'s self) -> impl Iterator<Item = &'a PackageId> + 's {
+        self.parents.keys().cloned()
+    }
+
+    /// Returns the path from one of the roots to `id`, which must be
+    /// reachable from them.
+    pub fn path_to(&self, mut id: &'a PackageId) -> Vec<&'a PackageId> {
+        let mut path = vec![id];
+        while let Some(parent) = self.parents[id] {
+            path.push(parent);
+            id = parent;
+        }
+        path.reverse();
+        path
+    }
+}
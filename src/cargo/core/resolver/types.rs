@@ -3,8 +3,11 @@ use std::collections::{HashMap, HashSet};
 use std::ops::Range;
 use std::rc::Rc;
 
+use semver::Version;
+
 use core::interning::InternedString;
 use core::{Dependency, PackageId, PackageIdSpec, Registry, Summary};
+use util::to_semver::rust_version_satisfied;
 use util::{CargoError, CargoResult};
 
 pub struct RegistryQueryer<'a> {
@@ -16,6 +19,9 @@ pub struct RegistryQueryer<'a> {
     // versions first. That allows `cargo update -Z minimal-versions` which will
     // specify minimum dependency versions to be used.
     minimal_versions: bool,
+    // If set, candidates whose `rust-version` is this version of Rust or
+    // older are ordered before those which need a newer one.
+    rust_version: Option<&'a Version>,
 }
 
 impl<'a> RegistryQueryer<'a> {
@@ -24,6 +30,7 @@ impl<'a> RegistryQueryer<'a> {
         replacements: &'a [(PackageIdSpec, Dependency)],
         try_to_use: &'a HashSet<&'a PackageId>,
         minimal_versions: bool,
+        rust_version: Option<&'a Version>,
     ) -> Self {
         RegistryQueryer {
             registry,
@@ -31,6 +38,7 @@ impl<'a> RegistryQueryer<'a> {
             cache: HashMap::new(),
             try_to_use,
             minimal_versions,
+            rust_version,
         }
     }
 
@@ -125,13 +133,22 @@ impl<'a> RegistryQueryer<'a> {
 
         // When we attempt versions for a package we'll want to do so in a
         // sorted fashion to pick the "best candidates" first. Currently we try
-        // prioritized summaries (those in `try_to_use`) and failing that we
-        // list everything from the maximum version to the lowest version.
+        // prioritized summaries (those in `try_to_use`), then those compatible
+        // with `rust_version` if it's set, and failing that we list everything
+        // from the maximum version to the lowest version.
+        let rust_version = self.rust_version;
+        let compatible = |s: &Summary| match (rust_version, s.rust_version()) {
+            (Some(rustc), Some(needed)) => rust_version_satisfied(needed, rustc),
+            _ => true,
+        };
         ret.sort_unstable_by(|a, b| {
             let a_in_previous = self.try_to_use.contains(a.summary.package_id());
             let b_in_previous = self.try_to_use.contains(b.summary.package_id());
             let previous_cmp = a_in_previous.cmp(&b_in_previous).reverse();
-            match previous_cmp {
+            let compatible_cmp = compatible(&a.summary)
+                .cmp(&compatible(&b.summary))
+                .reverse();
+            match previous_cmp.then(compatible_cmp) {
                 Ordering::Equal => {
                     let cmp = a.summary.version().cmp(b.summary.version());
                     if self.minimal_versions {
@@ -8,6 +8,15 @@ mod source_id;
 
 pub use self::source_id::{GitReference, SourceId};
 
+/// The result of `Source::download`.
+pub enum MaybePackage {
+    /// The package is available locally.
+    Ready(Package),
+    /// The package has to be fetched from `url` first. `descriptor` is how
+    /// the download is described to the user.
+    Download { url: String, descriptor: String },
+}
+
 /// A Source finds and downloads remote packages based on names and
 /// versions.
 pub trait Source {
@@ -49,7 +58,16 @@ pub trait Source {
 
     /// The download method fetches the full package for each name and
     /// version specified.
-    fn download(&mut self, package: &PackageId) -> CargoResult<Package>;
+    ///
+    /// Sources which need to fetch the package over the network instead
+    /// return `MaybePackage::Download`, leaving the transfer itself to the
+    /// caller so that many packages can be downloaded at once. The fetched
+    /// data is then handed back through `finish_download`.
+    fn download(&mut self, package: &PackageId) -> CargoResult<MaybePackage>;
+
+    /// Completes a download requested by `download`, given the contents of
+    /// the URL it returned.
+    fn finish_download(&mut self, package: &PackageId, data: Vec<u8>) -> CargoResult<Package>;
 
     /// Generates a unique string which represents the fingerprint of the
     /// current state of the source.
@@ -111,10 +129,72 @@ impl<'a, T: Source + ?Sized + 'a> Source for Box<T> {
     }
 
     /// Forwards to `Source::download`
-    fn download(&mut self, id: &PackageId) -> CargoResult<Package> {
+    fn download(&mut self, id: &PackageId) -> CargoResult<MaybePackage> {
+        (**self).download(id)
+    }
+
+    /// Forwards to `Source::finish_download`
+    fn finish_download(&mut self, id: &PackageId, data: Vec<u8>) -> CargoResult<Package> {
+        (**self).finish_download(id, data)
+    }
+
+    /// Forwards to `Source::fingerprint`
+    fn fingerprint(&self, pkg: &Package) -> CargoResult<String> {
+        (**self).fingerprint(pkg)
+    }
+
+    /// Forwards to `Source::verify`
+    fn verify(&self, pkg: &PackageId) -> CargoResult<()> {
+        (**self).verify(pkg)
+    }
+}
+
+impl<'a, T: Source + ?Sized + 'a> Source for &'a mut T {
+    /// Forwards to `Source::supports_checksums`
+    fn supports_checksums(&self) -> bool {
+        (**self).supports_checksums()
+    }
+
+    /// Forwards to `Source::requires_precise`
+    fn requires_precise(&self) -> bool {
+        (**self).requires_precise()
+    }
+
+    /// Forwards to `Source::query`
+    fn query(&mut self, dep: &Dependency, f: &mut FnMut(Summary)) -> CargoResult<()> {
+        (**self).query(dep, f)
+    }
+
+    /// Forwards to `Source::query`
+    fn fuzzy_query(&mut self, dep: &Dependency, f: &mut FnMut(Summary)) -> CargoResult<()> {
+        (**self).fuzzy_query(dep, f)
+    }
+
+    /// Forwards to `Source::source_id`
+    fn source_id(&self) -> &SourceId {
+        (**self).source_id()
+    }
+
+    /// Forwards to `Source::replaced_source_id`
+    fn replaced_source_id(&self) -> &SourceId {
+        (**self).replaced_source_id()
+    }
+
+    /// Forwards to `Source::update`
+    fn update(&mut self) -> CargoResult<()> {
+        (**self).update()
+    }
+
+    /// Forwards to `Source::download`
+    fn download(&mut self, id: &PackageId) -> CargoResult<MaybePackage> {
         (**self).download(id)
     }
 
+    /// Forwards to `Source::finish_download`
+    fn finish_download(&mut self, id: &PackageId, data: Vec<u8>) -> CargoResult<Package> {
+        (**self).finish_download(id, data)
+    }
+
     /// Forwards to `Source::fingerprint`
     fn fingerprint(&self, pkg: &Package) -> CargoResult<String> {
         (**self).fingerprint(pkg)
@@ -226,6 +226,12 @@ impl SourceId {
         }
     }
 
+    /// Is this source a registry whose index is fetched file by file over
+    /// HTTP, indicated by a `sparse+` prefix on the index URL
+    pub fn is_sparse(&self) -> bool {
+        self.inner.kind == Kind::Registry && self.inner.url.scheme().starts_with("sparse+")
+    }
+
     /// Is this source from an alternative registry
     pub fn is_alt_registry(&self) -> bool {
         self.is_registry() && self.inner.name.is_some()
@@ -251,6 +257,9 @@ impl SourceId {
                 };
                 Ok(Box::new(PathSource::new(&path, self, config)))
             }
+            Kind::Registry if self.is_sparse() => {
+                Ok(Box::new(RegistrySource::sparse(self, config)))
+            }
             Kind::Registry => Ok(Box::new(RegistrySource::remote(self, config))),
             Kind::LocalRegistry => {
                 let path = match self.inner.url.to_file_path() {
@@ -29,6 +29,7 @@ struct Inner {
     checksum: Option<String>,
     links: Option<InternedString>,
     namespaced_features: bool,
+    rust_version: Option<Version>,
 }
 
 impl Summary {
@@ -65,6 +66,7 @@ impl Summary {
                 checksum: None,
                 links: links.map(|l| InternedString::new(l.as_ref())),
                 namespaced_features,
+                rust_version: None,
             }),
         })
     }
@@ -96,6 +98,10 @@ impl Summary {
     pub fn namespaced_features(&self) -> bool {
         self.inner.namespaced_features
     }
+    /// The oldest version of Rust this package supports, if it declares one.
+    pub fn rust_version(&self) -> Option<&Version> {
+        self.inner.rust_version.as_ref()
+    }
 
     pub fn override_id(mut self, id: PackageId) -> Summary {
         Rc::make_mut(&mut self.inner).package_id = id;
@@ -107,6 +113,11 @@ impl Summary {
         self
     }
 
+    pub fn set_rust_version(mut self, rust_version: Version) -> Summary {
+        Rc::make_mut(&mut self.inner).rust_version = Some(rust_version);
+        self
+    }
+
     pub fn map_dependencies<F>(mut self, f: F) -> Summary
     where
         F: FnMut(Dependency) -> Dependency,
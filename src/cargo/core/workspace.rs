@@ -9,8 +9,9 @@ use url::Url;
 
 use core::profiles::Profiles;
 use core::registry::PackageRegistry;
+use core::resolver::ResolveBehavior;
 use core::{Dependency, PackageIdSpec};
-use core::{EitherManifest, Package, SourceId, VirtualManifest};
+use core::{EitherManifest, Package, SourceId, Summary, VirtualManifest};
 use ops;
 use sources::PathSource;
 use util::errors::{CargoResult, CargoResultExt};
@@ -199,6 +200,16 @@ impl<'cfg> Workspace<'cfg> {
         Ok(ws)
     }
 
+    /// Replaces the summary of the member with the manifest at
+    /// `manifest_path`, so that changes which haven't been written to its
+    /// manifest yet can be resolved.
+    pub fn set_member_summary(&mut self, manifest_path: &Path, summary: Summary) {
+        let key = manifest_path.parent().unwrap();
+        if let Some(&mut MaybePackage::Package(ref mut p)) = self.packages.packages.get_mut(key) {
+            p.manifest_mut().set_summary(summary);
+        }
+    }
+
     /// Returns the current package of this workspace.
     ///
     /// Note that this can return an error if it the current manifest is
@@ -245,6 +256,19 @@ impl<'cfg> Workspace<'cfg> {
         }
     }
 
+    /// Returns which feature resolver to use, as set by the `resolver` key
+    /// of the root manifest.
+    pub fn resolve_behavior(&self) -> ResolveBehavior {
+        let root = self.root_manifest
+            .as_ref()
+            .unwrap_or(&self.current_manifest);
+        let behavior = match *self.packages.get(root) {
+            MaybePackage::Package(ref p) => p.manifest().resolve_behavior(),
+            MaybePackage::Virtual(ref vm) => vm.resolve_behavior(),
+        };
+        behavior.unwrap_or(ResolveBehavior::V1)
+    }
+
     /// Returns the root path of this workspace.
     ///
     /// That is, this returns the path of the directory containing the
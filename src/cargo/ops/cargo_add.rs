@@ -0,0 +1,546 @@
+//! Implementation of `cargo add` and `cargo rm`, and of the manifest edits
+//! made by `cargo update --breaking`.
+//!
+//! Manifests are edited with `toml_edit` rather than by round-tripping through
+//! `util::toml::TomlManifest`, so that comments, whitespace and the order of
+//! keys written by the user are all preserved.
+
+use std::iter::FromIterator;
+use std::path::{Path, PathBuf};
+
+use semver::VersionReq;
+use toml_edit::{self, Document, InlineTable, Item, Value};
+
+use core::dependency::Kind;
+use core::registry::{PackageRegistry, Registry};
+use core::{Dependency, GitReference, SourceId, Summary, Workspace};
+use ops;
+use util::errors::CargoResult;
+use util::{paths, Config};
+
+/// Where the dependencies added by `cargo add` come from.
+pub enum AddSource {
+    /// A registry, given by name, or crates.io if `None`.
+    Registry(Option<String>),
+    /// A package on the local filesystem.
+    Path(PathBuf),
+    /// A git repository, optionally at a specific branch, tag or revision.
+    Git(String, Option<GitReference>),
+}
+
+pub struct AddOptions<'a> {
+    pub config: &'a Config,
+    /// The crates to add, each optionally followed by `@<version requirement>`.
+    pub crates: Vec<String>,
+    pub source: AddSource,
+    /// Which dependency table to add the crates to.
+    pub kind: Kind,
+    /// Add the crates to a platform-specific table for this target or `cfg`.
+    pub target: Option<String>,
+    pub features: Vec<String>,
+    pub no_default_features: bool,
+    pub optional: bool,
+    /// The name to import the crate under, if different from the package name.
+    pub rename: Option<String>,
+}
+
+pub struct RemoveOptions<'a> {
+    pub config: &'a Config,
+    /// The names (or renamed keys) of the dependencies to remove.
+    pub crates: Vec<String>,
+    pub kind: Kind,
+    pub target: Option<String>,
+}
+
+/// The keys of a dependency entry which select where it comes from. When an
+/// existing entry is updated, any of these not set by the new entry are
+/// removed so the two sources don't conflict.
+const SOURCE_KEYS: &[&str] = &["version", "registry", "path", "git", "branch", "tag", "rev"];
+
+/// Executes `cargo add`, editing the manifest at `manifest_path` in place.
+pub fn add(manifest_path: &Path, opts: &AddOptions) -> CargoResult<()> {
+    let config = opts.config;
+    if opts.crates.len() > 1 {
+        match opts.source {
+            AddSource::Registry(_) => {}
+            _ => bail!("cannot add more than one crate with `--path` or `--git`"),
+        }
+        if opts.rename.is_some() {
+            bail!("cannot add more than one crate with `--rename`")
+        }
+        if !opts.features.is_empty() {
+            bail!("cannot add more than one crate with `--features`")
+        }
+    }
+
+    let mut manifest = LocalManifest::open(manifest_path)?;
+    let table_path = table_path(opts.kind, &opts.target);
+    let table_name = table_path.join(".");
+
+    let mut crates = opts.crates.clone();
+    let path_package = match opts.source {
+        AddSource::Path(ref path) => {
+            let source_id = SourceId::for_path(path)?;
+            let (pkg, _) = ops::read_package(&path.join("Cargo.toml"), &source_id, config)?;
+            if crates.is_empty() {
+                crates.push(pkg.name().to_string());
+            }
+            Some(pkg)
+        }
+        _ => None,
+    };
+    if crates.is_empty() {
+        bail!("no crates were specified to add")
+    }
+
+    for krate in crates.iter() {
+        let (name, req) = match krate.find('@') {
+            Some(i) => (&krate[..i], Some(&krate[i + 1..])),
+            None => (&krate[..], None),
+        };
+
+        let mut entry = DependencyEntry::default();
+        let description = match opts.source {
+            AddSource::Registry(ref registry) => {
+                let registry = registry.as_ref().map(|s| s.as_str());
+                let summary = query_registry(config, name, req, registry)?;
+                let description = match req {
+                    Some(req) => {
+                        entry.set("version", req);
+                        format!("{} {}", name, req)
+                    }
+                    None => {
+                        entry.set("version", summary.version().to_string());
+                        format!("{} v{}", name, summary.version())
+                    }
+                };
+                if let Some(registry) = registry {
+                    entry.set("registry", registry);
+                }
+                description
+            }
+            AddSource::Path(ref path) => {
+                let pkg = path_package.as_ref().unwrap();
+                if pkg.name().as_str() != name {
+                    bail!(
+                        "the package at `{}` is named `{}`, not `{}`",
+                        path.display(),
+                        pkg.name(),
+                        name
+                    )
+                }
+                if req.is_some() {
+                    bail!("cannot specify a version requirement with `--path`")
+                }
+                let manifest_dir = manifest_path.parent().unwrap();
+                entry.set("path", relative_path(manifest_dir, path));
+                format!("{} ({})", name, path.display())
+            }
+            AddSource::Git(ref url, ref reference) => {
+                if req.is_some() {
+                    bail!("cannot specify a version requirement with `--git`")
+                }
+                entry.set("git", url.as_str());
+                match *reference {
+                    Some(GitReference::Branch(ref b)) => entry.set("branch", b.as_str()),
+                    Some(GitReference::Tag(ref t)) => entry.set("tag", t.as_str()),
+                    Some(GitReference::Rev(ref r)) => entry.set("rev", r.as_str()),
+                    None => {}
+                }
+                format!("{} ({})", name, url)
+            }
+        };
+
+        let key = match opts.rename {
+            Some(ref rename) => {
+                entry.set("package", name);
+                rename.as_str()
+            }
+            None => name,
+        };
+        if !opts.features.is_empty() {
+            let features = Value::from_iter(opts.features.iter().map(|s| s.as_str()));
+            entry.set("features", features);
+        }
+        if opts.no_default_features {
+            entry.set("default-features", false);
+        }
+        if opts.optional {
+            entry.set("optional", true);
+        }
+
+        config
+            .shell()
+            .status("Adding", format!("{} to {}", description, table_name))?;
+        let table = manifest.table_mut(&table_path)?;
+        let existing = &mut table[key];
+        if existing.is_none() {
+            *existing = entry.to_item();
+        } else {
+            entry.merge_into(existing);
+        }
+    }
+
+    manifest.write()
+}
+
+/// Executes `cargo rm`, editing the manifest at `manifest_path` in place.
+pub fn remove(manifest_path: &Path, opts: &RemoveOptions) -> CargoResult<()> {
+    let mut manifest = LocalManifest::open(manifest_path)?;
+    let table_path = table_path(opts.kind, &opts.target);
+    let table_name = table_path.join(".");
+
+    for name in opts.crates.iter() {
+        let removed = match find_table(&mut manifest.doc.root, &table_path) {
+            Some(&mut Item::Table(ref mut t)) => t.remove(name).is_some(),
+            Some(&mut Item::Value(Value::InlineTable(ref mut t))) => t.remove(name).is_some(),
+            _ => false,
+        };
+        if !removed {
+            bail!(
+                "the dependency `{}` could not be found in `{}`",
+                name,
+                table_name
+            )
+        }
+        opts.config
+            .shell()
+            .status("Removing", format!("{} from {}", name, table_name))?;
+    }
+
+    // Don't leave an empty dependency table behind.
+    let empty = match find_table(&mut manifest.doc.root, &table_path) {
+        Some(&mut Item::Table(ref t)) => t.iter().next().is_none(),
+        _ => false,
+    };
+    if empty {
+        let (last, parent) = table_path.split_last().unwrap();
+        if let Some(&mut Item::Table(ref mut t)) = find_table(&mut manifest.doc.root, parent) {
+            t.remove(last);
+        }
+    }
+
+    manifest.write()
+}
+
+/// Raises the version requirements of the registry dependencies of the
+/// members of `ws` which don't accept the newest version of the package to
+/// require that version, for `cargo update --breaking`.
+///
+/// Only dependencies on the packages named in `names` are changed, or all of
+/// them if it's empty. Nothing is written: the members of `ws` are changed in
+/// memory so the new requirements can be resolved, and the edited manifests
+/// are returned to be written once that succeeds.
+pub fn upgrade_requirements(
+    ws: &mut Workspace,
+    names: &[String],
+) -> CargoResult<UpgradedManifests> {
+    let config = ws.config();
+    let mut registry = PackageRegistry::new(config)?;
+    registry.lock_patches();
+
+    let mut manifests = Vec::new();
+    let mut summaries = Vec::new();
+    for member in ws.members() {
+        let path = member.manifest_path();
+        let mut manifest = LocalManifest::open(path)?;
+        let mut raised = Vec::new();
+        for dep in member.dependencies() {
+            let name = dep.package_name();
+            if !dep.source_id().is_registry() || !dep.specified_req() {
+                continue;
+            }
+            if !names.is_empty() && !names.iter().any(|n| *n == name.as_str()) {
+                continue;
+            }
+            // Pre-releases are only upgraded to if one is already required.
+            let prerelease = names_prerelease(dep.version_req());
+            let query = Dependency::parse_no_deprecated(&name, None, dep.source_id())?;
+            let latest = registry
+                .query_vec(&query, false)?
+                .into_iter()
+                .map(|s| s.version().clone())
+                .filter(|v| prerelease || !v.is_prerelease())
+                .max();
+            let latest = match latest {
+                Some(ref v) if !dep.version_req().matches(v) => v.to_string(),
+                _ => continue,
+            };
+
+            let target = dep.platform().map(|p| p.to_string());
+            let old = match find_table(&mut manifest.doc.root, &table_path(dep.kind(), &target)) {
+                Some(table) => set_version(&mut table[dep.name_in_toml().as_str()], &latest),
+                None => None,
+            };
+            if let Some(old) = old {
+                config.shell().status(
+                    "Upgrading",
+                    format!(
+                        "requirement of {} on {} from `{}` to `{}`",
+                        member.name(),
+                        name,
+                        old,
+                        latest
+                    ),
+                )?;
+                raised.push((dep.clone(), VersionReq::parse(&latest)?));
+            }
+        }
+        if !raised.is_empty() {
+            let summary = member.summary().clone().map_dependencies(|mut dep| {
+                if let Some(&(_, ref req)) = raised.iter().find(|r| r.0 == dep) {
+                    dep.set_version_req(req.clone());
+                }
+                dep
+            });
+            summaries.push((path.to_path_buf(), summary));
+            manifests.push(manifest);
+        }
+    }
+    for (path, summary) in summaries {
+        ws.set_member_summary(&path, summary);
+    }
+    Ok(UpgradedManifests { manifests })
+}
+
+/// The manifests of the workspace members whose requirements were raised by
+/// `upgrade_requirements`.
+pub struct UpgradedManifests {
+    manifests: Vec<LocalManifest>,
+}
+
+impl UpgradedManifests {
+    pub fn write(&self) -> CargoResult<()> {
+        for manifest in self.manifests.iter() {
+            manifest.write()?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether any of the versions in `req` has a pre-release part, like the
+/// `1.0.0-beta.1` in `^1.0.0-beta.1`.
+fn names_prerelease(req: &VersionReq) -> bool {
+    req.to_string()
+        .split(',')
+        .any(|predicate| predicate.contains('-'))
+}
+
+/// Replaces the version requirement of a dependency entry, returning the old
+/// one, or `None` if the entry doesn't have one.
+fn set_version(item: &mut Item, version: &str) -> Option<String> {
+    let old = item.as_value().and_then(|v| v.as_str()).map(|s| s.to_string());
+    if let Some(old) = old {
+        *item = toml_edit::value(version);
+        return Some(old);
+    }
+    if let Some(table) = item.as_value_mut().and_then(|v| v.as_inline_table_mut()) {
+        let old = table.get("version")?.as_str()?.to_string();
+        *table.get_mut("version").unwrap() = Value::from(version);
+        table.fmt();
+        return Some(old);
+    }
+    if let Some(table) = item.as_table_mut() {
+        let old = table["version"].as_value()?.as_str()?.to_string();
+        table["version"] = toml_edit::value(version);
+        return Some(old);
+    }
+    None
+}
+
+/// A `Cargo.toml` file loaded in a format-preserving representation.
+struct LocalManifest {
+    path: PathBuf,
+    doc: Document,
+}
+
+impl LocalManifest {
+    fn open(path: &Path) -> CargoResult<LocalManifest> {
+        let contents = paths::read(path)?;
+        let doc = contents
+            .parse::<Document>()
+            .map_err(|e| format_err!("failed to parse manifest at `{}`\n\n{}", path.display(), e))?;
+        Ok(LocalManifest {
+            path: path.to_path_buf(),
+            doc,
+        })
+    }
+
+    /// Returns the table at `table_path`, creating it (and any missing parent
+    /// tables) if needed.
+    fn table_mut(&mut self, table_path: &[String]) -> CargoResult<&mut Item> {
+        fn descend<'a>(item: &'a mut Item, path: &[String]) -> CargoResult<&'a mut Item> {
+            let (segment, rest) = match path.split_first() {
+                Some(pair) => pair,
+                None => return Ok(item),
+            };
+            let next = &mut item[segment.as_str()];
+            if next.is_none() {
+                let mut table = toml_edit::Table::new();
+                // Parents of the table we're after, like `[target]`, don't
+                // need a header of their own.
+                table.set_implicit(!rest.is_empty());
+                *next = Item::Table(table);
+            }
+            if !next.is_table_like() {
+                bail!("the `{}` key in the manifest is not a table", segment)
+            }
+            descend(next, rest)
+        }
+        descend(&mut self.doc.root, table_path)
+    }
+
+    fn write(&self) -> CargoResult<()> {
+        paths::write(&self.path, self.doc.to_string().as_bytes())
+    }
+}
+
+/// Returns the table at `table_path`, if it exists.
+fn find_table<'a>(item: &'a mut Item, table_path: &[String]) -> Option<&'a mut Item> {
+    match table_path.split_first() {
+        None => Some(item),
+        Some((segment, rest)) => match item.as_table_mut().and_then(|t| t.get_mut(segment)) {
+            Some(next) => find_table(next, rest),
+            None => None,
+        },
+    }
+}
+
+/// The keys of the dependency table for `kind` and `target`, like
+/// `["target", "cfg(unix)", "dev-dependencies"]`.
+fn table_path(kind: Kind, target: &Option<String>) -> Vec<String> {
+    let section = match kind {
+        Kind::Normal => "dependencies",
+        Kind::Development => "dev-dependencies",
+        Kind::Build => "build-dependencies",
+    };
+    match *target {
+        Some(ref target) => vec!["target".to_string(), target.clone(), section.to_string()],
+        None => vec![section.to_string()],
+    }
+}
+
+/// The keys of a dependency entry, in the order they're written.
+#[derive(Default)]
+struct DependencyEntry {
+    fields: Vec<(&'static str, Value)>,
+}
+
+impl DependencyEntry {
+    fn set<V: Into<Value>>(&mut self, key: &'static str, value: V) {
+        self.fields.push((key, value.into()));
+    }
+
+    fn has(&self, key: &str) -> bool {
+        self.fields.iter().any(|&(k, _)| k == key)
+    }
+
+    fn to_item(&self) -> Item {
+        // A dependency with nothing but a version is written as `foo = "1.0"`.
+        if self.fields.len() == 1 && self.has("version") {
+            return toml_edit::value(self.fields[0].1.clone());
+        }
+        let mut table = InlineTable::default();
+        for &(key, ref value) in self.fields.iter() {
+            table.get_or_insert(key, value.clone());
+        }
+        table.fmt();
+        toml_edit::value(Value::InlineTable(table))
+    }
+
+    /// Updates an existing entry in place, keeping any keys (and their
+    /// position) which this entry doesn't change.
+    fn merge_into(&self, item: &mut Item) {
+        if let Some(table) = item.as_value_mut().and_then(|v| v.as_inline_table_mut()) {
+            for key in SOURCE_KEYS.iter().filter(|k| !self.has(k)) {
+                table.remove(key);
+            }
+            for &(key, ref value) in self.fields.iter() {
+                if table.contains_key(key) {
+                    *table.get_mut(key).unwrap() = value.clone();
+                } else {
+                    table.get_or_insert(key, value.clone());
+                }
+            }
+            table.fmt();
+            return;
+        }
+        if let Some(table) = item.as_table_mut() {
+            for key in SOURCE_KEYS.iter().filter(|k| !self.has(k)) {
+                table.remove(key);
+            }
+            for &(key, ref value) in self.fields.iter() {
+                table[key] = toml_edit::value(value.clone());
+            }
+            return;
+        }
+        *item = self.to_item();
+    }
+}
+
+/// Finds the newest version of `name` matching `req` in the given registry.
+fn query_registry(
+    config: &Config,
+    name: &str,
+    req: Option<&str>,
+    registry: Option<&str>,
+) -> CargoResult<Summary> {
+    let source_id = match registry {
+        Some(registry) => SourceId::alt_registry(config, registry)?,
+        None => SourceId::crates_io(config)?,
+    };
+    let dep = Dependency::parse_no_deprecated(name, req, &source_id)?;
+    let mut registry = PackageRegistry::new(config)?;
+    registry.lock_patches();
+    let summaries = registry.query_vec(&dep, false)?;
+    match summaries.into_iter().max_by_key(|s| s.version().clone()) {
+        Some(summary) => Ok(summary),
+        None => match req {
+            Some(req) => bail!(
+                "no version of `{}` matching `{}` could be found in {}",
+                name,
+                req,
+                source_id.display_registry()
+            ),
+            None => bail!(
+                "the crate `{}` could not be found in {}",
+                name,
+                source_id.display_registry()
+            ),
+        },
+    }
+}
+
+/// Returns `to` relative to the directory `from`, using `/` as the separator
+/// so the result can be written to a manifest on any platform.
+fn relative_path(from: &Path, to: &Path) -> String {
+    let from = paths::normalize_path(from);
+    let to = paths::normalize_path(to);
+    let from = from.components().collect::<Vec<_>>();
+    let to = to.components().collect::<Vec<_>>();
+    let common = from
+        .iter()
+        .zip(to.iter())
+        .take_while(|&(a, b)| a == b)
+        .count();
+    if common == 0 {
+        // Different roots (like drive letters on Windows), so there's no
+        // relative path between the two.
+        return to.iter()
+            .collect::<PathBuf>()
+            .display()
+            .to_string();
+    }
+    let mut parts = Vec::new();
+    for _ in common..from.len() {
+        parts.push("..".to_string());
+    }
+    for component in to[common..].iter() {
+        parts.push(component.as_os_str().to_string_lossy().into_owned());
+    }
+    if parts.is_empty() {
+        ".".to_string()
+    } else {
+        parts.join("/")
+    }
+}
@@ -0,0 +1,338 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use semver::VersionReq;
+use toml;
+
+use core::compiler::MessageFormat;
+use core::{PackageId, Resolve, Workspace};
+use ops;
+use util::errors::{CargoResult, CargoResultExt};
+use util::machine_message::{self, AdvisoryMatch};
+use util::paths;
+
+pub struct AdvisoriesOptions {
+    /// The directory of the advisory database, a checkout of
+    /// <https://github.com/RustSec/advisory-db> or a copy of it.
+    pub db: PathBuf,
+    pub message_format: MessageFormat,
+}
+
+/// An advisory file, as found in the RustSec advisory database.
+///
+/// The version requirements are either in the `[advisory]` table, as in
+/// older advisories, or in a `[versions]` table of their own. Advisories in
+/// the Markdown format keep this in a fenced ```` ```toml ```` block at the
+/// start of the file, and their title in the heading following it.
+#[derive(Deserialize)]
+struct AdvisoryFile {
+    advisory: RawAdvisory,
+    versions: Option<RawVersions>,
+}
+
+#[derive(Deserialize)]
+struct RawAdvisory {
+    id: String,
+    package: String,
+    title: Option<String>,
+    date: Option<String>,
+    url: Option<String>,
+    #[serde(default)]
+    patched_versions: Vec<String>,
+    #[serde(default)]
+    unaffected_versions: Vec<String>,
+    /// Set for advisories which have been withdrawn, by older databases.
+    #[serde(default)]
+    obsolete: bool,
+    /// The date on which the advisory was withdrawn.
+    withdrawn: Option<String>,
+    /// The kind of an advisory which isn't about a vulnerability, like
+    /// `unmaintained`.
+    informational: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawVersions {
+    #[serde(default)]
+    patched: Vec<String>,
+    #[serde(default)]
+    unaffected: Vec<String>,
+}
+
+struct Advisory {
+    id: String,
+    package: String,
+    title: String,
+    date: Option<String>,
+    url: Option<String>,
+    informational: Option<String>,
+    patched_versions: Vec<String>,
+    unaffected_versions: Vec<String>,
+    /// `patched_versions` and `unaffected_versions` together, parsed.
+    safe: Vec<VersionReq>,
+}
+
+impl Advisory {
+    fn affects(&self, id: &PackageId) -> bool {
+        id.name().as_str() == self.package
+            && id.source_id().is_default_registry()
+            && !self.safe.iter().any(|req| req.matches(id.version()))
+    }
+}
+
+/// Executes `cargo advisories`.
+///
+/// Every package in `Cargo.lock` is checked against the advisories in the
+/// database, and an error is returned if any of them is affected by one which
+/// isn't informational.
+pub fn advisories(ws: &Workspace, opts: &AdvisoriesOptions) -> CargoResult<()> {
+    let advisories = load_advisories(&opts.db)
+        .chain_err(|| format!("failed to load the advisory database at `{}`", opts.db.display()))?;
+    let resolve = match ops::load_pkg_lockfile(ws)? {
+        Some(resolve) => resolve,
+        None => bail!(
+            "a Cargo.lock must exist to check it against advisories, \
+             run `cargo generate-lockfile` to create one"
+        ),
+    };
+
+    let roots = ws.members().map(|m| m.package_id()).collect::<Vec<_>>();
+    let paths = dependency_paths(&resolve, &roots);
+
+    let mut ids = resolve.iter().collect::<Vec<_>>();
+    ids.sort();
+    let mut matches = Vec::new();
+    for &id in ids.iter() {
+        for advisory in advisories.iter().filter(|a| a.affects(id)) {
+            matches.push(AdvisoryMatch {
+                package_id: id,
+                id: &advisory.id,
+                title: &advisory.title,
+                date: advisory.date.as_ref().map(|s| s.as_str()),
+                url: advisory.url.as_ref().map(|s| s.as_str()),
+                informational: advisory.informational.as_ref().map(|s| s.as_str()),
+                patched_versions: &advisory.patched_versions,
+                unaffected_versions: &advisory.unaffected_versions,
+                paths: paths(id),
+            });
+        }
+    }
+
+    match opts.message_format {
+        MessageFormat::Json => {
+            for m in matches.iter() {
+                machine_message::emit(m);
+            }
+        }
+        MessageFormat::Human | MessageFormat::Short => print_report(&matches),
+    }
+
+    let informational = matches.iter().filter(|m| m.informational.is_some()).count();
+    let vulnerabilities = matches.len() - informational;
+    if vulnerabilities != 0 {
+        bail!(
+            "found {} advisor{} for packages in Cargo.lock",
+            vulnerabilities,
+            if vulnerabilities == 1 { "y" } else { "ies" }
+        );
+    }
+    if informational != 0 {
+        ws.config().shell().warn(format!(
+            "found {} informational advisor{} for packages in Cargo.lock",
+            informational,
+            if informational == 1 { "y" } else { "ies" }
+        ))?;
+    }
+    ws.config().shell().status(
+        "Checked",
+        format!(
+            "{} package{} against {} advisor{}",
+            ids.len(),
+            if ids.len() == 1 { "" } else { "s" },
+            advisories.len(),
+            if advisories.len() == 1 { "y" } else { "ies" }
+        ),
+    )?;
+    Ok(())
+}
+
+/// Loads every advisory in the database at `db`, from the `crates` directory
+/// if there is one, and otherwise from `db` itself.
+fn load_advisories(db: &Path) -> CargoResult<Vec<Advisory>> {
+    if !db.is_dir() {
+        bail!("`{}` is not a directory", db.display());
+    }
+    let crates = db.join("crates");
+    let mut files = Vec::new();
+    find_advisory_files(if crates.is_dir() { &crates } else { db }, &mut files)?;
+    files.sort();
+    if files.is_empty() && fs::read_dir(db)?.next().is_some() {
+        bail!(
+            "no advisories found in `{}`, advisories are expected in `.toml` \
+             files or `RUSTSEC-*.md` files",
+            db.display()
+        );
+    }
+
+    let mut advisories = Vec::new();
+    for file in files {
+        let (advisory, versions) = parse_advisory(&file)
+            .chain_err(|| format!("failed to parse advisory `{}`", file.display()))?;
+        if advisory.obsolete || advisory.withdrawn.is_some() {
+            continue;
+        }
+        let (patched_versions, unaffected_versions) = match versions {
+            Some(v) => (v.patched, v.unaffected),
+            None => (advisory.patched_versions, advisory.unaffected_versions),
+        };
+        let safe = patched_versions
+            .iter()
+            .chain(unaffected_versions.iter())
+            .map(|s| {
+                VersionReq::parse(s).chain_err(|| {
+                    format!(
+                        "invalid version requirement `{}` in advisory `{}`",
+                        s,
+                        file.display()
+                    )
+                })
+            })
+            .collect::<CargoResult<Vec<_>>>()?;
+        advisories.push(Advisory {
+            id: advisory.id,
+            package: advisory.package,
+            title: advisory.title.unwrap(),
+            date: advisory.date,
+            url: advisory.url,
+            informational: advisory.informational,
+            patched_versions,
+            unaffected_versions,
+            safe,
+        });
+    }
+    Ok(advisories)
+}
+
+/// Parses an advisory file, in either the TOML or the Markdown format.
+fn parse_advisory(file: &Path) -> CargoResult<(RawAdvisory, Option<RawVersions>)> {
+    let contents = paths::read(file)?;
+    let (raw, title): (AdvisoryFile, _) = if is_markdown(file) {
+        let contents = contents.trim_left();
+        if !contents.starts_with("```toml") {
+            bail!("expected the advisory to start with a ```toml block");
+        }
+        let contents = &contents["```toml".len()..];
+        let end = match contents.find("\n```") {
+            Some(end) => end,
+            None => bail!("the ```toml block of the advisory is not closed"),
+        };
+        let title = contents[end + "\n```".len()..]
+            .lines()
+            .map(|line| line.trim())
+            .find(|line| line.starts_with("# "))
+            .map(|line| line["# ".len()..].trim().to_string());
+        (toml::from_str(&contents[..end])?, title)
+    } else {
+        (toml::from_str(&contents)?, None)
+    };
+    let AdvisoryFile {
+        mut advisory,
+        versions,
+    } = raw;
+    if advisory.title.is_none() {
+        advisory.title = title;
+    }
+    if advisory.title.is_none() {
+        bail!("the advisory has no title");
+    }
+    Ok((advisory, versions))
+}
+
+fn is_markdown(path: &Path) -> bool {
+    let name = path.file_name().and_then(|s| s.to_str()).unwrap_or("");
+    name.starts_with("RUSTSEC-") && name.ends_with(".md")
+}
+
+fn find_advisory_files(dir: &Path, files: &mut Vec<PathBuf>) -> CargoResult<()> {
+    let entries = fs::read_dir(dir)
+        .chain_err(|| format!("failed to read directory `{}`", dir.display()))?;
+    for entry in entries {
+        let path = entry?.path();
+        let hidden = path.file_name()
+            .and_then(|s| s.to_str())
+            .map(|s| s.starts_with('.'))
+            .unwrap_or(false);
+        if hidden {
+            continue;
+        }
+        let is_toml = path.extension().and_then(|s| s.to_str()) == Some("toml");
+        if path.is_dir() {
+            find_advisory_files(&path, files)?;
+        } else if is_toml || is_markdown(&path) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Returns a function giving, for each package depending on a package, the
+/// shortest path from one of `roots` to the dependent, followed by the
+/// package itself.
+fn dependency_paths<'a>(
+    resolve: &'a Resolve,
+    roots: &[&'a PackageId],
+) -> Box<Fn(&'a PackageId) -> Vec<Vec<&'a PackageId>> + 'a> {
+    let paths = resolve.shortest_paths(roots);
+    let mut dependents = HashMap::new();
+    for parent in paths.packages() {
+        for (dep, _) in resolve.deps(parent) {
+            dependents.entry(dep).or_insert_with(Vec::new).push(parent);
+        }
+    }
+    for list in dependents.values_mut() {
+        list.sort();
+    }
+
+    Box::new(move |id| match dependents.get(id) {
+        Some(list) => list
+            .iter()
+            .map(|&parent| {
+                let mut path = paths.path_to(parent);
+                path.push(id);
+                path
+            })
+            .collect(),
+        // A workspace member itself.
+        None => vec![vec![id]],
+    })
+}
+
+fn print_report(matches: &[AdvisoryMatch]) {
+    for (i, m) in matches.iter().enumerate() {
+        if i != 0 {
+            println!();
+        }
+        println!("{}: {}", m.id, m.package_id);
+        println!("  title: {}", m.title);
+        if let Some(date) = m.date {
+            println!("  date: {}", date);
+        }
+        if let Some(url) = m.url {
+            println!("  url: {}", url);
+        }
+        if let Some(informational) = m.informational {
+            println!("  informational: {}", informational);
+        }
+        if m.patched_versions.is_empty() {
+            println!("  patched versions: none");
+        } else {
+            println!("  patched versions: {}", m.patched_versions.join(", "));
+        }
+        println!("  dependency paths:");
+        for path in m.paths.iter() {
+            let path = path.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+            println!("    {}", path.join(" -> "));
+        }
+    }
+}
@@ -0,0 +1,251 @@
+use std::collections::BTreeMap;
+
+use semver::VersionReq;
+use url::Url;
+
+use core::{Package, SourceId, Workspace};
+use ops;
+use util::errors::{CargoResult, CargoResultExt};
+use util::spdx::{License, LicenseExpr};
+use util::Config;
+
+/// The `[policy]` table of the configuration.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct PolicyConfig {
+    allow_licenses: Option<Vec<String>>,
+    deny_licenses: Option<Vec<String>>,
+    deny_crates: Option<BTreeMap<String, String>>,
+    allow_sources: Option<Vec<String>>,
+}
+
+/// The dependency policy, parsed from the `[policy]` table.
+struct Policy {
+    /// If set, every package's license must be satisfiable using only these.
+    allow_licenses: Option<Vec<License>>,
+    deny_licenses: Vec<License>,
+    deny_crates: Vec<(String, VersionReq)>,
+    /// If set, every package must come from a source of one of these.
+    allow_sources: Option<Vec<AllowedSource>>,
+}
+
+enum AllowedSource {
+    /// Any source of a kind, like `git`.
+    Kind(String),
+    /// A source at this URL or anywhere below it.
+    Url(Url),
+}
+
+impl Policy {
+    fn load(config: &Config) -> CargoResult<Policy> {
+        let raw = config
+            .get::<Option<PolicyConfig>>("policy")?
+            .unwrap_or_default();
+
+        let licenses = |key: &str, list: Option<Vec<String>>| -> CargoResult<Option<Vec<License>>> {
+            let list = match list {
+                Some(list) => list,
+                None => return Ok(None),
+            };
+            list.iter()
+                .map(|s| {
+                    s.parse().chain_err(|| {
+                        format!("invalid license `{}` in `policy.{}`", s, key)
+                    })
+                })
+                .collect::<CargoResult<Vec<_>>>()
+                .map(Some)
+        };
+        let allow_licenses = licenses("allow-licenses", raw.allow_licenses)?;
+        let deny_licenses = licenses("deny-licenses", raw.deny_licenses)?.unwrap_or_default();
+
+        let mut deny_crates = Vec::new();
+        for (name, req) in raw.deny_crates.unwrap_or_default() {
+            let req = VersionReq::parse(&req).chain_err(|| {
+                format!(
+                    "invalid version requirement `{}` for `{}` in `policy.deny-crates`",
+                    req, name
+                )
+            })?;
+            deny_crates.push((name, req));
+        }
+
+        let allow_sources = match raw.allow_sources {
+            Some(list) => {
+                let mut sources = Vec::new();
+                for s in list {
+                    let source = match &s[..] {
+                        "registry" | "git" | "path" | "directory" => AllowedSource::Kind(s.clone()),
+                        _ => match Url::parse(&s) {
+                            Ok(url) => AllowedSource::Url(url),
+                            Err(_) => bail!(
+                                "invalid source `{}` in `policy.allow-sources`, expected one \
+                                 of `registry`, `git`, `path` or `directory`, or a URL",
+                                s
+                            ),
+                        },
+                    };
+                    sources.push(source);
+                }
+                Some(sources)
+            }
+            None => None,
+        };
+
+        Ok(Policy {
+            allow_licenses,
+            deny_licenses,
+            deny_crates,
+            allow_sources,
+        })
+    }
+
+    fn checks_licenses(&self) -> bool {
+        self.allow_licenses.is_some() || !self.deny_licenses.is_empty()
+    }
+
+    /// Why `license` isn't acceptable, if it isn't.
+    fn license_problem(&self, license: &License) -> Option<&'static str> {
+        if self.deny_licenses.iter().any(|d| license.is(&d.id)) {
+            return Some("denied");
+        }
+        match self.allow_licenses {
+            Some(ref allowed) if !allowed.iter().any(|a| license.is(&a.id)) => Some("not allowed"),
+            _ => None,
+        }
+    }
+
+    /// Every way in which `pkg` violates the policy.
+    fn violations(&self, pkg: &Package) -> Vec<String> {
+        let mut violations = Vec::new();
+
+        for &(ref name, ref req) in self.deny_crates.iter() {
+            if pkg.name().as_str() == *name && req.matches(pkg.version()) {
+                violations.push(format!(
+                    "`{} = \"{}\"` is denied by `policy.deny-crates`",
+                    name, req
+                ));
+            }
+        }
+
+        if self.checks_licenses() {
+            let metadata = pkg.manifest().metadata();
+            match metadata.license {
+                Some(ref s) => match s.parse::<LicenseExpr>() {
+                    Ok(expr) => {
+                        if !expr.satisfiable(&|l| self.license_problem(l).is_none()) {
+                            let problems = expr
+                                .licenses()
+                                .into_iter()
+                                .filter_map(|l| {
+                                    self.license_problem(l).map(|p| format!("`{}` is {}", l, p))
+                                })
+                                .collect::<Vec<_>>();
+                            violations.push(format!(
+                                "license `{}` can't be complied with: {}",
+                                s,
+                                problems.join(", ")
+                            ));
+                        }
+                    }
+                    Err(e) => violations.push(format!(
+                        "license `{}` is not a valid SPDX expression: {}",
+                        s, e
+                    )),
+                },
+                None if metadata.license_file.is_some() => violations.push(
+                    "has no `license` field, only a `license-file` which can't be checked"
+                        .to_string(),
+                ),
+                None => violations.push("has no `license` field".to_string()),
+            }
+        }
+
+        if let Some(ref allowed) = self.allow_sources {
+            let source = pkg.package_id().source_id();
+            if !allowed.iter().any(|a| a.matches(source)) {
+                violations.push(format!(
+                    "source `{}` is not allowed by `policy.allow-sources`",
+                    source
+                ));
+            }
+        }
+
+        violations
+    }
+}
+
+impl AllowedSource {
+    fn matches(&self, source: &SourceId) -> bool {
+        match *self {
+            AllowedSource::Kind(ref kind) => match &kind[..] {
+                "registry" => source.is_registry(),
+                "git" => source.is_git(),
+                "path" => source.is_path(),
+                _ => !source.is_registry() && !source.is_git() && !source.is_path(),
+            },
+            AllowedSource::Url(ref url) => {
+                let other = source.url();
+                if url.scheme() != other.scheme()
+                    || url.host_str() != other.host_str()
+                    || url.port() != other.port()
+                {
+                    return false;
+                }
+                let prefix = url.path().trim_right_matches('/');
+                let path = other.path();
+                path == prefix || path.starts_with(&format!("{}/", prefix))
+            }
+        }
+    }
+}
+
+/// Executes `cargo audit-policy`.
+///
+/// Every package in the lock file, other than the members of the workspace
+/// themselves, is checked against the `[policy]` table of the configuration.
+pub fn audit_policy(ws: &Workspace) -> CargoResult<()> {
+    let config = ws.config();
+    let policy = Policy::load(config)?;
+    let (packages, resolve) = ops::resolve_ws(ws)?;
+
+    let mut ids = resolve
+        .iter()
+        .filter(|id| !ws.members().any(|m| m.package_id() == *id))
+        .collect::<Vec<_>>();
+    ids.sort();
+    let pkgs = packages.get_many(ids.iter().cloned())?;
+
+    let mut report = Vec::new();
+    let mut count = 0;
+    for pkg in pkgs.iter() {
+        let violations = policy.violations(pkg);
+        if violations.is_empty() {
+            continue;
+        }
+        count += 1;
+        report.push(format!("  {}", pkg.package_id()));
+        for violation in violations {
+            report.push(format!("    {}", violation));
+        }
+    }
+
+    if count > 0 {
+        bail!(
+            "{} package{} violate{} the dependency policy:\n{}",
+            count,
+            if count == 1 { "" } else { "s" },
+            if count == 1 { "s" } else { "" },
+            report.join("\n")
+        );
+    }
+    config.shell().status(
+        "Checked",
+        format!(
+            "{} package{} against the dependency policy",
+            pkgs.len(),
+            if pkgs.len() == 1 { "" } else { "s" }
+        ),
+    )?;
+    Ok(())
+}
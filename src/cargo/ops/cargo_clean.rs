@@ -1,8 +1,9 @@
 use std::fs;
 use std::path::Path;
 
-use core::compiler::{BuildConfig, BuildContext, CompileMode, Context, Kind, Unit};
+use core::compiler::{BuildConfig, BuildContext, CompileMode, Context, Kind, ProfileKind, Unit};
 use core::profiles::ProfileFor;
+use core::resolver::{FeaturesFor, Method};
 use core::Workspace;
 use ops;
 use util::errors::{CargoResult, CargoResultExt};
@@ -46,6 +47,11 @@ pub fn clean(ws: &Workspace, opts: &CleanOptions) -> CargoResult<()> {
     let (packages, resolve) = ops::resolve_ws(ws)?;
 
     let profiles = ws.profiles();
+    let profile_kind = if opts.release {
+        ProfileKind::Release
+    } else {
+        ProfileKind::Dev
+    };
     let mut units = Vec::new();
 
     for spec in opts.spec.iter() {
@@ -64,7 +70,7 @@ pub fn clean(ws: &Workspace, opts: &CleanOptions) -> CargoResult<()> {
                                 ws.is_member(pkg),
                                 *profile_for,
                                 CompileMode::Build,
-                                opts.release,
+                                &profile_kind,
                             ))
                         } else {
                             profiles.get_profile(
@@ -72,16 +78,19 @@ pub fn clean(ws: &Workspace, opts: &CleanOptions) -> CargoResult<()> {
                                 ws.is_member(pkg),
                                 *profile_for,
                                 *mode,
-                                opts.release,
+                                &profile_kind,
                             )
                         };
-                        units.push(Unit {
-                            pkg,
-                            target,
-                            profile,
-                            kind: *kind,
-                            mode: *mode,
-                        });
+                        for features in FeaturesFor::all_values() {
+                            units.push(Unit {
+                                pkg,
+                                target,
+                                profile,
+                                kind: *kind,
+                                mode: *mode,
+                                features: *features,
+                            });
+                        }
                     }
                 }
             }
@@ -89,8 +98,8 @@ pub fn clean(ws: &Workspace, opts: &CleanOptions) -> CargoResult<()> {
     }
 
     let mut build_config = BuildConfig::new(config, Some(1), &opts.target, CompileMode::Build)?;
-    build_config.release = opts.release;
-    let bcx = BuildContext::new(
+    build_config.profile = profile_kind;
+    let mut bcx = BuildContext::new(
         ws,
         &resolve,
         &packages,
@@ -99,6 +108,7 @@ pub fn clean(ws: &Workspace, opts: &CleanOptions) -> CargoResult<()> {
         profiles,
         None,
     )?;
+    bcx.resolve_features(&units, Method::Everything)?;
     let mut cx = Context::new(config, &bcx)?;
     cx.prepare_units(None, &units)?;
 
@@ -29,7 +29,7 @@ use std::sync::Arc;
 use core::compiler::{BuildConfig, BuildContext, Compilation, Context, DefaultExecutor, Executor};
 use core::compiler::{CompileMode, Kind, Unit};
 use core::profiles::{ProfileFor, Profiles};
-use core::resolver::{Method, Resolve};
+use core::resolver::{FeaturesFor, Method, Resolve};
 use core::{Package, Source, Target};
 use core::{PackageId, PackageIdSpec, TargetKind, Workspace};
 use ops;
@@ -264,6 +264,7 @@ pub fn compile_ws<'a>(
 
     let profiles = ws.profiles();
     profiles.validate_packages(&mut config.shell(), &packages)?;
+    profiles.validate_kind(&build_config.profile)?;
 
     let mut extra_compiler_args = None;
 
@@ -291,7 +292,7 @@ pub fn compile_ws<'a>(
 
     let ret = {
         let _p = profile::start("compiling");
-        let bcx = BuildContext::new(
+        let mut bcx = BuildContext::new(
             ws,
             &resolve_with_overrides,
             &packages,
@@ -300,6 +301,7 @@ pub fn compile_ws<'a>(
             profiles,
             extra_compiler_args,
         )?;
+        bcx.resolve_features(&units, method)?;
         let cx = Context::new(config, &bcx)?;
         cx.compile(&units, export_dir.clone(), &exec)?
     };
@@ -499,7 +501,7 @@ fn generate_targets<'a>(
             ws.is_member(pkg),
             profile_for,
             target_mode,
-            build_config.release,
+            &build_config.profile,
         );
         // Once the profile has been selected for benchmarks, we don't need to
         // distinguish between benches and tests. Switching the mode allows
@@ -518,6 +520,7 @@ fn generate_targets<'a>(
             profile,
             kind,
             mode: target_mode,
+            features: FeaturesFor::Target,
         }
     };
 
@@ -0,0 +1,303 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde_json::{Map, Value};
+use toml;
+
+use util::config::{ConfigValue as CV, Definition};
+use util::{CargoResult, Config};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Toml,
+    Json,
+}
+
+pub struct GetOptions<'a> {
+    /// The key to show, everything is shown if this is `None`.
+    pub key: Option<&'a str>,
+    pub format: ConfigFormat,
+    /// Annotate every value with the file or environment variable it came
+    /// from.
+    pub show_origin: bool,
+}
+
+/// A single value of the merged configuration, along with its full key.
+struct Leaf {
+    key: Vec<String>,
+    value: LeafValue,
+    definition: Definition,
+}
+
+enum LeafValue {
+    Integer(i64),
+    Boolean(bool),
+    String(String),
+    /// The elements of a list may each come from a different file.
+    List(Vec<(String, Definition)>),
+}
+
+/// The tables whose keys can be recovered from the name of their `CARGO_*`
+/// environment variable, as none of them have nested tables.
+const ENV_TABLES: &[&str] = &[
+    "build", "cargo-new", "http", "install", "net", "registry", "term",
+];
+
+/// Executes `cargo config get`.
+///
+/// The configuration files are merged as Cargo normally does, and every value
+/// they set below `key` is printed, with the value of the `CARGO_*`
+/// environment variable for its key instead if that's set. Without a key, the
+/// environment variables of keys no file sets are shown too. The credentials
+/// file isn't shown, as it only holds secrets.
+pub fn get(config: &Config, opts: &GetOptions) -> CargoResult<()> {
+    if opts.format == ConfigFormat::Json && opts.show_origin {
+        bail!(
+            "the `json` format does not support --show-origin, \
+             try the `toml` format instead"
+        );
+    }
+
+    let values = config.values()?;
+    let credentials = config.home().join("credentials").into_path_unlocked();
+    let mut leaves = Vec::new();
+    match opts.key {
+        Some(key) => {
+            let parts = key.split('.').map(|s| s.to_string()).collect::<Vec<_>>();
+            let mut value = values.get(&parts[0]);
+            for part in parts[1..].iter() {
+                value = match value {
+                    Some(&CV::Table(ref table, _)) => table.get(part),
+                    _ => None,
+                };
+            }
+            if let Some(value) = value {
+                collect(config, parts, value, &credentials, &mut leaves)?;
+            }
+            // A key which no file sets may still be set in the environment.
+            if leaves.is_empty() {
+                if let Some(env) = config.get_env_string(key)? {
+                    leaves.push(Leaf {
+                        key: key.split('.').map(|s| s.to_string()).collect(),
+                        value: LeafValue::String(env.val),
+                        definition: env.definition,
+                    });
+                }
+            }
+            if leaves.is_empty() {
+                bail!("config value `{}` is not set", key);
+            }
+        }
+        None => {
+            collect_table(config, Vec::new(), values, &credentials, &mut leaves)?;
+            collect_env(config, &mut leaves);
+            leaves.sort_by(|a, b| a.key.cmp(&b.key));
+        }
+    }
+
+    match opts.format {
+        ConfigFormat::Toml => {
+            for leaf in leaves.iter() {
+                print_toml(leaf, opts.show_origin);
+            }
+        }
+        ConfigFormat::Json => {
+            let mut root = Map::new();
+            for leaf in leaves {
+                insert_json(&mut root, leaf);
+            }
+            ::print_json(&Value::Object(root));
+        }
+    }
+    Ok(())
+}
+
+fn collect(
+    config: &Config,
+    key: Vec<String>,
+    value: &CV,
+    credentials: &Path,
+    leaves: &mut Vec<Leaf>,
+) -> CargoResult<()> {
+    if let CV::Table(ref table, _) = *value {
+        return collect_table(config, key, table, credentials, leaves);
+    }
+    if *value.definition() == Definition::Path(credentials.to_path_buf()) {
+        return Ok(());
+    }
+
+    let definition = value.definition().clone();
+    let mut leaf = Leaf {
+        value: match *value {
+            CV::Integer(i, _) => LeafValue::Integer(i),
+            CV::Boolean(b, _) => LeafValue::Boolean(b),
+            CV::String(ref s, _) => LeafValue::String(s.clone()),
+            CV::List(ref list, _) => LeafValue::List(
+                list.iter()
+                    .map(|&(ref s, ref def)| (s.clone(), def.clone()))
+                    .collect(),
+            ),
+            CV::Table(..) => unreachable!(),
+        },
+        key,
+        definition,
+    };
+
+    // The environment takes precedence over the files, and its value is
+    // interpreted the way the file's value would be.
+    if let Some(env) = config.get_env_string(&leaf.key.join("."))? {
+        leaf.value = match leaf.value {
+            LeafValue::Integer(_) if env.val.parse::<i64>().is_ok() => {
+                LeafValue::Integer(env.val.parse().unwrap())
+            }
+            LeafValue::Boolean(_) if env.val.parse::<bool>().is_ok() => {
+                LeafValue::Boolean(env.val.parse().unwrap())
+            }
+            LeafValue::List(_) => LeafValue::List(
+                env.val
+                    .split(' ')
+                    .map(|s| (s.to_string(), env.definition.clone()))
+                    .collect(),
+            ),
+            _ => LeafValue::String(env.val),
+        };
+        leaf.definition = env.definition;
+    }
+    leaves.push(leaf);
+    Ok(())
+}
+
+/// Adds the `CARGO_*` environment variables of the keys in `ENV_TABLES` which
+/// aren't among `leaves` yet, like `CARGO_BUILD_JOBS` for `build.jobs`.
+fn collect_env(config: &Config, leaves: &mut Vec<Leaf>) {
+    let collected = leaves
+        .iter()
+        .map(|leaf| env_name(&leaf.key))
+        .collect::<Vec<_>>();
+    let mut names = config.env_vars().keys().collect::<Vec<_>>();
+    names.sort();
+    for name in names {
+        if collected.contains(name) {
+            continue;
+        }
+        for table in ENV_TABLES {
+            let prefix = env_name(&[table.to_string()]) + "_";
+            if !name.starts_with(&prefix) || name.len() == prefix.len() {
+                continue;
+            }
+            let field = name[prefix.len()..].to_lowercase().replace('_', "-");
+            leaves.push(Leaf {
+                key: vec![table.to_string(), field],
+                value: LeafValue::String(config.env_vars()[name].clone()),
+                definition: Definition::Environment(name.clone()),
+            });
+        }
+    }
+}
+
+/// The name of the environment variable for `key`, the way `Config` derives
+/// it.
+fn env_name(key: &[String]) -> String {
+    let mut name = "CARGO".to_string();
+    for part in key {
+        name.push('_');
+        name.push_str(&part.to_uppercase().replace('-', "_"));
+    }
+    name
+}
+
+fn collect_table(
+    config: &Config,
+    key: Vec<String>,
+    table: &HashMap<String, CV>,
+    credentials: &Path,
+    leaves: &mut Vec<Leaf>,
+) -> CargoResult<()> {
+    let mut names = table.keys().collect::<Vec<_>>();
+    names.sort();
+    for name in names {
+        let mut key = key.clone();
+        key.push(name.clone());
+        collect(config, key, &table[name], credentials, leaves)?;
+    }
+    Ok(())
+}
+
+fn print_toml(leaf: &Leaf, show_origin: bool) {
+    let key = leaf.key
+        .iter()
+        .map(|part| toml_key(part))
+        .collect::<Vec<_>>()
+        .join(".");
+    let origin = |definition: &Definition| {
+        if show_origin {
+            format!(" # {}", definition)
+        } else {
+            String::new()
+        }
+    };
+    match leaf.value {
+        LeafValue::Integer(i) => println!("{} = {}{}", key, i, origin(&leaf.definition)),
+        LeafValue::Boolean(b) => println!("{} = {}{}", key, b, origin(&leaf.definition)),
+        LeafValue::String(ref s) => println!(
+            "{} = {}{}",
+            key,
+            toml::Value::String(s.clone()),
+            origin(&leaf.definition)
+        ),
+        LeafValue::List(ref list) if show_origin => {
+            println!("{} = [", key);
+            for &(ref s, ref definition) in list.iter() {
+                println!(
+                    "    {},{}",
+                    toml::Value::String(s.clone()),
+                    origin(definition)
+                );
+            }
+            println!("]");
+        }
+        LeafValue::List(ref list) => {
+            let list = list.iter()
+                .map(|&(ref s, _)| toml::Value::String(s.clone()).to_string())
+                .collect::<Vec<_>>();
+            println!("{} = [{}]", key, list.join(", "));
+        }
+    }
+}
+
+/// Quotes a part of a key if it can't be written bare, like the `cfg(...)`
+/// of a `[target.'cfg(...)']` table.
+fn toml_key(part: &str) -> String {
+    let bare = !part.is_empty()
+        && part.chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if bare {
+        part.to_string()
+    } else {
+        toml::Value::String(part.to_string()).to_string()
+    }
+}
+
+fn insert_json(root: &mut Map<String, Value>, leaf: Leaf) {
+    let value = match leaf.value {
+        LeafValue::Integer(i) => Value::from(i),
+        LeafValue::Boolean(b) => Value::from(b),
+        LeafValue::String(s) => Value::from(s),
+        LeafValue::List(list) => {
+            Value::from(list.into_iter().map(|(s, _)| s).collect::<Vec<_>>())
+        }
+    };
+    let (last, parents) = leaf.key.split_last().unwrap();
+    let mut table = root;
+    for part in parents {
+        let current = table;
+        let entry = current
+            .entry(part.clone())
+            .or_insert_with(|| Value::Object(Map::new()));
+        table = match *entry {
+            Value::Object(ref mut map) => map,
+            _ => unreachable!(),
+        };
+    }
+    table.insert(last.clone(), value);
+}
@@ -0,0 +1,161 @@
+use std::collections::{BTreeMap, HashMap};
+
+use core::compiler::MessageFormat;
+use core::{PackageId, Resolve, Workspace};
+use ops::{self, Packages};
+use util::machine_message::{self, DuplicateDependent, DuplicatePackage, DuplicateVersion};
+use util::CargoResult;
+
+pub struct DuplicatesOptions {
+    pub features: Vec<String>,
+    pub no_default_features: bool,
+    pub all_features: bool,
+    /// The packages whose dependency graph is checked.
+    pub spec: Packages,
+    pub message_format: MessageFormat,
+    /// Fail if any package appears in more than one version.
+    pub deny: bool,
+}
+
+/// Executes `cargo duplicates`.
+pub fn duplicates(ws: &Workspace, opts: &DuplicatesOptions) -> CargoResult<()> {
+    let specs = opts.spec.to_package_id_specs(ws)?;
+    let (_packages, resolve) = ops::resolve_ws_precisely(
+        ws,
+        None,
+        &opts.features,
+        opts.all_features,
+        opts.no_default_features,
+        &specs,
+    )?;
+    let roots = specs
+        .iter()
+        .map(|spec| spec.query(resolve.iter()))
+        .collect::<CargoResult<Vec<_>>>()?;
+
+    let report = find_duplicates(&resolve, &roots);
+    match opts.message_format {
+        MessageFormat::Json => {
+            for duplicate in report.iter() {
+                machine_message::emit(duplicate);
+            }
+        }
+        MessageFormat::Human | MessageFormat::Short => print_report(&report),
+    }
+
+    if opts.deny && !report.is_empty() {
+        bail!(
+            "found {} package{} with more than one version",
+            report.len(),
+            if report.len() == 1 { "" } else { "s" }
+        );
+    }
+    Ok(())
+}
+
+/// Finds every package name which is used in more than one version by the
+/// packages reachable from `roots`, along with why each version is used.
+fn find_duplicates<'a>(
+    resolve: &'a Resolve,
+    roots: &[&'a PackageId],
+) -> Vec<DuplicatePackage<'a>> {
+    let paths = resolve.shortest_paths(roots);
+    let mut by_name = BTreeMap::new();
+    for id in paths.packages() {
+        by_name.entry(id.name()).or_insert_with(Vec::new).push(id);
+    }
+    let mut dependents = HashMap::new();
+    for parent in paths.packages() {
+        for (dep, deps) in resolve.deps(parent) {
+            dependents
+                .entry(dep)
+                .or_insert_with(Vec::new)
+                .push((parent, deps));
+        }
+    }
+
+    let mut report = Vec::new();
+    for (name, mut ids) in by_name {
+        if ids.len() < 2 {
+            continue;
+        }
+        ids.sort();
+        let versions = ids.iter()
+            .map(|&id| {
+                let mut users = dependents.get(id).cloned().unwrap_or_default();
+                users.sort_by(|a, b| a.0.cmp(b.0));
+                let mut list = Vec::new();
+                for (parent, deps) in users {
+                    let mut reqs = deps.iter()
+                        .map(|d| d.version_req())
+                        .collect::<Vec<_>>();
+                    reqs.sort_by_key(|r| r.to_string());
+                    reqs.dedup();
+                    for req in reqs {
+                        let mut path = paths.path_to(parent);
+                        path.push(id);
+                        let excludes = ids.iter()
+                            .cloned()
+                            .filter(|&other| other != id && !req.matches(other.version()))
+                            .collect();
+                        list.push(DuplicateDependent {
+                            path,
+                            requirement: req.to_string(),
+                            excludes,
+                        });
+                    }
+                }
+                DuplicateVersion {
+                    package_id: id,
+                    dependents: list,
+                }
+            })
+            .collect();
+        report.push(DuplicatePackage {
+            name: name.as_str(),
+            versions,
+        });
+    }
+    report
+}
+
+fn print_report(report: &[DuplicatePackage]) {
+    for (i, duplicate) in report.iter().enumerate() {
+        if i != 0 {
+            println!();
+        }
+        println!(
+            "package `{}` is used in {} versions",
+            duplicate.name,
+            duplicate.versions.len()
+        );
+        for version in duplicate.versions.iter() {
+            println!("  {}", version.package_id);
+            for dependent in version.dependents.iter() {
+                let path = dependent
+                    .path
+                    .iter()
+                    .map(|id| id.to_string())
+                    .collect::<Vec<_>>();
+                println!("    {}", path.join(" -> "));
+                if dependent.excludes.is_empty() {
+                    println!(
+                        "      requirement `{}` also accepts the other versions",
+                        dependent.requirement
+                    );
+                } else {
+                    let excludes = dependent
+                        .excludes
+                        .iter()
+                        .map(|id| id.version().to_string())
+                        .collect::<Vec<_>>();
+                    println!(
+                        "      requirement `{}` excludes {}",
+                        dependent.requirement,
+                        excludes.join(", ")
+                    );
+                }
+            }
+        }
+    }
+}
@@ -33,7 +33,6 @@ pub fn fetch<'a>(
                 continue;
             }
 
-            packages.get(id)?;
             let deps = resolve.deps(id)
                 .filter(|&(_id, deps)| {
                     deps.iter()
@@ -57,6 +56,7 @@ pub fn fetch<'a>(
                 .map(|(id, _deps)| id);
             deps_to_fetch.extend(deps);
         }
+        packages.get_many(fetched_packages)?;
     }
 
     Ok((resolve, packages))
@@ -1,9 +1,9 @@
 use std::collections::{BTreeMap, HashSet};
 
-use termcolor::Color::{self, Cyan, Green, Red};
+use termcolor::Color::{self, Cyan, Green, Red, Yellow};
 
 use core::registry::PackageRegistry;
-use core::resolver::Method;
+use core::resolver::{self, Method};
 use core::PackageId;
 use core::{Resolve, SourceId, Workspace};
 use ops;
@@ -15,11 +15,23 @@ pub struct UpdateOptions<'a> {
     pub to_update: Vec<String>,
     pub precise: Option<&'a str>,
     pub aggressive: bool,
+    /// Only print what would change, without writing `Cargo.lock` or any
+    /// manifests.
+    pub dry_run: bool,
+    /// Raise the version requirements in the manifests of the workspace
+    /// members to the newest versions, even if they're incompatible.
+    pub breaking: bool,
 }
 
 pub fn generate_lockfile(ws: &Workspace) -> CargoResult<()> {
+    let resolve = resolve_without_lockfile(ws)?;
+    ops::write_pkg_lockfile(ws, &resolve)?;
+    Ok(())
+}
+
+fn resolve_without_lockfile(ws: &Workspace) -> CargoResult<Resolve> {
     let mut registry = PackageRegistry::new(ws.config())?;
-    let resolve = ops::resolve_with_previous(
+    ops::resolve_with_previous(
         &mut registry,
         ws,
         Method::Everything,
@@ -28,27 +40,67 @@ pub fn generate_lockfile(ws: &Workspace) -> CargoResult<()> {
         &[],
         true,
         true,
-    )?;
-    ops::write_pkg_lockfile(ws, &resolve)?;
-    Ok(())
+    )
 }
 
 pub fn update_lockfile(ws: &Workspace, opts: &UpdateOptions) -> CargoResult<()> {
     if opts.aggressive && opts.precise.is_some() {
         bail!("cannot specify both aggressive and precise simultaneously")
     }
+    if opts.breaking && opts.precise.is_some() {
+        bail!("cannot specify both breaking and precise simultaneously")
+    }
 
     if ws.members().count() == 0 {
         bail!("you can't generate a lockfile for an empty workspace.")
     }
 
-    if opts.config.cli_unstable().offline {
+    if opts.config.offline() {
         bail!("you can't update in the offline mode");
     }
 
+    if !opts.breaking {
+        return update(ws, opts, None);
+    }
+    // The new requirements are resolved in a copy of the workspace, and only
+    // written to the manifests once that has succeeded, just before the
+    // lockfile which depends on them.
+    let mut ws = Workspace::new(&ws.root().join("Cargo.toml"), opts.config)?;
+    let manifests = ops::upgrade_requirements(&mut ws, &opts.to_update)?;
+    update(&ws, opts, Some(&manifests))
+}
+
+fn update(
+    ws: &Workspace,
+    opts: &UpdateOptions,
+    manifests: Option<&ops::UpgradedManifests>,
+) -> CargoResult<()> {
     let previous_resolve = match ops::load_pkg_lockfile(ws)? {
         Some(resolve) => resolve,
-        None => return generate_lockfile(ws),
+        None if opts.dry_run => {
+            let resolve = resolve_without_lockfile(ws)?;
+            let mut ids = resolve
+                .iter()
+                .filter(|id| !ws.members().any(|m| m.package_id() == *id))
+                .collect::<Vec<_>>();
+            ids.sort();
+            for id in ids {
+                opts.config
+                    .shell()
+                    .status_with_color("Adding", format!("{}", id), Cyan)?;
+            }
+            opts.config
+                .shell()
+                .warn("not writing a lockfile due to dry run")?;
+            return Ok(());
+        }
+        None => {
+            let resolve = resolve_without_lockfile(ws)?;
+            if let Some(manifests) = manifests {
+                manifests.write()?;
+            }
+            return ops::write_pkg_lockfile(ws, &resolve);
+        }
     };
     let mut registry = PackageRegistry::new(opts.config)?;
     let mut to_avoid = HashSet::new();
@@ -99,16 +151,32 @@ pub fn update_lockfile(ws: &Workspace, opts: &UpdateOptions) -> CargoResult<()>
     };
     for (removed, added) in compare_dependency_graphs(&previous_resolve, &resolve) {
         if removed.len() == 1 && added.len() == 1 {
-            let msg = if removed[0].source_id().is_git() {
-                format!(
+            if removed[0].source_id().is_git() {
+                let msg = format!(
                     "{} -> #{}",
                     removed[0],
                     &added[0].source_id().precise().unwrap()[..8]
-                )
+                );
+                print_change("Updating", msg, Green)?;
+            } else if opts.dry_run {
+                // A dry run tells apart which way each version moves, and
+                // whether the new one is compatible with the old.
+                let (old, new) = (removed[0].version(), added[0].version());
+                let breaking = if resolver::compatible(old, new) {
+                    ""
+                } else {
+                    " (breaking)"
+                };
+                let msg = format!("{} -> v{}{}", removed[0], new, breaking);
+                if new > old {
+                    print_change("Upgrading", msg, Green)?;
+                } else {
+                    print_change("Downgrading", msg, Yellow)?;
+                }
             } else {
-                format!("{} -> v{}", removed[0], added[0].version())
-            };
-            print_change("Updating", msg, Green)?;
+                let msg = format!("{} -> v{}", removed[0], added[0].version());
+                print_change("Updating", msg, Green)?;
+            }
         } else {
             for package in removed.iter() {
                 print_change("Removing", format!("{}", package), Red)?;
@@ -119,7 +187,16 @@ pub fn update_lockfile(ws: &Workspace, opts: &UpdateOptions) -> CargoResult<()>
         }
     }
 
-    ops::write_pkg_lockfile(ws, &resolve)?;
+    if opts.dry_run {
+        opts.config
+            .shell()
+            .warn("not updating lockfile due to dry run")?;
+    } else {
+        if let Some(manifests) = manifests {
+            manifests.write()?;
+        }
+        ops::write_pkg_lockfile(ws, &resolve)?;
+    }
     return Ok(());
 
     fn fill_with_deps<'a>(
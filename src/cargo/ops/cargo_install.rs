@@ -11,7 +11,7 @@ use tempfile::Builder as TempFileBuilder;
 use toml;
 
 use core::{Dependency, Edition, Package, PackageIdSpec, Source, SourceId};
-use core::{PackageId, Workspace};
+use core::{PackageId, PackageSet, SourceMap, Workspace};
 use core::compiler::{DefaultExecutor, Executor};
 use ops::{self, CompileFilter};
 use sources::{GitSource, PathSource, SourceConfigMap};
@@ -503,7 +503,13 @@ where
             let deps = source.query_vec(&dep)?;
             match deps.iter().map(|p| p.package_id()).max() {
                 Some(pkgid) => {
-                    let pkg = source.download(pkgid)?;
+                    let pkg = {
+                        let mut map = SourceMap::new();
+                        map.insert(Box::new(&mut source));
+                        PackageSet::new(&[pkgid.clone()], map, config)?
+                            .get(pkgid)?
+                            .clone()
+                    };
                     Ok((pkg, Box::new(source)))
                 }
                 None => {
@@ -0,0 +1,139 @@
+use std::collections::{HashMap, HashSet};
+
+use core::compiler::MessageFormat;
+use core::registry::{PackageRegistry, Registry};
+use core::{Dependency, PackageId, Workspace};
+use ops;
+use util::machine_message::{self, OutdatedPackage};
+use util::CargoResult;
+
+pub struct OutdatedOptions {
+    /// Also report the dependencies of dependencies.
+    pub transitive: bool,
+    pub message_format: MessageFormat,
+}
+
+/// Executes `cargo outdated`.
+///
+/// Every dependency from a registry is compared against all the versions its
+/// registry has. The newest version is reported along with the newest one
+/// which still matches the requirements of every package depending on it,
+/// which `cargo update` would be able to move to.
+pub fn outdated(ws: &Workspace, opts: &OutdatedOptions) -> CargoResult<()> {
+    let config = ws.config();
+    let (_packages, resolve) = ops::resolve_ws(ws)?;
+
+    let members = ws.members().map(|m| m.package_id()).collect::<HashSet<_>>();
+    let mut direct = HashSet::new();
+    let mut requirements = HashMap::new();
+    for id in resolve.iter() {
+        for (dep, deps) in resolve.deps(id) {
+            if members.contains(id) {
+                direct.insert(dep);
+            }
+            requirements
+                .entry(dep)
+                .or_insert_with(Vec::new)
+                .extend(deps.iter().map(|d| d.version_req().clone()));
+        }
+    }
+
+    let mut ids = resolve
+        .iter()
+        .filter(|id| !members.contains(id) && id.source_id().is_registry())
+        .filter(|id| opts.transitive || direct.contains(id))
+        .collect::<Vec<_>>();
+    ids.sort();
+
+    let mut registry = PackageRegistry::new(config)?;
+    registry.lock_patches();
+    let mut report = Vec::new();
+    for id in ids {
+        let query = Dependency::parse_no_deprecated(&id.name(), None, id.source_id())?;
+        let versions = registry
+            .query_vec(&query, false)?
+            .into_iter()
+            .map(|s| s.version().clone())
+            .collect::<Vec<_>>();
+        let reqs = requirements.get(id).map(|r| &r[..]).unwrap_or(&[]);
+        // A pre-release is only the latest version for a package which is
+        // already locked to one.
+        let latest = versions
+            .iter()
+            .filter(|v| id.version().is_prerelease() || !v.is_prerelease())
+            .max()
+            .unwrap_or(id.version());
+        let compatible = versions
+            .iter()
+            .filter(|v| reqs.iter().all(|req| req.matches(v)))
+            .max()
+            .unwrap_or(id.version());
+        if latest <= id.version() && compatible <= id.version() {
+            continue;
+        }
+        report.push(OutdatedPackage {
+            package_id: id,
+            direct: direct.contains(id),
+            locked: id.version().to_string(),
+            compatible: compatible.to_string(),
+            latest: latest.to_string(),
+        });
+    }
+
+    match opts.message_format {
+        MessageFormat::Json => {
+            for package in report.iter() {
+                machine_message::emit(package);
+            }
+        }
+        MessageFormat::Human | MessageFormat::Short => {
+            if report.is_empty() {
+                config
+                    .shell()
+                    .status("Finished", "all dependencies are up to date")?;
+            } else {
+                print_table(&report, opts.transitive);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_table(report: &[OutdatedPackage], transitive: bool) {
+    let mut rows = vec![vec![
+        "Name".to_string(),
+        "Locked".to_string(),
+        "Compatible".to_string(),
+        "Latest".to_string(),
+    ]];
+    if transitive {
+        rows[0].push("Kind".to_string());
+    }
+    for package in report.iter() {
+        let mut row = vec![
+            package.package_id.name().to_string(),
+            package.locked.clone(),
+            package.compatible.clone(),
+            package.latest.clone(),
+        ];
+        if transitive {
+            let kind = if package.direct { "direct" } else { "transitive" };
+            row.push(kind.to_string());
+        }
+        rows.push(row);
+    }
+
+    let mut widths = vec![0; rows[0].len()];
+    for row in rows.iter() {
+        for (width, cell) in widths.iter_mut().zip(row.iter()) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    for row in rows.iter() {
+        let line = row.iter()
+            .zip(widths.iter())
+            .map(|(cell, &width)| format!("{:1$}", cell, width))
+            .collect::<Vec<_>>();
+        println!("{}", line.join("  ").trim_right());
+    }
+}
@@ -57,9 +57,10 @@ fn metadata_full<'a>(ws: &'a Workspace, opt: &OutputMetadataOptions) -> CargoRes
     let (package_set, resolve) = deps;
 
     let packages = package_set
-        .package_ids()
-        .map(|i| package_set.get(i).map(|p| p.clone()))
-        .collect::<CargoResult<Vec<_>>>()?;
+        .get_many(package_set.package_ids())?
+        .into_iter()
+        .cloned()
+        .collect::<Vec<_>>();
 
     Ok(ExportInfo {
         packages,
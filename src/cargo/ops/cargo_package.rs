@@ -1,7 +1,7 @@
 use std::fs::{self, File};
 use std::io::SeekFrom;
 use std::io::prelude::*;
-use std::path::{self, Path};
+use std::path::{self, Path, PathBuf};
 use std::sync::Arc;
 
 use flate2::read::GzDecoder;
@@ -14,6 +14,7 @@ use core::compiler::{BuildConfig, CompileMode, DefaultExecutor, Executor};
 use sources::PathSource;
 use util::{self, internal, Config, FileLock};
 use util::paths;
+use util::toml::packaged_file_name;
 use util::errors::{CargoResult, CargoResultExt};
 use ops;
 
@@ -48,6 +49,9 @@ pub fn package(ws: &Workspace, opts: &PackageOpts) -> CargoResult<Option<FileLoc
             .iter()
             .map(|file| util::without_prefix(file, root).unwrap().to_path_buf())
             .collect();
+        for &(_, ref name) in outside_files(pkg).iter() {
+            list.push(name.into());
+        }
         if include_lockfile(pkg) {
             list.push("Cargo.lock".into());
         }
@@ -217,6 +221,21 @@ fn check_not_dirty(p: &Package, src: &PathSource, config: &Config) -> CargoResul
     }
 }
 
+/// The `readme` and `license-file` of a package which live outside of its
+/// directory (e.g. inherited from the workspace), along with the name they are
+/// packaged under in the root of the package.
+fn outside_files(pkg: &Package) -> Vec<(PathBuf, String)> {
+    let metadata = pkg.manifest().metadata();
+    let root = pkg.root();
+    let mut files = Vec::new();
+    for file in metadata.readme.iter().chain(metadata.license_file.iter()) {
+        if let Some(name) = packaged_file_name(root, file) {
+            files.push((util::normalize_path(&root.join(file)), name));
+        }
+    }
+    files
+}
+
 fn tar(ws: &Workspace, src: &PathSource, dst: &File, filename: &str) -> CargoResult<()> {
     // Prepare the encoder and its header
     let filename = Path::new(filename);
@@ -229,9 +248,21 @@ fn tar(ws: &Workspace, src: &PathSource, dst: &File, filename: &str) -> CargoRes
     let pkg = ws.current()?;
     let config = ws.config();
     let root = pkg.root();
+    let outside = outside_files(pkg);
     for file in src.list_files(pkg)?.iter() {
         let relative = util::without_prefix(file, root).unwrap();
         check_filename(relative)?;
+        let conflict = outside
+            .iter()
+            .find(|&&(_, ref name)| relative == Path::new(name));
+        if let Some(&(ref outside, _)) = conflict {
+            bail!(
+                "`{}` is outside of the package and would be packaged as `{}`, \
+                 but the package already contains a file with that name",
+                outside.display(),
+                relative.display()
+            )
+        }
         let relative = relative.to_str().ok_or_else(|| {
             format_err!("non-utf8 path in source directory: {}", relative.display())
         })?;
@@ -297,6 +328,31 @@ fn tar(ws: &Workspace, src: &PathSource, dst: &File, filename: &str) -> CargoRes
         }
     }
 
+    for &(ref file, ref name) in outside.iter() {
+        config
+            .shell()
+            .verbose(|shell| shell.status("Archiving", &file.display()))?;
+        let path = format!(
+            "{}-{}{}{}",
+            pkg.name(),
+            pkg.version(),
+            path::MAIN_SEPARATOR,
+            name
+        );
+        let mut header = Header::new_ustar();
+        header
+            .set_path(&path)
+            .chain_err(|| format!("failed to add to archive: `{}`", name))?;
+        let mut file = File::open(file)
+            .chain_err(|| format!("failed to open for archiving: `{}`", file.display()))?;
+        let metadata = file.metadata()
+            .chain_err(|| format!("could not learn metadata for: `{}`", name))?;
+        header.set_metadata(&metadata);
+        header.set_cksum();
+        ar.append(&header, &mut file)
+            .chain_err(|| internal(format!("could not archive source file `{}`", name)))?;
+    }
+
     if include_lockfile(pkg) {
         let toml = paths::read(&ws.root().join("Cargo.lock"))?;
         let path = format!(
@@ -1,16 +1,99 @@
+use std::cell::RefCell;
 use std::ffi::OsString;
+use std::io::{self, Write};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use jobserver::{Acquired, Client};
 
 use ops;
-use core::compiler::{Compilation, Doctest};
-use util::{self, CargoTestError, ProcessError, Test};
-use util::errors::CargoResult;
-use core::Workspace;
+use core::compiler::{Compilation, Doctest, MessageFormat};
+use util::{self, CargoTestError, Config, ProcessBuilder, ProcessError, Test};
+use util::errors::{CargoError, CargoResult, CargoResultExt};
+use util::machine_message::{self, BenchResult, TestBinary, TestBinaryFinished,
+                            TestBinaryStarted, TestResult, TestSummary};
+use core::{TargetKind, Workspace};
 
 pub struct TestOptions<'a> {
     pub compile_opts: ops::CompileOptions<'a>,
     pub no_run: bool,
     pub no_fail_fast: bool,
     pub only_doc: bool,
+    /// Only run the test targets which fall into this partition.
+    pub partition: Option<Partition>,
+    /// How many test binaries to run at once, one at a time if `None`.
+    pub test_jobs: Option<u32>,
+}
+
+/// One of `total` roughly equal parts of the test targets, so they can be
+/// split across several machines.
+///
+/// Test targets are assigned to partitions deterministically, so the same
+/// list of targets is always split the same way.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Partition {
+    pub kind: PartitionKind,
+    /// Which partition to run, starting at 1.
+    pub index: u64,
+    pub total: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PartitionKind {
+    /// Deal targets out to the partitions in turn, in the order they're run.
+    Count,
+    /// Assign targets by a hash of their package and target names, so adding
+    /// or removing a target doesn't move any others.
+    Hash,
+}
+
+impl Partition {
+    /// Whether the test target at `position` in the order targets are run,
+    /// named `key`, belongs to this partition.
+    fn selects(&self, position: usize, key: &str) -> bool {
+        let slot = match self.kind {
+            PartitionKind::Count => position as u64,
+            PartitionKind::Hash => util::hash_u64(&key),
+        };
+        slot % self.total == self.index - 1
+    }
+}
+
+impl FromStr for Partition {
+    type Err = CargoError;
+
+    fn from_str(s: &str) -> CargoResult<Partition> {
+        let parse = || -> Option<Partition> {
+            let mut parts = s.splitn(2, ':');
+            let kind = match parts.next()? {
+                "count" => PartitionKind::Count,
+                "hash" => PartitionKind::Hash,
+                _ => return None,
+            };
+            let mut numbers = parts.next()?.splitn(2, '/');
+            let index = numbers.next()?.parse().ok()?;
+            let total = numbers.next()?.parse().ok()?;
+            if index == 0 || index > total {
+                return None;
+            }
+            Some(Partition { kind, index, total })
+        };
+        parse().ok_or_else(|| {
+            format_err!(
+                "invalid partition `{}`, expected `count:M/N` or `hash:M/N` \
+                 where M is between 1 and N",
+                s
+            )
+        })
+    }
+}
+
+/// The name a test target is assigned to a partition by.
+fn partition_key(pkg: &str, kind: &TargetKind, name: &str) -> String {
+    format!("{}/{:?}/{}", pkg, kind, name)
 }
 
 pub fn run_tests(
@@ -92,55 +175,159 @@ fn run_unit_tests(
 ) -> CargoResult<(Test, Vec<ProcessError>)> {
     let config = options.compile_opts.config;
     let cwd = options.compile_opts.config.cwd();
+    let json = options.compile_opts.build_config.message_format == MessageFormat::Json;
 
-    let mut errors = Vec::new();
-
-    for &(ref pkg, ref kind, ref test, ref exe) in &compilation.tests {
+    let mut to_run = Vec::new();
+    for (i, &(ref pkg, ref kind, ref test, ref exe)) in compilation.tests.iter().enumerate() {
+        if let Some(ref partition) = options.partition {
+            if !partition.selects(i, &partition_key(&pkg.name(), kind, test)) {
+                continue;
+            }
+        }
         let to_display = match util::without_prefix(exe, cwd) {
             Some(path) => path,
             None => &**exe,
         };
         let mut cmd = compilation.target_process(exe, pkg)?;
         cmd.args(test_args);
-        config
-            .shell()
-            .concise(|shell| shell.status("Running", to_display.display().to_string()))?;
-        config
-            .shell()
-            .verbose(|shell| shell.status("Running", cmd.to_string()))?;
-
-        let result = cmd.exec();
+        let binary = TestBinary {
+            package_id: pkg.package_id(),
+            target_kind: kind,
+            target_name: test.as_str(),
+            executable: Some(exe.as_path()),
+        };
+        to_run.push((to_display, cmd, binary));
+    }
 
-        match result {
-            Err(e) => {
-                let e = e.downcast::<ProcessError>()?;
-                errors.push((kind.clone(), test.clone(), pkg.name().to_string(), e));
-                if !options.no_fail_fast {
-                    break;
+    let mut errors = match options.test_jobs {
+        Some(jobs) if jobs > 1 && to_run.len() > 1 => {
+            run_in_parallel(config, &to_run, jobs as usize, json, options.no_fail_fast)?
+        }
+        _ => {
+            let mut errors = Vec::new();
+            for (i, &(to_display, ref cmd, ref binary)) in to_run.iter().enumerate() {
+                print_running(config, to_display, cmd)?;
+                if let Err(e) = exec_test(config, cmd, binary, json) {
+                    errors.push((i, e.downcast::<ProcessError>()?));
+                    if !options.no_fail_fast {
+                        break;
+                    }
                 }
             }
-            Ok(()) => {}
+            errors
         }
-    }
+    };
 
     if errors.len() == 1 {
-        let (kind, name, pkg_name, e) = errors.pop().unwrap();
+        let (i, e) = errors.pop().unwrap();
+        let binary = &to_run[i].2;
         Ok((
             Test::UnitTest {
-                kind,
-                name,
-                pkg_name,
+                kind: binary.target_kind.clone(),
+                name: binary.target_name.to_string(),
+                pkg_name: binary.package_id.name().to_string(),
             },
             vec![e],
         ))
     } else {
         Ok((
             Test::Multiple,
-            errors.into_iter().map(|(_, _, _, e)| e).collect(),
+            errors.into_iter().map(|(_, e)| e).collect(),
         ))
     }
 }
 
+fn print_running(config: &Config, to_display: &Path, cmd: &ProcessBuilder) -> CargoResult<()> {
+    config
+        .shell()
+        .concise(|shell| shell.status("Running", to_display.display().to_string()))?;
+    config
+        .shell()
+        .verbose(|shell| shell.status("Running", cmd.to_string()))
+}
+
+enum Message {
+    Token(io::Result<Acquired>),
+    Finished(usize, BufferedOutput),
+}
+
+/// Runs up to `jobs` test binaries at once, each on a thread of its own, and
+/// returns the index of each one which failed along with its error.
+///
+/// The output of each binary is buffered and only printed once it exits, so
+/// the output of different binaries isn't interleaved. If Cargo itself was
+/// started by a jobserver, such as `make`, binaries are only started while we
+/// have a token from it.
+fn run_in_parallel(
+    config: &Config,
+    to_run: &[(&Path, ProcessBuilder, TestBinary)],
+    jobs: usize,
+    json: bool,
+    no_fail_fast: bool,
+) -> CargoResult<Vec<(usize, ProcessError)>> {
+    let client = match config.jobserver_from_env() {
+        Some(client) => client.clone(),
+        None => Client::new(jobs - 1).chain_err(|| "failed to create jobserver")?,
+    };
+    let (tx, rx) = mpsc::channel();
+    let helper = {
+        let tx = tx.clone();
+        client
+            .into_helper_thread(move |token| {
+                drop(tx.send(Message::Token(token)));
+            })
+            .chain_err(|| "failed to create helper thread for jobserver management")?
+    };
+
+    let mut pending = 0..to_run.len();
+    let mut tokens = Vec::new();
+    let mut running = 0;
+    let mut errors = Vec::new();
+    loop {
+        // Once a binary has failed nothing new is started, unless we were
+        // asked not to fail fast, but the ones already running are waited on.
+        let stop = !errors.is_empty() && !no_fail_fast;
+
+        // We always have one implicit token of our own, so the first binary
+        // doesn't need one from the jobserver.
+        while !stop && running < jobs && running <= tokens.len() {
+            let i = match pending.next() {
+                Some(i) => i,
+                None => break,
+            };
+            let cmd = to_run[i].1.clone();
+            let tx = tx.clone();
+            thread::spawn(move || {
+                drop(tx.send(Message::Finished(i, BufferedOutput::run(&cmd))));
+            });
+            running += 1;
+        }
+        // Give back any tokens we aren't using.
+        tokens.truncate(running.saturating_sub(1));
+        if running == 0 {
+            break;
+        }
+        if !stop && pending.len() > 0 && running < jobs && running > tokens.len() {
+            helper.request_token();
+        }
+
+        match rx.recv().unwrap() {
+            Message::Token(token) => {
+                tokens.push(token.chain_err(|| "failed to acquire jobserver token")?);
+            }
+            Message::Finished(i, output) => {
+                running -= 1;
+                let (to_display, ref cmd, ref binary) = to_run[i];
+                print_running(config, to_display, cmd)?;
+                if let Err(e) = output.print(config, binary, json) {
+                    errors.push((i, e.downcast::<ProcessError>()?));
+                }
+            }
+        }
+    }
+    Ok(errors)
+}
+
 fn run_doc_tests(
     options: &TestOptions,
     test_args: &[String],
@@ -148,18 +335,27 @@ fn run_doc_tests(
 ) -> CargoResult<(Test, Vec<ProcessError>)> {
     let mut errors = Vec::new();
     let config = options.compile_opts.config;
+    let json = options.compile_opts.build_config.message_format == MessageFormat::Json;
 
     // We don't build/rust doctests if target != host
     if compilation.host != compilation.target {
         return Ok((Test::Doc, errors));
     }
 
-    for doctest_info in &compilation.to_doc_test {
+    for (i, doctest_info) in compilation.to_doc_test.iter().enumerate() {
         let Doctest {
             package,
             target,
             deps,
         } = doctest_info;
+        // Doc tests come after all the other test targets in the order
+        // partitions are dealt out in.
+        if let Some(ref partition) = options.partition {
+            let key = partition_key(&package.name(), target.kind(), target.name());
+            if !partition.selects(compilation.tests.len() + i, &key) {
+                continue;
+            }
+        }
         config.shell().status("Doc-tests", target.name())?;
         let mut p = compilation.rustdoc_process(package, target)?;
         p.arg("--test")
@@ -207,7 +403,13 @@ fn run_doc_tests(
         config
             .shell()
             .verbose(|shell| shell.status("Running", p.to_string()))?;
-        if let Err(e) = p.exec() {
+        let binary = TestBinary {
+            package_id: package.package_id(),
+            target_kind: target.kind(),
+            target_name: target.name(),
+            executable: None,
+        };
+        if let Err(e) = exec_test(config, &p, &binary, json) {
             let e = e.downcast::<ProcessError>()?;
             errors.push(e);
             if !options.no_fail_fast {
@@ -217,3 +419,193 @@ fn run_doc_tests(
     }
     Ok((Test::Doc, errors))
 }
+
+/// Runs a test binary, or rustdoc for doc tests.
+///
+/// With JSON messages, libtest's output is parsed as it's printed to emit a
+/// message for every test, as well as for the start and end of the process.
+/// The output itself goes to stderr, so stdout only has JSON on it.
+fn exec_test(
+    config: &Config,
+    cmd: &ProcessBuilder,
+    binary: &TestBinary,
+    json: bool,
+) -> CargoResult<()> {
+    if !json {
+        return cmd.exec();
+    }
+
+    machine_message::emit(&TestBinaryStarted { binary });
+    let start = Instant::now();
+    let mut summary = None;
+    let result = cmd.exec_with_streaming(
+        &mut |line| {
+            parse_output_line(binary, line, &mut summary);
+            writeln!(config.shell().err(), "{}", line)?;
+            Ok(())
+        },
+        &mut |line| {
+            writeln!(config.shell().err(), "{}", line)?;
+            Ok(())
+        },
+        false,
+    );
+    emit_finished(binary, &result, start.elapsed(), summary);
+    result.map(|_| ())
+}
+
+/// The output of a test binary, buffered until it exits so that it can be
+/// printed all at once.
+struct BufferedOutput {
+    /// Every line of output, and whether it was printed to stdout.
+    lines: Vec<(bool, String)>,
+    duration: Duration,
+    result: CargoResult<()>,
+}
+
+impl BufferedOutput {
+    fn run(cmd: &ProcessBuilder) -> BufferedOutput {
+        let start = Instant::now();
+        let lines = RefCell::new(Vec::new());
+        let result = cmd.exec_with_streaming(
+            &mut |line| {
+                lines.borrow_mut().push((true, line.to_string()));
+                Ok(())
+            },
+            &mut |line| {
+                lines.borrow_mut().push((false, line.to_string()));
+                Ok(())
+            },
+            false,
+        );
+        BufferedOutput {
+            lines: lines.into_inner(),
+            duration: start.elapsed(),
+            result: result.map(|_| ()),
+        }
+    }
+
+    /// Prints the output the same way `exec_test` would have while the
+    /// binary ran, and returns its result.
+    fn print(self, config: &Config, binary: &TestBinary, json: bool) -> CargoResult<()> {
+        if json {
+            machine_message::emit(&TestBinaryStarted { binary });
+        }
+        let mut summary = None;
+        {
+            let stdout = io::stdout();
+            let mut stdout = stdout.lock();
+            let mut shell = config.shell();
+            for (is_out, line) in self.lines {
+                if json {
+                    if is_out {
+                        parse_output_line(binary, &line, &mut summary);
+                    }
+                    writeln!(shell.err(), "{}", line)?;
+                } else if is_out {
+                    writeln!(stdout, "{}", line)?;
+                } else {
+                    writeln!(shell.err(), "{}", line)?;
+                }
+            }
+        }
+        if json {
+            emit_finished(binary, &self.result, self.duration, summary);
+        }
+        self.result
+    }
+}
+
+/// Emits a message for a line of libtest's output if it's the result of a
+/// test, or records it if it's the summary at the end.
+fn parse_output_line(binary: &TestBinary, line: &str, summary: &mut Option<TestSummary>) {
+    if let Some((name, outcome, bench)) = parse_test_line(line) {
+        machine_message::emit(&TestResult {
+            binary,
+            name,
+            outcome,
+            bench,
+        });
+    } else if let Some(s) = parse_test_summary(line) {
+        *summary = Some(s);
+    }
+}
+
+fn emit_finished<T>(
+    binary: &TestBinary,
+    result: &CargoResult<T>,
+    elapsed: Duration,
+    summary: Option<TestSummary>,
+) {
+    let exit_code = match *result {
+        Ok(_) => Some(0),
+        Err(ref e) => e.downcast_ref::<ProcessError>()
+            .and_then(|e| e.exit)
+            .and_then(|status| status.code()),
+    };
+    machine_message::emit(&TestBinaryFinished {
+        binary,
+        success: result.is_ok(),
+        exit_code,
+        duration: elapsed.as_secs() as f64 + f64::from(elapsed.subsec_nanos()) / 1e9,
+        summary,
+    });
+}
+
+/// Parses a line like `test foo::bar ... ok` printed by libtest into the name
+/// of the test, its outcome, and the timing of a benchmark.
+fn parse_test_line(line: &str) -> Option<(&str, &'static str, Option<BenchResult>)> {
+    if !line.starts_with("test ") {
+        return None;
+    }
+    let sep = line.rfind(" ... ")?;
+    let name = &line[5..sep];
+    let result = line[sep + 5..].trim();
+    let (outcome, bench) = match result {
+        "ok" => ("ok", None),
+        "FAILED" => ("failed", None),
+        "ignored" => ("ignored", None),
+        _ if result.starts_with("bench:") => ("bench", Some(parse_bench(&result[6..])?)),
+        _ => return None,
+    };
+    Some((name, outcome, bench))
+}
+
+/// Parses the timing of a benchmark, like `1,234 ns/iter (+/- 56)`.
+fn parse_bench(s: &str) -> Option<BenchResult> {
+    let number = |s: &str| s.trim().replace(',', "").parse::<u64>().ok();
+    let mut parts = s.splitn(2, "ns/iter");
+    let ns_per_iter = number(parts.next()?)?;
+    let deviation = parts.next()?.trim();
+    if !deviation.starts_with("(+/-") || !deviation.ends_with(')') {
+        return None;
+    }
+    let deviation = number(&deviation[4..deviation.len() - 1])?;
+    Some(BenchResult {
+        ns_per_iter,
+        deviation,
+    })
+}
+
+/// Parses libtest's final line, like
+/// `test result: ok. 3 passed; 0 failed; 1 ignored; 0 measured; 0 filtered out`.
+fn parse_test_summary(line: &str) -> Option<TestSummary> {
+    if !line.starts_with("test result: ") {
+        return None;
+    }
+    let counts = line.splitn(2, ". ").nth(1)?;
+    let mut summary = TestSummary::default();
+    for count in counts.split(';') {
+        let mut words = count.trim().splitn(2, ' ');
+        let n = words.next()?.parse().ok()?;
+        match words.next()?.trim() {
+            "passed" => summary.passed = n,
+            "failed" => summary.failed = n,
+            "ignored" => summary.ignored = n,
+            "measured" => summary.measured = n,
+            "filtered out" => summary.filtered_out = n,
+            _ => {}
+        }
+    }
+    Some(summary)
+}
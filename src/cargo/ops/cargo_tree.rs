@@ -0,0 +1,250 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use core::compiler::{BuildConfig, CompileMode, Kind, TargetInfo};
+use core::dependency::{self, Dependency};
+use core::{PackageId, PackageIdSpec, Resolve, Workspace};
+use ops::{self, Packages};
+use util::{CargoResult, Config};
+
+pub struct TreeOptions {
+    pub features: Vec<String>,
+    pub no_default_features: bool,
+    pub all_features: bool,
+    /// The packages to display the tree for.
+    pub spec: Packages,
+    /// The target triple to filter platform-specific dependencies with.
+    /// `None` means the host, and `all` includes every platform.
+    pub target: Option<String>,
+    /// Display what depends on the given package instead of what it
+    /// depends on.
+    pub invert: Option<String>,
+    /// Only display packages which appear in the graph with more than one
+    /// version, along with what pulls each version in.
+    pub duplicates: bool,
+    /// The kinds of dependency edges to follow.
+    pub kinds: Vec<dependency::Kind>,
+}
+
+/// The order in which dependency kinds are printed below each package.
+const KIND_ORDER: &[dependency::Kind] = &[
+    dependency::Kind::Normal,
+    dependency::Kind::Build,
+    dependency::Kind::Development,
+];
+
+/// Executes `cargo tree`.
+pub fn tree(ws: &Workspace, opts: &TreeOptions) -> CargoResult<()> {
+    if opts.invert.is_some() && opts.duplicates {
+        bail!("the `--invert` and `--duplicates` flags cannot be used together");
+    }
+
+    let specs = opts.spec.to_package_id_specs(ws)?;
+    let (_packages, resolve) = ops::resolve_ws_precisely(
+        ws,
+        None,
+        &opts.features,
+        opts.all_features,
+        opts.no_default_features,
+        &specs,
+    )?;
+
+    let target = match opts.target {
+        Some(ref t) if t == "all" => None,
+        ref t => Some(ActivePlatform::new(ws, t)?),
+    };
+    let graph = Graph::new(&resolve, &opts.kinds, target.as_ref());
+
+    let roots = specs
+        .iter()
+        .map(|spec| spec.query(resolve.iter()))
+        .collect::<CargoResult<Vec<_>>>()?;
+
+    if opts.duplicates {
+        let reverse = graph.invert();
+        let duplicates = find_duplicates(&graph, &roots);
+        for (i, &id) in duplicates.iter().enumerate() {
+            if i != 0 {
+                println!();
+            }
+            print_tree(&reverse, id);
+        }
+    } else if let Some(ref invert) = opts.invert {
+        let id = PackageIdSpec::parse(invert)?.query(resolve.iter())?;
+        print_tree(&graph.invert(), id);
+    } else {
+        for (i, &id) in roots.iter().enumerate() {
+            if i != 0 {
+                println!();
+            }
+            print_tree(&graph, id);
+        }
+    }
+    Ok(())
+}
+
+/// The platform dependencies are filtered with.
+struct ActivePlatform {
+    name: String,
+    info: TargetInfo,
+}
+
+impl ActivePlatform {
+    fn new(ws: &Workspace, target: &Option<String>) -> CargoResult<ActivePlatform> {
+        let config: &Config = ws.config();
+        let build_config = BuildConfig::new(config, Some(1), target, CompileMode::Build)?;
+        let rustc = config.rustc(Some(ws))?;
+        let info = TargetInfo::new(config, &build_config.requested_target, &rustc, Kind::Target)?;
+        let name = build_config
+            .requested_target
+            .unwrap_or_else(|| rustc.host.clone());
+        Ok(ActivePlatform { name, info })
+    }
+
+    fn activates(&self, dep: &Dependency) -> bool {
+        match dep.platform() {
+            Some(p) => p.matches(&self.name, self.info.cfg()),
+            None => true,
+        }
+    }
+}
+
+/// A filtered view of the dependency edges in a `Resolve`.
+struct Graph<'a> {
+    edges: HashMap<&'a PackageId, Vec<(dependency::Kind, &'a PackageId)>>,
+}
+
+impl<'a> Graph<'a> {
+    fn new(
+        resolve: &'a Resolve,
+        kinds: &[dependency::Kind],
+        platform: Option<&ActivePlatform>,
+    ) -> Graph<'a> {
+        let mut edges = HashMap::new();
+        for id in resolve.iter() {
+            let mut list = Vec::new();
+            for (dep_id, deps) in resolve.deps(id) {
+                for &kind in KIND_ORDER {
+                    if !kinds.contains(&kind) {
+                        continue;
+                    }
+                    let active = deps.iter().any(|d| {
+                        d.kind() == kind && platform.map(|p| p.activates(d)).unwrap_or(true)
+                    });
+                    if active {
+                        list.push((kind, dep_id));
+                    }
+                }
+            }
+            list.sort();
+            list.dedup();
+            edges.insert(id, list);
+        }
+        Graph { edges }
+    }
+
+    fn edges(&self, id: &PackageId) -> &[(dependency::Kind, &'a PackageId)] {
+        self.edges.get(id).map(|v| &v[..]).unwrap_or(&[])
+    }
+
+    /// Returns a graph with the direction of every edge reversed.
+    fn invert(&self) -> Graph<'a> {
+        let mut edges = HashMap::new();
+        for (&from, list) in self.edges.iter() {
+            edges.entry(from).or_insert_with(Vec::new);
+            for &(kind, to) in list {
+                edges.entry(to).or_insert_with(Vec::new).push((kind, from));
+            }
+        }
+        for list in edges.values_mut() {
+            list.sort();
+        }
+        Graph { edges }
+    }
+}
+
+/// Finds every package reachable from `roots` whose name is shared with
+/// another reachable package of a different version.
+fn find_duplicates<'a>(graph: &Graph<'a>, roots: &[&'a PackageId]) -> Vec<&'a PackageId> {
+    let mut seen = HashSet::new();
+    let mut stack = roots.to_vec();
+    while let Some(id) = stack.pop() {
+        if seen.insert(id) {
+            stack.extend(graph.edges(id).iter().map(|&(_, dep)| dep));
+        }
+    }
+
+    let mut by_name = BTreeMap::new();
+    for id in seen {
+        by_name.entry(id.name()).or_insert_with(Vec::new).push(id);
+    }
+    let mut duplicates = Vec::new();
+    for (_, mut ids) in by_name {
+        if ids.len() > 1 {
+            ids.sort();
+            duplicates.extend(ids);
+        }
+    }
+    duplicates
+}
+
+fn print_tree<'a>(graph: &Graph<'a>, root: &'a PackageId) {
+    let mut visited = HashSet::new();
+    let mut levels_continue = Vec::new();
+    print_package(graph, root, &mut levels_continue, &mut visited);
+}
+
+fn print_prefix(levels_continue: &[bool]) {
+    for &continues in levels_continue {
+        print!("{}", if continues { "│   " } else { "    " });
+    }
+}
+
+fn print_package<'a>(
+    graph: &Graph<'a>,
+    id: &'a PackageId,
+    levels_continue: &mut Vec<bool>,
+    visited: &mut HashSet<&'a PackageId>,
+) {
+    if let Some((&last_continues, rest)) = levels_continue.split_last() {
+        print_prefix(rest);
+        print!("{}", if last_continues { "├── " } else { "└── " });
+    }
+
+    // Packages which have already been printed are marked with `(*)` and
+    // their dependencies are not repeated.
+    let new = visited.insert(id);
+    if !new {
+        println!("{} (*)", id);
+        return;
+    }
+    println!("{}", id);
+
+    for &kind in KIND_ORDER {
+        let deps = graph
+            .edges(id)
+            .iter()
+            .filter(|&&(k, _)| k == kind)
+            .map(|&(_, dep)| dep)
+            .collect::<Vec<_>>();
+        if deps.is_empty() {
+            continue;
+        }
+        match kind {
+            dependency::Kind::Normal => {}
+            dependency::Kind::Build => {
+                print_prefix(levels_continue);
+                println!("[build-dependencies]");
+            }
+            dependency::Kind::Development => {
+                print_prefix(levels_continue);
+                println!("[dev-dependencies]");
+            }
+        }
+        let mut it = deps.iter().peekable();
+        while let Some(&dep) = it.next() {
+            levels_continue.push(it.peek().is_some());
+            print_package(graph, dep, levels_continue, visited);
+            levels_continue.pop();
+        }
+    }
+}
@@ -0,0 +1,301 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use hex;
+use serde_json;
+
+use core::{GitReference, PackageId, Workspace};
+use ops;
+use sources::PathSource;
+use util::errors::{CargoResult, CargoResultExt};
+use util::{paths, Config, Sha256};
+
+pub struct VendorOptions<'a> {
+    /// Don't delete crates which are no longer used from the vendor directory.
+    pub no_delete: bool,
+    /// Always include the version in the name of each crate's directory.
+    pub versioned_dirs: bool,
+    /// The directory to vendor crates into, as given on the command line.
+    pub destination: &'a Path,
+    /// Manifests of additional workspaces whose dependencies are vendored
+    /// alongside those of the current workspace.
+    pub extra: Vec<PathBuf>,
+}
+
+/// The `[source]` configuration needed to build using the vendored crates.
+#[derive(Serialize)]
+pub struct VendorConfig {
+    source: BTreeMap<String, VendorSource>,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum VendorSource {
+    Directory {
+        directory: PathBuf,
+    },
+    Registry {
+        registry: Option<String>,
+        #[serde(rename = "replace-with")]
+        replace_with: String,
+    },
+    Git {
+        git: String,
+        branch: Option<String>,
+        tag: Option<String>,
+        rev: Option<String>,
+        #[serde(rename = "replace-with")]
+        replace_with: String,
+    },
+}
+
+/// The contents of a `.cargo-checksum.json` file, as read by
+/// `DirectorySource`.
+#[derive(Serialize, Deserialize)]
+struct VendorChecksum {
+    package: Option<String>,
+    files: BTreeMap<String, String>,
+}
+
+/// The name under which the vendor directory is registered as a source.
+const VENDOR_SOURCE_NAME: &str = "vendored-sources";
+
+/// Executes `cargo vendor`.
+///
+/// Every non-path package in the resolve graph of the workspace (and any
+/// extra workspaces) is copied into the destination directory, and the
+/// source replacement configuration to use them is returned.
+pub fn vendor(ws: &Workspace, opts: &VendorOptions) -> CargoResult<VendorConfig> {
+    let config = ws.config();
+    let mut extra_workspaces = Vec::new();
+    for extra in opts.extra.iter() {
+        let extra = config.cwd().join(extra);
+        let ws = Workspace::new(&extra, config)?;
+        extra_workspaces.push(ws);
+    }
+    let workspaces = extra_workspaces.iter().chain(Some(ws)).collect::<Vec<_>>();
+    sync(config, &workspaces, opts).chain_err(|| "failed to sync")
+}
+
+fn sync(
+    config: &Config,
+    workspaces: &[&Workspace],
+    opts: &VendorOptions,
+) -> CargoResult<VendorConfig> {
+    let destination = config.cwd().join(opts.destination);
+    fs::create_dir_all(&destination).chain_err(|| {
+        format!(
+            "failed to create vendor directory `{}`",
+            destination.display()
+        )
+    })?;
+
+    // Download every package we need before touching the vendor directory,
+    // so a network failure doesn't leave it half-deleted.
+    let mut packages = BTreeMap::new();
+    let mut checksums = HashMap::new();
+    for ws in workspaces {
+        let (package_set, resolve) =
+            ops::resolve_ws(ws).chain_err(|| "failed to load pkg lockfile")?;
+        let ids = resolve
+            .iter()
+            .filter(|id| !id.source_id().is_path())
+            .collect::<Vec<_>>();
+        let pkgs = package_set
+            .get_many(ids)
+            .chain_err(|| "failed to download packages")?;
+        for pkg in pkgs {
+            let id = pkg.package_id();
+            packages.insert(id.clone(), pkg.clone());
+            let checksum = resolve.checksums().get(id).and_then(|c| c.clone());
+            checksums.insert(id.clone(), checksum);
+        }
+    }
+
+    // A directory source can only contain one copy of each name/version
+    // pair, so the same version coming from two sources can't be vendored.
+    let mut versions = HashMap::new();
+    for id in packages.keys() {
+        let map = versions.entry(id.name()).or_insert_with(BTreeMap::new);
+        if let Some(prev) = map.get(id.version()) {
+            bail!(
+                "found duplicate version of package `{} v{}` \
+                 vendored from two sources:\n\
+                 \n\
+                 \tsource 1: {}\n\
+                 \tsource 2: {}",
+                id.name(),
+                id.version(),
+                prev,
+                id.source_id()
+            );
+        }
+        map.insert(id.version(), id.source_id());
+    }
+
+    // The newest version of each crate goes into a directory named after the
+    // crate, older versions get the version appended.
+    let dir_name = |id: &PackageId| {
+        let newest = versions[&id.name()].keys().next_back().unwrap();
+        if opts.versioned_dirs || id.version() != *newest {
+            format!("{}-{}", id.name(), id.version())
+        } else {
+            id.name().to_string()
+        }
+    };
+
+    let mut to_remove = HashSet::new();
+    if !opts.no_delete {
+        for entry in destination.read_dir()? {
+            let path = entry?.path();
+            if path.join(".cargo-checksum.json").exists() {
+                to_remove.insert(path);
+            }
+        }
+    }
+
+    let mut sources = HashSet::new();
+    for (id, pkg) in packages.iter() {
+        sources.insert(id.source_id().clone());
+        let dst = destination.join(dir_name(id));
+        to_remove.remove(&dst);
+
+        // Registry crates are immutable, so if the same checksum was already
+        // vendored there's nothing to do.
+        let checksum = &checksums[id];
+        let cksum_file = dst.join(".cargo-checksum.json");
+        if checksum.is_some() && cksum_file.exists() {
+            let existing = paths::read(&cksum_file)?;
+            if let Ok(existing) = serde_json::from_str::<VendorChecksum>(&existing) {
+                if existing.package == *checksum {
+                    continue;
+                }
+            }
+        }
+
+        config
+            .shell()
+            .status("Vendoring", format!("{} to {}", id, dst.display()))?;
+        if dst.exists() {
+            paths::remove_dir_all(&dst)?;
+        }
+        let src = pkg.root();
+        let files = PathSource::new(src, id.source_id(), config).list_files(pkg)?;
+        let mut map = BTreeMap::new();
+        cp_sources(src, &files, &dst, &mut map)
+            .chain_err(|| format!("failed to copy over vendored sources for: {}", id))?;
+
+        let json = serde_json::to_string(&VendorChecksum {
+            package: checksum.clone(),
+            files: map,
+        })?;
+        paths::write(&cksum_file, json.as_bytes())?;
+    }
+
+    for path in to_remove {
+        paths::remove_dir_all(&path)?;
+    }
+
+    let mut source_config = BTreeMap::new();
+    for source_id in sources {
+        // Git sources of one repository differ only in their reference, so
+        // that's part of the name to keep them from replacing each other.
+        let name = if source_id.is_default_registry() {
+            "crates-io".to_string()
+        } else {
+            match source_id.git_reference().and_then(|r| r.pretty_ref()) {
+                Some(reference) => format!("{}?{}", source_id.url(), reference),
+                None => source_id.url().to_string(),
+            }
+        };
+        let source = if source_id.is_default_registry() {
+            VendorSource::Registry {
+                registry: None,
+                replace_with: VENDOR_SOURCE_NAME.to_string(),
+            }
+        } else if source_id.is_git() {
+            let mut branch = None;
+            let mut tag = None;
+            let mut rev = None;
+            match source_id.git_reference() {
+                Some(&GitReference::Branch(ref b)) => branch = Some(b.clone()),
+                Some(&GitReference::Tag(ref t)) => tag = Some(t.clone()),
+                Some(&GitReference::Rev(ref r)) => rev = Some(r.clone()),
+                None => {}
+            }
+            VendorSource::Git {
+                git: source_id.url().to_string(),
+                branch,
+                tag,
+                rev,
+                replace_with: VENDOR_SOURCE_NAME.to_string(),
+            }
+        } else {
+            VendorSource::Registry {
+                registry: Some(source_id.url().to_string()),
+                replace_with: VENDOR_SOURCE_NAME.to_string(),
+            }
+        };
+        source_config.insert(name, source);
+    }
+    source_config.insert(
+        VENDOR_SOURCE_NAME.to_string(),
+        VendorSource::Directory {
+            directory: opts.destination.to_path_buf(),
+        },
+    );
+
+    Ok(VendorConfig {
+        source: source_config,
+    })
+}
+
+fn cp_sources(
+    src: &Path,
+    files: &[PathBuf],
+    dst: &Path,
+    cksums: &mut BTreeMap<String, String>,
+) -> CargoResult<()> {
+    for p in files {
+        let relative = p.strip_prefix(&src).unwrap();
+
+        match relative.to_str() {
+            // Skip git config files as they're not relevant to builds most of
+            // the time, and if a vendor directory is checked into someone
+            // else's repository they'd only interfere with it.
+            Some(".gitattributes") | Some(".gitignore") | Some(".git") => continue,
+
+            // Temporary Cargo files
+            Some(".cargo-ok") => continue,
+
+            // Skip patch-style orig/rej files. Published crates on crates.io
+            // have `Cargo.toml.orig` which we don't want to use here and
+            // otherwise these are rarely used as part of the build process.
+            Some(filename) => {
+                if filename.ends_with(".orig") || filename.ends_with(".rej") {
+                    continue;
+                }
+            }
+            _ => {}
+        };
+
+        // Join the components individually so the destination uses the
+        // platform's separators even if `relative` came from git.
+        let dst = relative
+            .iter()
+            .fold(dst.to_owned(), |acc, component| acc.join(&component));
+
+        fs::create_dir_all(dst.parent().unwrap())?;
+        let contents = paths::read_bytes(p)?;
+        paths::write(&dst, &contents)?;
+
+        let mut sha = Sha256::new();
+        sha.update(&contents);
+        cksums.insert(
+            relative.to_str().unwrap().replace("\\", "/"),
+            hex::encode(sha.finish()),
+        );
+    }
+    Ok(())
+}
@@ -1,16 +1,25 @@
+pub use self::cargo_add::{add, remove, upgrade_requirements, AddOptions, AddSource, RemoveOptions};
+pub use self::cargo_add::UpgradedManifests;
+pub use self::cargo_advisories::{advisories, AdvisoriesOptions};
+pub use self::cargo_audit_policy::audit_policy;
 pub use self::cargo_clean::{clean, CleanOptions};
 pub use self::cargo_compile::{compile, compile_with_exec, compile_ws, CompileOptions};
 pub use self::cargo_compile::{CompileFilter, FilterRule, Packages};
+pub use self::cargo_config::{get as config_get, ConfigFormat, GetOptions as ConfigGetOptions};
 pub use self::cargo_read_manifest::{read_package, read_packages};
 pub use self::cargo_run::run;
 pub use self::cargo_install::{install, install_list, uninstall};
 pub use self::cargo_new::{init, new, NewOptions, VersionControl};
 pub use self::cargo_doc::{doc, DocOptions};
+pub use self::cargo_duplicates::{duplicates, DuplicatesOptions};
 pub use self::cargo_generate_lockfile::generate_lockfile;
 pub use self::cargo_generate_lockfile::update_lockfile;
 pub use self::cargo_generate_lockfile::UpdateOptions;
 pub use self::lockfile::{load_pkg_lockfile, write_pkg_lockfile};
-pub use self::cargo_test::{run_benches, run_tests, TestOptions};
+pub use self::cargo_outdated::{outdated, OutdatedOptions};
+pub use self::cargo_test::{run_benches, run_tests, Partition, PartitionKind, TestOptions};
+pub use self::cargo_tree::{tree, TreeOptions};
+pub use self::cargo_vendor::{vendor, VendorConfig, VendorOptions};
 pub use self::cargo_package::{package, PackageOpts};
 pub use self::registry::{publish, registry_configuration, RegistryConfig};
 pub use self::registry::{http_handle, needs_custom_http_transport, registry_login, search};
@@ -23,19 +32,27 @@ pub use self::resolve::{add_overrides, get_resolved_packages, resolve_with_previ
 pub use self::cargo_output_metadata::{output_metadata, ExportInfo, OutputMetadataOptions};
 pub use self::fix::{fix, FixOptions, fix_maybe_exec_rustc};
 
+mod cargo_add;
+mod cargo_advisories;
+mod cargo_audit_policy;
 mod cargo_clean;
 mod cargo_compile;
+mod cargo_config;
 mod cargo_doc;
+mod cargo_duplicates;
 mod cargo_fetch;
 mod cargo_generate_lockfile;
 mod cargo_install;
 mod cargo_new;
+mod cargo_outdated;
 mod cargo_output_metadata;
 mod cargo_package;
 mod cargo_pkgid;
 mod cargo_read_manifest;
 mod cargo_run;
 mod cargo_test;
+mod cargo_tree;
+mod cargo_vendor;
 mod lockfile;
 mod registry;
 mod resolve;
@@ -242,6 +242,7 @@ fn transmit(
             license_file: license_file.clone(),
             badges: badges.clone(),
             links: links.clone(),
+            rust_version: pkg.rust_version().map(|v| v.to_string()),
         },
         tarball,
     );
@@ -1,5 +1,7 @@
 use std::collections::HashSet;
 
+use semver::Version;
+
 use core::registry::PackageRegistry;
 use core::resolver::{self, Method, Resolve};
 use core::{PackageId, PackageIdSpec, PackageSet, Source, SourceId, Workspace};
@@ -16,7 +18,7 @@ use util::profile;
 pub fn resolve_ws<'a>(ws: &Workspace<'a>) -> CargoResult<(PackageSet<'a>, Resolve)> {
     let mut registry = PackageRegistry::new(ws.config())?;
     let resolve = resolve_with_registry(ws, &mut registry, true)?;
-    let packages = get_resolved_packages(&resolve, registry);
+    let packages = get_resolved_packages(&resolve, registry)?;
     Ok((packages, resolve))
 }
 
@@ -96,7 +98,7 @@ pub fn resolve_ws_with_method<'a>(
         true,
     )?;
 
-    let packages = get_resolved_packages(&resolved_with_overrides, registry);
+    let packages = get_resolved_packages(&resolved_with_overrides, registry)?;
 
     Ok((packages, resolved_with_overrides))
 }
@@ -322,11 +324,13 @@ pub fn resolve_with_previous<'a, 'cfg>(
     };
 
     ws.preload(registry);
+    let rust_version = preferred_rust_version(ws)?;
     let mut resolved = resolver::resolve(
         &summaries,
         &replace,
         registry,
         &try_to_use,
+        rust_version.as_ref(),
         Some(ws.config()),
         warn,
     )?;
@@ -337,6 +341,31 @@ pub fn resolve_with_previous<'a, 'cfg>(
     Ok(resolved)
 }
 
+/// Returns the version of Rust the resolver should prefer dependencies to be
+/// compatible with, which is only done when `resolver.incompatible-rust-versions`
+/// is set to `fallback`.
+///
+/// That's the oldest `rust-version` of the workspace members, or the version
+/// of the active rustc if none of them declares one.
+fn preferred_rust_version(ws: &Workspace) -> CargoResult<Option<Version>> {
+    let config = ws.config();
+    match config.get_string("resolver.incompatible-rust-versions")? {
+        None => return Ok(None),
+        Some(ref v) if v.val == "allow" => return Ok(None),
+        Some(ref v) if v.val == "fallback" => {}
+        Some(v) => bail!(
+            "`resolver.incompatible-rust-versions` must be `allow` or \
+             `fallback`, but found `{}`",
+            v.val
+        ),
+    }
+    let oldest = ws.members().filter_map(|pkg| pkg.rust_version()).min();
+    match oldest {
+        Some(rust_version) => Ok(Some(rust_version.clone())),
+        None => Ok(Some(config.rustc(Some(ws))?.version()?)),
+    }
+}
+
 /// Read the `paths` configuration variable to discover all path overrides that
 /// have been configured.
 pub fn add_overrides<'a>(
@@ -348,11 +377,11 @@ pub fn add_overrides<'a>(
         None => return Ok(()),
     };
 
-    let paths = paths.val.iter().map(|&(ref s, ref p)| {
-        // The path listed next to the string is the config file in which the
-        // key was located, so we want to pop off the `.cargo/config` component
-        // to get the directory containing the `.cargo` folder.
-        (p.parent().unwrap().parent().unwrap().join(s), p)
+    let paths = paths.val.iter().map(|&(ref s, ref def)| {
+        // The definition listed next to the string is usually the config file
+        // in which the key was located, and paths are relative to the
+        // directory containing its `.cargo` folder.
+        (def.root(ws.config()).join(s), def)
     });
 
     for (path, definition) in paths {
@@ -363,7 +392,7 @@ pub fn add_overrides<'a>(
                 "failed to update path override `{}` \
                  (defined in `{}`)",
                 path.display(),
-                definition.display()
+                definition
             )
         })?;
         registry.add_override(Box::new(source));
@@ -374,7 +403,7 @@ pub fn add_overrides<'a>(
 pub fn get_resolved_packages<'a>(
     resolve: &Resolve,
     registry: PackageRegistry<'a>,
-) -> PackageSet<'a> {
+) -> CargoResult<PackageSet<'a>> {
     let ids: Vec<PackageId> = resolve.iter().cloned().collect();
     registry.get(&ids)
 }
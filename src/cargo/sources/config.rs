@@ -5,14 +5,14 @@
 //! sources to one another via the `replace-with` key in `.cargo/config`.
 
 use std::collections::HashMap;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 
 use url::Url;
 
 use core::{GitReference, Source, SourceId};
 use sources::ReplacedSource;
 use util::{Config, ToUrl};
-use util::config::ConfigValue;
+use util::config::{ConfigValue, Definition};
 use util::errors::{CargoResult, CargoResultExt};
 
 #[derive(Clone)]
@@ -36,9 +36,9 @@ struct SourceConfig {
     id: SourceId,
 
     // Name of the source that this source should be replaced with. This field
-    // is a tuple of (name, path) where path is where this configuration key was
-    // defined (the literal `.cargo/config` file).
-    replace_with: Option<(String, PathBuf)>,
+    // is a tuple of (name, definition) where definition is where this
+    // configuration key was defined (usually the literal `.cargo/config` file).
+    replace_with: Option<(String, Definition)>,
 }
 
 impl<'cfg> SourceConfigMap<'cfg> {
@@ -78,7 +78,8 @@ impl<'cfg> SourceConfigMap<'cfg> {
             Some(name) => name,
             None => return Ok(id.load(self.config)?),
         };
-        let mut path = Path::new("/");
+        let root = Definition::Path(PathBuf::from("/"));
+        let mut def = &root;
         let orig_name = name;
         let new_id;
         loop {
@@ -90,13 +91,13 @@ impl<'cfg> SourceConfigMap<'cfg> {
                      (configuration in `{}`)",
                     name,
                     orig_name,
-                    path.display()
+                    def
                 ),
             };
             match cfg.replace_with {
-                Some((ref s, ref p)) => {
+                Some((ref s, ref d)) => {
                     name = s;
-                    path = p;
+                    def = d;
                 }
                 None if *id == cfg.id => return Ok(id.load(self.config)?),
                 None => {
@@ -111,7 +112,7 @@ impl<'cfg> SourceConfigMap<'cfg> {
                      `{}` is eventually replaced with itself \
                      (configuration in `{}`)",
                     name,
-                    path.display()
+                    def
                 )
             }
         }
@@ -159,19 +160,13 @@ restore the source replacement configuration to continue the build
             srcs.push(SourceId::for_registry(&url)?);
         }
         if let Some(val) = table.get("local-registry") {
-            let (s, path) = val.string(&format!("source.{}.local-registry", name))?;
-            let mut path = path.to_path_buf();
-            path.pop();
-            path.pop();
-            path.push(s);
+            let (s, def) = val.string(&format!("source.{}.local-registry", name))?;
+            let path = def.root(self.config).join(s);
             srcs.push(SourceId::for_local_registry(&path)?);
         }
         if let Some(val) = table.get("directory") {
-            let (s, path) = val.string(&format!("source.{}.directory", name))?;
-            let mut path = path.to_path_buf();
-            path.pop();
-            path.pop();
-            path.push(s);
+            let (s, def) = val.string(&format!("source.{}.directory", name))?;
+            let path = def.root(self.config).join(s);
             srcs.push(SourceId::for_directory(&path)?);
         }
         if let Some(val) = table.get("git") {
@@ -214,8 +209,8 @@ restore the source replacement configuration to continue the build
 
         let mut replace_with = None;
         if let Some(val) = table.get("replace-with") {
-            let (s, path) = val.string(&format!("source.{}.replace-with", name))?;
-            replace_with = Some((s.to_string(), path.to_path_buf()));
+            let (s, def) = val.string(&format!("source.{}.replace-with", name))?;
+            replace_with = Some((s.to_string(), def.clone()));
         }
 
         self.add(
@@ -229,13 +224,13 @@ restore the source replacement configuration to continue the build
         return Ok(());
 
         fn url(cfg: &ConfigValue, key: &str) -> CargoResult<Url> {
-            let (url, path) = cfg.string(key)?;
+            let (url, def) = cfg.string(key)?;
             let url = url.to_url().chain_err(|| {
                 format!(
                     "configuration key `{}` specified an invalid \
                      URL (in {})",
                     key,
-                    path.display()
+                    def
                 )
             })?;
             Ok(url)
@@ -0,0 +1,311 @@
+//! A `RegistryData` for registries which serve their index over plain HTTP.
+//!
+//! Rather than cloning the whole index with git, each index file is fetched
+//! individually when it's needed, from the same path relative to the index
+//! URL as it would have in a git index. Fetched files are cached in the
+//! registry's index directory together with the `ETag` and `Last-Modified`
+//! headers of the response, so later updates only need a conditional request
+//! per file, which the server can answer with `304 Not Modified`.
+//!
+//! Registries are selected to use this protocol by prefixing their index URL
+//! with `sparse+`, as in `sparse+https://example.com/index/`.
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str;
+
+use curl::easy::List;
+use serde_json;
+use url::Url;
+
+use core::{PackageId, SourceId};
+use sources::registry::remote::{crate_url, store_crate};
+use sources::registry::{IndexFileNotFound, MaybeLock, RegistryConfig, RegistryData};
+use util::errors::{CargoResult, CargoResultExt, HttpNot200};
+use util::{network, paths};
+use util::{Config, FileLock, Filesystem, ToUrl};
+
+/// Suffix of the file next to each cached index file which records the
+/// headers needed to revalidate it. Crate names can't contain a `.`, so this
+/// never collides with an index file.
+const HEADERS_SUFFIX: &str = ".cache-headers";
+
+pub struct HttpRegistry<'cfg> {
+    index_path: Filesystem,
+    cache_path: Filesystem,
+    source_id: SourceId,
+    config: &'cfg Config,
+    /// Whether cached index files need revalidating with the server. Until
+    /// the index is updated, cached files are used as they are so that
+    /// locked dependencies can be resolved without touching the network.
+    requested_update: Cell<bool>,
+    /// Index files which have already been fetched or revalidated since the
+    /// index was updated.
+    fresh: RefCell<HashSet<PathBuf>>,
+}
+
+/// The result of requesting an index file from the server.
+enum Fetched {
+    /// The file changed, here are its new contents and caching headers.
+    Modified(Vec<u8>, Vec<String>),
+    /// Our cached copy is still up to date.
+    NotModified,
+    /// The file doesn't exist, so the crate isn't in the registry.
+    NotFound,
+}
+
+impl<'cfg> HttpRegistry<'cfg> {
+    pub fn new(source_id: &SourceId, config: &'cfg Config, name: &str) -> HttpRegistry<'cfg> {
+        HttpRegistry {
+            index_path: config.registry_index_path().join(name),
+            cache_path: config.registry_cache_path().join(name),
+            source_id: source_id.clone(),
+            config,
+            requested_update: Cell::new(false),
+            fresh: RefCell::new(HashSet::new()),
+        }
+    }
+
+    /// The index URL with the `sparse+` prefix removed, ending in a `/` so
+    /// index paths can be joined onto it.
+    fn index_url(&self) -> CargoResult<Url> {
+        let url = self.source_id.url().as_str();
+        let mut url = url.trim_left_matches("sparse+").to_string();
+        if !url.ends_with('/') {
+            url.push('/');
+        }
+        url.to_url()
+    }
+
+    /// Requests `path` from the server, conditionally on our cached copy
+    /// having changed if we have one.
+    fn fetch(&self, path: &Path, cache_file: &Path) -> CargoResult<Fetched> {
+        let relative = path.to_str().unwrap().replace("\\", "/");
+        let url = self.index_url()?.join(&relative)?.to_string();
+
+        let mut headers = List::new();
+        if cache_file.exists() {
+            let cached_headers = paths::read(&headers_path(cache_file)).unwrap_or_default();
+            for line in cached_headers.lines() {
+                let mut parts = line.splitn(2, ':');
+                let (name, value) = match (parts.next(), parts.next()) {
+                    (Some(name), Some(value)) => (name.trim(), value.trim()),
+                    _ => continue,
+                };
+                if name.eq_ignore_ascii_case("etag") {
+                    headers.append(&format!("If-None-Match: {}", value))?;
+                } else if name.eq_ignore_ascii_case("last-modified") {
+                    headers.append(&format!("If-Modified-Since: {}", value))?;
+                }
+            }
+        }
+
+        let mut handle = self.config.http()?.borrow_mut();
+        handle.get(true)?;
+        handle.url(&url)?;
+        handle.follow_location(true)?;
+        handle.progress(false)?;
+        handle.http_headers(headers)?;
+        let mut body = Vec::new();
+        let mut cache_headers = Vec::new();
+        let result = network::with_retry(self.config, || {
+            body = Vec::new();
+            cache_headers = Vec::new();
+            {
+                let mut handle = handle.transfer();
+                handle.write_function(|buf| {
+                    body.extend_from_slice(buf);
+                    Ok(buf.len())
+                })?;
+                handle.header_function(|header| {
+                    if let Ok(header) = str::from_utf8(header) {
+                        let header = header.trim();
+                        let lower = header.to_lowercase();
+                        if lower.starts_with("etag:") || lower.starts_with("last-modified:") {
+                            cache_headers.push(header.to_string());
+                        }
+                    }
+                    true
+                })?;
+                handle.perform()?;
+            }
+            Ok(handle.response_code()?)
+        });
+        // The handle is shared, so don't let our conditional headers leak
+        // into anyone else's requests.
+        handle.http_headers(List::new())?;
+        let code = result.chain_err(|| format!("failed to fetch `{}`", url))?;
+
+        match code {
+            200 => Ok(Fetched::Modified(body, cache_headers)),
+            304 => Ok(Fetched::NotModified),
+            404 | 410 => Ok(Fetched::NotFound),
+            code => {
+                let url = handle.effective_url()?.unwrap_or(&url);
+                Err(HttpNot200 {
+                    code,
+                    url: url.to_string(),
+                }.into())
+            }
+        }
+    }
+}
+
+fn headers_path(cache_file: &Path) -> PathBuf {
+    let mut name = cache_file.file_name().unwrap().to_os_string();
+    name.push(HEADERS_SUFFIX);
+    cache_file.with_file_name(name)
+}
+
+impl<'cfg> RegistryData for HttpRegistry<'cfg> {
+    fn prepare(&self) -> CargoResult<()> {
+        self.index_path.create_dir()?;
+        Ok(())
+    }
+
+    fn index_path(&self) -> &Filesystem {
+        &self.index_path
+    }
+
+    fn load(
+        &self,
+        root: &Path,
+        path: &Path,
+        data: &mut FnMut(&[u8]) -> CargoResult<()>,
+    ) -> CargoResult<()> {
+        let cache_file = root.join(path);
+        let offline = self.config.offline();
+        let use_cache = offline || !self.requested_update.get()
+            || self.fresh.borrow().contains(path);
+        if use_cache && (offline || cache_file.exists()) {
+            if !cache_file.exists() {
+                return Err(IndexFileNotFound(path.display().to_string()).into());
+            }
+            return data(&paths::read_bytes(&cache_file)?);
+        }
+
+        debug!("fetching index file `{}`", path.display());
+        let fetched = self.fetch(path, &cache_file)?;
+        self.fresh.borrow_mut().insert(path.to_path_buf());
+        match fetched {
+            Fetched::Modified(body, headers) => {
+                fs::create_dir_all(cache_file.parent().unwrap())?;
+                paths::write(&cache_file, &body)?;
+                let mut contents = headers.join("\n");
+                contents.push('\n');
+                paths::write(&headers_path(&cache_file), contents.as_bytes())?;
+                data(&body)
+            }
+            Fetched::NotModified => data(&paths::read_bytes(&cache_file)?),
+            Fetched::NotFound => {
+                // Crates can't be unpublished, but be sure we don't keep
+                // serving a stale copy if the file went away anyway.
+                if cache_file.exists() {
+                    paths::remove_file(&cache_file)?;
+                    let _ = paths::remove_file(&headers_path(&cache_file));
+                }
+                Err(IndexFileNotFound(path.display().to_string()).into())
+            }
+        }
+    }
+
+    fn config(&mut self) -> CargoResult<Option<RegistryConfig>> {
+        debug!("loading config");
+        self.prepare()?;
+        let root = self.index_path.clone().into_path_unlocked();
+        let mut config = None;
+        self.load(&root, Path::new("config.json"), &mut |json| {
+            config = Some(serde_json::from_slice(json)?);
+            Ok(())
+        })?;
+        trace!("config loaded");
+        Ok(config)
+    }
+
+    fn update_index(&mut self) -> CargoResult<()> {
+        if self.config.offline() {
+            return Ok(());
+        }
+        if self.config.cli_unstable().no_index_update {
+            return Ok(());
+        }
+        if self.requested_update.get() {
+            return Ok(());
+        }
+
+        debug!("updating the index");
+
+        // See `RemoteRegistry::update_index` for why we get the HTTP handle
+        // here even though nothing is downloaded yet.
+        self.config.http()?;
+
+        self.prepare()?;
+        self.config
+            .shell()
+            .status("Updating", self.source_id.display_registry())?;
+
+        // Nothing is fetched up front, every index file the resolver asks
+        // for is revalidated lazily in `load` instead.
+        self.requested_update.set(true);
+        Ok(())
+    }
+
+    fn download(&mut self, pkg: &PackageId, _checksum: &str) -> CargoResult<MaybeLock> {
+        let filename = format!("{}-{}.crate", pkg.name(), pkg.version());
+        let path = Path::new(&filename);
+
+        // See `RemoteRegistry::download` for why there are two checks here.
+        if let Ok(dst) = self.cache_path.open_ro(path, self.config, &filename) {
+            let meta = dst.file().metadata()?;
+            if meta.len() > 0 {
+                return Ok(MaybeLock::Ready(dst));
+            }
+        }
+        let dst = self.cache_path.open_rw(path, self.config, &filename)?;
+        let meta = dst.file().metadata()?;
+        if meta.len() > 0 {
+            return Ok(MaybeLock::Ready(dst));
+        }
+        let config = match self.config()? {
+            Some(config) => config,
+            None => bail!(
+                "{} does not have a `config.json`",
+                self.source_id.display_registry()
+            ),
+        };
+        Ok(MaybeLock::Download {
+            url: crate_url(&config.dl, pkg)?,
+            descriptor: pkg.to_string(),
+        })
+    }
+
+    fn finish_download(
+        &mut self,
+        pkg: &PackageId,
+        checksum: &str,
+        data: &[u8],
+    ) -> CargoResult<FileLock> {
+        let filename = format!("{}-{}.crate", pkg.name(), pkg.version());
+        let mut dst = self.cache_path.open_rw(&filename, self.config, &filename)?;
+        store_crate(&mut dst, pkg, checksum, data)?;
+        Ok(dst)
+    }
+
+    fn is_sparse(&self) -> bool {
+        true
+    }
+
+    fn is_crate_downloaded(&self, pkg: &PackageId) -> bool {
+        let filename = format!("{}-{}.crate", pkg.name(), pkg.version());
+        let path = Path::new(&filename);
+
+        if let Ok(dst) = self.cache_path.open_ro(path, self.config, &filename) {
+            if let Ok(meta) = dst.file().metadata() {
+                return meta.len() > 0;
+            }
+        }
+        false
+    }
+}
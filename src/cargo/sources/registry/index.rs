@@ -8,7 +8,8 @@ use serde_json;
 use core::dependency::Dependency;
 use core::{PackageId, SourceId, Summary};
 use sources::registry::RegistryData;
-use sources::registry::{RegistryPackage, INDEX_LOCK};
+use sources::registry::{IndexFileNotFound, RegistryPackage, INDEX_LOCK};
+use util::to_semver::parse_rust_version;
 use util::{internal, CargoResult, Config, Filesystem};
 
 /// Crates.io treats hyphen and underscores as interchangeable
@@ -184,8 +185,22 @@ impl<'cfg> RegistryIndex<'cfg> {
             3 => format!("3/{}/{}", &fs_name[..1], fs_name),
             _ => format!("{}/{}/{}", &fs_name[0..2], &fs_name[2..4], fs_name),
         };
+        // Every name tried in a sparse registry is a request to the server,
+        // so only the name as given and its all-hyphen and all-underscore
+        // spellings are tried there.
+        let paths = if load.is_sparse() {
+            let mut paths = vec![raw_path.clone()];
+            for path in vec![raw_path.replace('_', "-"), raw_path.replace('-', "_")] {
+                if !paths.contains(&path) {
+                    paths.push(path);
+                }
+            }
+            paths
+        } else {
+            UncanonicalizedIter::new(&raw_path).take(1024).collect()
+        };
         let mut ret = Vec::new();
-         for path in UncanonicalizedIter::new(&raw_path).take(1024) {
+        for path in paths {
             let mut hit_closure = false;
             let err = load.load(&root, Path::new(&path), &mut |contents| {
                 hit_closure = true;
@@ -194,25 +209,19 @@ impl<'cfg> RegistryIndex<'cfg> {
                 ret.reserve(contents.lines().count());
                 let lines = contents.lines().map(|s| s.trim()).filter(|l| !l.is_empty());
 
-                let online = !self.config.cli_unstable().offline;
                 // Attempt forwards-compatibility on the index by ignoring
                 // everything that we ourselves don't understand, that should
                 // allow future cargo implementations to break the
                 // interpretation of each line here and older cargo will simply
                 // ignore the new lines.
                 ret.extend(lines.filter_map(|line| {
-                    let (summary, locked) = match self.parse_registry_package(line) {
-                        Ok(p) => p,
+                    match self.parse_registry_package(line) {
+                        Ok(p) => Some(p),
                         Err(e) => {
                             info!("failed to parse `{}` registry package: {}", name, e);
                             trace!("line: {}", line);
-                            return None;
+                            None
                         }
-                    };
-                    if online || load.is_crate_downloaded(summary.package_id()) {
-                        Some((summary, locked))
-                    } else {
-                        None
                     }
                 }));
 
@@ -228,6 +237,13 @@ impl<'cfg> RegistryIndex<'cfg> {
                 // result in the index so return when we find it.
                 return Ok(ret);
             }
+            // A sparse registry tells a missing crate apart from a server it
+            // couldn't reach, which mustn't look like the crate not existing.
+            if let Err(e) = err {
+                if load.is_sparse() && e.downcast_ref::<IndexFileNotFound>().is_none() {
+                    return Err(e);
+                }
+            }
         }
 
         Ok(ret)
@@ -246,6 +262,7 @@ impl<'cfg> RegistryIndex<'cfg> {
             features,
             yanked,
             links,
+            rust_version,
         } = serde_json::from_str(line)?;
         let pkgid = PackageId::new(&name, &vers, &self.source_id)?;
         let name = pkgid.name();
@@ -254,7 +271,14 @@ impl<'cfg> RegistryIndex<'cfg> {
             .map(|dep| dep.into_dep(&self.source_id))
             .collect::<CargoResult<Vec<_>>>()?;
         let summary = Summary::new(pkgid, deps, &features, links, false)?;
-        let summary = summary.set_checksum(cksum.clone());
+        let mut summary = summary.set_checksum(cksum.clone());
+        // An unparseable `rust_version` only loses the preference for
+        // compatible versions, so it doesn't make the entry unusable.
+        if let Some(rust_version) = rust_version {
+            if let Ok(rust_version) = parse_rust_version(&rust_version) {
+                summary = summary.set_rust_version(rust_version);
+            }
+        }
         self.hashes
             .entry(name.as_str())
             .or_insert_with(HashMap::new)
@@ -269,11 +293,20 @@ impl<'cfg> RegistryIndex<'cfg> {
         f: &mut FnMut(Summary),
     ) -> CargoResult<()> {
         let source_id = self.source_id.clone();
+        let offline = self.config.offline();
         let name = dep.package_name().as_str();
         let summaries = self.summaries(name, load)?;
         let summaries = summaries
             .iter()
             .filter(|&&(_, yanked)| dep.source_id().precise().is_some() || !yanked)
+            // In offline mode only the versions which have already been
+            // downloaded are used, unless the version is locked, in which case
+            // a missing one is reported when it's about to be downloaded.
+            .filter(|&&(ref s, _)| {
+                !offline
+                    || dep.source_id().precise().is_some()
+                    || load.is_crate_downloaded(s.package_id())
+            })
             .map(|s| s.0.clone());
 
         // Handle `cargo update --precise` here. If specified, our own source
@@ -49,6 +49,12 @@
 //!   the end of a file (the exact format is described later). This means that
 //!   the commits for an index are quite small and easily applied/compressable.
 //!
+//! Cloning the whole index is wasteful when only a few crates are needed
+//! though, so a registry can instead serve the same files over plain HTTP,
+//! which Cargo fetches one at a time as they're needed. Such "sparse"
+//! registries are selected with a `sparse+` prefix on their index URL, see
+//! the `http_remote` module for more.
+//!
 //! ## The format of the Index
 //!
 //! The index is a store for the list of versions for all packages known, so its
@@ -170,7 +176,7 @@ use serde_json;
 use tar::Archive;
 
 use core::dependency::{Dependency, Kind};
-use core::{Package, PackageId, Source, SourceId, Summary};
+use core::{MaybePackage, Package, PackageId, Source, SourceId, Summary};
 use sources::PathSource;
 use util::errors::CargoResultExt;
 use util::hex;
@@ -221,6 +227,7 @@ pub struct RegistryPackage<'a> {
     cksum: String,
     yanked: Option<bool>,
     links: Option<Cow<'a, str>>,
+    rust_version: Option<Cow<'a, str>>,
 }
 
 #[test]
@@ -337,13 +344,43 @@ pub trait RegistryData {
     ) -> CargoResult<()>;
     fn config(&mut self) -> CargoResult<Option<RegistryConfig>>;
     fn update_index(&mut self) -> CargoResult<()>;
-    fn download(&mut self, pkg: &PackageId, checksum: &str) -> CargoResult<FileLock>;
+    fn download(&mut self, pkg: &PackageId, checksum: &str) -> CargoResult<MaybeLock>;
+    fn finish_download(
+        &mut self,
+        pkg: &PackageId,
+        checksum: &str,
+        data: &[u8],
+    ) -> CargoResult<FileLock>;
 
     fn is_crate_downloaded(&self, _pkg: &PackageId) -> bool {
         true
     }
+
+    /// Whether index files are fetched over the network one at a time. Such
+    /// registries fail `load` with `IndexFileNotFound` for a missing file, so
+    /// any other error means the index couldn't be reached, and every file
+    /// looked up costs a request.
+    fn is_sparse(&self) -> bool {
+        false
+    }
 }
 
+/// The error of `RegistryData::load` for an index file which doesn't exist,
+/// as the crate isn't in the registry.
+#[derive(Debug, Fail)]
+#[fail(display = "index file `{}` does not exist", _0)]
+pub struct IndexFileNotFound(pub String);
+
+/// The result of `RegistryData::download`.
+pub enum MaybeLock {
+    /// The `.crate` file is already in the cache.
+    Ready(FileLock),
+    /// The `.crate` file needs to be fetched from `url`, and then passed to
+    /// `RegistryData::finish_download`.
+    Download { url: String, descriptor: String },
+}
+
+mod http_remote;
 mod index;
 mod local;
 mod remote;
@@ -361,6 +398,12 @@ impl<'cfg> RegistrySource<'cfg> {
         RegistrySource::new(source_id, config, &name, Box::new(ops), true)
     }
 
+    pub fn sparse(source_id: &SourceId, config: &'cfg Config) -> RegistrySource<'cfg> {
+        let name = short_name(source_id);
+        let ops = http_remote::HttpRegistry::new(source_id, config, &name);
+        RegistrySource::new(source_id, config, &name, Box::new(ops), false)
+    }
+
     pub fn local(source_id: &SourceId, path: &Path, config: &'cfg Config) -> RegistrySource<'cfg> {
         let name = short_name(source_id);
         let ops = local::LocalRegistry::new(path, config, &name);
@@ -445,6 +488,36 @@ impl<'cfg> RegistrySource<'cfg> {
         Ok(dst.clone())
     }
 
+    /// Loads the package out of a downloaded tarball, unpacking it first if
+    /// needed.
+    fn get_pkg(&mut self, package: &PackageId, path: &FileLock) -> CargoResult<Package> {
+        let path = self
+            .unpack_package(package, path)
+            .chain_err(|| internal(format!("failed to unpack package `{}`", package)))?;
+        let mut src = PathSource::new(&path, &self.source_id, self.config);
+        src.update()?;
+        let pkg = match src.download(package)? {
+            MaybePackage::Ready(pkg) => pkg,
+            MaybePackage::Download { .. } => unreachable!(),
+        };
+
+        // Unfortunately the index and the actual Cargo.toml in the index can
+        // differ due to historical Cargo bugs. To paper over these we trash the
+        // *summary* loaded from the Cargo.toml we just downloaded with the one
+        // we loaded from the index.
+        let summaries = self
+            .index
+            .summaries(package.name().as_str(), &mut *self.ops)?;
+        let summary = summaries
+            .iter()
+            .map(|s| &s.0)
+            .find(|s| s.package_id() == package)
+            .expect("summary not found");
+        let mut manifest = pkg.manifest().clone();
+        manifest.set_summary(summary.clone());
+        Ok(Package::new(manifest, pkg.manifest_path()))
+    }
+
     fn do_update(&mut self) -> CargoResult<()> {
         self.ops.update_index()?;
         let path = self.ops.index_path();
@@ -516,31 +589,20 @@ impl<'cfg> Source for RegistrySource<'cfg> {
         Ok(())
     }
 
-    fn download(&mut self, package: &PackageId) -> CargoResult<Package> {
+    fn download(&mut self, package: &PackageId) -> CargoResult<MaybePackage> {
         let hash = self.index.hash(package, &mut *self.ops)?;
-        let path = self.ops.download(package, &hash)?;
-        let path = self
-            .unpack_package(package, &path)
-            .chain_err(|| internal(format!("failed to unpack package `{}`", package)))?;
-        let mut src = PathSource::new(&path, &self.source_id, self.config);
-        src.update()?;
-        let pkg = src.download(package)?;
+        match self.ops.download(package, &hash)? {
+            MaybeLock::Ready(file) => self.get_pkg(package, &file).map(MaybePackage::Ready),
+            MaybeLock::Download { url, descriptor } => {
+                Ok(MaybePackage::Download { url, descriptor })
+            }
+        }
+    }
 
-        // Unfortunately the index and the actual Cargo.toml in the index can
-        // differ due to historical Cargo bugs. To paper over these we trash the
-        // *summary* loaded from the Cargo.toml we just downloaded with the one
-        // we loaded from the index.
-        let summaries = self
-            .index
-            .summaries(package.name().as_str(), &mut *self.ops)?;
-        let summary = summaries
-            .iter()
-            .map(|s| &s.0)
-            .find(|s| s.package_id() == package)
-            .expect("summary not found");
-        let mut manifest = pkg.manifest().clone();
-        manifest.set_summary(summary.clone());
-        Ok(Package::new(manifest, pkg.manifest_path()))
+    fn finish_download(&mut self, package: &PackageId, data: Vec<u8>) -> CargoResult<Package> {
+        let hash = self.index.hash(package, &mut *self.ops)?;
+        let file = self.ops.finish_download(package, &hash, &data)?;
+        self.get_pkg(package, &file)
     }
 
     fn fingerprint(&self, pkg: &Package) -> CargoResult<String> {
@@ -13,11 +13,11 @@ use lazycell::LazyCell;
 
 use core::{PackageId, SourceId};
 use sources::git;
-use sources::registry::{RegistryConfig, RegistryData, CRATE_TEMPLATE, INDEX_LOCK, VERSION_TEMPLATE};
-use util::network;
+use sources::registry::{MaybeLock, RegistryConfig, RegistryData, CRATE_TEMPLATE, INDEX_LOCK,
+                        VERSION_TEMPLATE};
 use util::{FileLock, Filesystem};
-use util::{Config, Progress, Sha256, ToUrl};
-use util::errors::{CargoResult, CargoResultExt, HttpNot200};
+use util::{Config, Sha256, ToUrl};
+use util::errors::{CargoResult, CargoResultExt};
 
 pub struct RemoteRegistry<'cfg> {
     index_path: Filesystem,
@@ -170,7 +170,7 @@ impl<'cfg> RegistryData for RemoteRegistry<'cfg> {
     }
 
     fn update_index(&mut self) -> CargoResult<()> {
-        if self.config.cli_unstable().offline {
+        if self.config.offline() {
             return Ok(());
         }
         if self.config.cli_unstable().no_index_update {
@@ -206,7 +206,7 @@ impl<'cfg> RegistryData for RemoteRegistry<'cfg> {
         Ok(())
     }
 
-    fn download(&mut self, pkg: &PackageId, checksum: &str) -> CargoResult<FileLock> {
+    fn download(&mut self, pkg: &PackageId, _checksum: &str) -> CargoResult<MaybeLock> {
         let filename = format!("{}-{}.crate", pkg.name(), pkg.version());
         let path = Path::new(&filename);
 
@@ -219,71 +219,30 @@ impl<'cfg> RegistryData for RemoteRegistry<'cfg> {
         if let Ok(dst) = self.cache_path.open_ro(path, self.config, &filename) {
             let meta = dst.file().metadata()?;
             if meta.len() > 0 {
-                return Ok(dst);
+                return Ok(MaybeLock::Ready(dst));
             }
         }
-        let mut dst = self.cache_path.open_rw(path, self.config, &filename)?;
+        let dst = self.cache_path.open_rw(path, self.config, &filename)?;
         let meta = dst.file().metadata()?;
         if meta.len() > 0 {
-            return Ok(dst);
+            return Ok(MaybeLock::Ready(dst));
         }
-        self.config.shell().status("Downloading", pkg)?;
-
         let config = self.config()?.unwrap();
-        let mut url = config.dl.clone();
-        if !url.contains(CRATE_TEMPLATE) && !url.contains(VERSION_TEMPLATE) {
-            write!(url, "/{}/{}/download", CRATE_TEMPLATE, VERSION_TEMPLATE).unwrap();
-        }
-        let url = url.replace(CRATE_TEMPLATE, &*pkg.name())
-            .replace(VERSION_TEMPLATE, &pkg.version().to_string())
-            .to_url()?;
-
-        // TODO: don't download into memory, but ensure that if we ctrl-c a
-        //       download we should resume either from the start or the middle
-        //       on the next time
-        let url = url.to_string();
-        let mut handle = self.config.http()?.borrow_mut();
-        handle.get(true)?;
-        handle.url(&url)?;
-        handle.follow_location(true)?;
-        let mut state = Sha256::new();
-        let mut body = Vec::new();
-        network::with_retry(self.config, || {
-            state = Sha256::new();
-            body = Vec::new();
-            let mut pb = Progress::new("Fetch", self.config);
-            {
-                handle.progress(true)?;
-                let mut handle = handle.transfer();
-                handle.progress_function(|dl_total, dl_cur, _, _| {
-                    pb.tick(dl_cur as usize, dl_total as usize).is_ok()
-                })?;
-                handle.write_function(|buf| {
-                    state.update(buf);
-                    body.extend_from_slice(buf);
-                    Ok(buf.len())
-                })?;
-                handle.perform()?;
-            }
-            let code = handle.response_code()?;
-            if code != 200 && code != 0 {
-                let url = handle.effective_url()?.unwrap_or(&url);
-                Err(HttpNot200 {
-                    code,
-                    url: url.to_string(),
-                }.into())
-            } else {
-                Ok(())
-            }
-        })?;
-
-        // Verify what we just downloaded
-        if hex::encode(state.finish()) != checksum {
-            bail!("failed to verify the checksum of `{}`", pkg)
-        }
+        Ok(MaybeLock::Download {
+            url: crate_url(&config.dl, pkg)?,
+            descriptor: pkg.to_string(),
+        })
+    }
 
-        dst.write_all(&body)?;
-        dst.seek(SeekFrom::Start(0))?;
+    fn finish_download(
+        &mut self,
+        pkg: &PackageId,
+        checksum: &str,
+        data: &[u8],
+    ) -> CargoResult<FileLock> {
+        let filename = format!("{}-{}.crate", pkg.name(), pkg.version());
+        let mut dst = self.cache_path.open_rw(&filename, self.config, &filename)?;
+        store_crate(&mut dst, pkg, checksum, data)?;
         Ok(dst)
     }
 
@@ -300,6 +259,42 @@ impl<'cfg> RegistryData for RemoteRegistry<'cfg> {
     }
 }
 
+/// Returns the URL of the `.crate` file for `pkg` given the `dl` key of a
+/// registry's `config.json`.
+pub fn crate_url(dl: &str, pkg: &PackageId) -> CargoResult<String> {
+    let mut url = dl.to_string();
+    if !url.contains(CRATE_TEMPLATE) && !url.contains(VERSION_TEMPLATE) {
+        write!(url, "/{}/{}/download", CRATE_TEMPLATE, VERSION_TEMPLATE).unwrap();
+    }
+    let url = url.replace(CRATE_TEMPLATE, &*pkg.name())
+        .replace(VERSION_TEMPLATE, &pkg.version().to_string())
+        .to_url()?;
+    Ok(url.to_string())
+}
+
+/// Verifies the downloaded contents of the `.crate` file for `pkg` against
+/// `checksum`, and if they match writes them into the cache file `dst`.
+pub fn store_crate(
+    dst: &mut FileLock,
+    pkg: &PackageId,
+    checksum: &str,
+    data: &[u8],
+) -> CargoResult<()> {
+    let mut state = Sha256::new();
+    state.update(data);
+    if hex::encode(state.finish()) != checksum {
+        bail!("failed to verify the checksum of `{}`", pkg)
+    }
+
+    // The file may hold the remains of an interrupted download, so make
+    // sure nothing is left after what we write.
+    dst.file().set_len(0)?;
+    dst.seek(SeekFrom::Start(0))?;
+    dst.write_all(data)?;
+    dst.seek(SeekFrom::Start(0))?;
+    Ok(())
+}
+
 impl<'cfg> Drop for RemoteRegistry<'cfg> {
     fn drop(&mut self) {
         // Just be sure to drop this before our other fields
@@ -2,8 +2,9 @@ use std;
 use std::cell::{RefCell, RefMut};
 use std::collections::hash_map::Entry::{Occupied, Vacant};
 use std::collections::hash_map::HashMap;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::env;
+use std::ffi::OsString;
 use std::fmt;
 use std::fs::{self, File};
 use std::io::prelude::*;
@@ -60,6 +61,9 @@ pub struct Config {
     frozen: bool,
     /// `locked` is set if we should not update lock files
     locked: bool,
+    /// `offline` is set if we should never access the network, but otherwise
+    /// continue operating if possible, with `--offline` or `net.offline`.
+    offline: bool,
     /// A global static IPC control mechanism (used for managing parallel builds)
     jobserver: Option<jobserver::Client>,
     /// Cli flags of the form "-Z something"
@@ -78,6 +82,11 @@ pub struct Config {
     env: HashMap<String, String>,
     /// Profiles loaded from config.
     profiles: LazyCell<ConfigProfiles>,
+    /// Environment variables set by the `[env]` table.
+    env_config: LazyCell<BTreeMap<String, OsString>>,
+    /// The `--config` arguments, each a `KEY=VALUE` TOML snippet or the path
+    /// of an extra config file.
+    cli_config: Vec<String>,
 }
 
 impl Config {
@@ -118,6 +127,7 @@ impl Config {
             extra_verbose: false,
             frozen: false,
             locked: false,
+            offline: false,
             jobserver: unsafe {
                 if GLOBAL_JOBSERVER.is_null() {
                     None
@@ -133,6 +143,8 @@ impl Config {
             target_dir: None,
             env,
             profiles: LazyCell::new(),
+            env_config: LazyCell::new(),
+            cli_config: Vec::new(),
         }
     }
 
@@ -249,7 +261,7 @@ impl Config {
 
     pub fn profiles(&self) -> CargoResult<&ConfigProfiles> {
         self.profiles.try_borrow_with(|| {
-            let ocp = self.get::<Option<ConfigProfiles>>("profile")?;
+            let ocp = ConfigProfiles::load(self)?;
             if let Some(config_profiles) = ocp {
                 // Warn if config profiles without CLI option.
                 if !self.cli_unstable().config_profile {
@@ -266,10 +278,68 @@ impl Config {
         })
     }
 
+    /// The environment variables from the `[env]` table, which are set for
+    /// every process run as part of a build, and by `cargo run` and `cargo
+    /// test`.
+    ///
+    /// A variable which is already set in Cargo's own environment is left
+    /// out, unless it's set with `force = true`. With `relative = true` the
+    /// value is a path relative to the directory containing the `.cargo`
+    /// directory the config file is in.
+    pub fn env_config(&self) -> CargoResult<&BTreeMap<String, OsString>> {
+        self.env_config.try_borrow_with(|| {
+            let mut vars = BTreeMap::new();
+            let table = match self.get_table("env")? {
+                Some(table) => table.val,
+                None => return Ok(vars),
+            };
+            for (name, value) in table {
+                let key = format!("env.{}", name);
+                let (value, force, relative, def) = match value {
+                    CV::String(s, def) => (s, false, false, def),
+                    CV::Table(mut table, def) => {
+                        let value = match table.remove("value") {
+                            Some(CV::String(s, _)) => s,
+                            Some(v) => {
+                                return self.expected("string", &format!("{}.value", key), &v)
+                            }
+                            None => bail!("missing `value` for `{}` in {}", key, def),
+                        };
+                        let flag = |field: &str| match table.get(field) {
+                            Some(&CV::Boolean(b, _)) => Ok(b),
+                            Some(v) => self.expected("boolean", &format!("{}.{}", key, field), v),
+                            None => Ok(false),
+                        };
+                        (value, flag("force")?, flag("relative")?, def)
+                    }
+                    v => return self.expected("string or table", &key, &v),
+                };
+                if !force && self.env.contains_key(&name) {
+                    continue;
+                }
+                let value = if relative {
+                    def.root(self).join(value).into_os_string()
+                } else {
+                    OsString::from(value)
+                };
+                vars.insert(name, value);
+            }
+            Ok(vars)
+        })
+    }
+
     pub fn values(&self) -> CargoResult<&HashMap<String, ConfigValue>> {
         self.values.try_borrow_with(|| self.load_values())
     }
 
+    pub fn values_mut(&mut self) -> CargoResult<&mut HashMap<String, ConfigValue>> {
+        self.values()?;
+        match self.values.borrow_mut() {
+            Some(map) => Ok(map),
+            None => bail!("config values not loaded yet"),
+        }
+    }
+
     // Note: This is used by RLS, not Cargo.
     pub fn set_values(&self, values: HashMap<String, ConfigValue>) -> CargoResult<()> {
         if self.values.borrow().is_some() {
@@ -281,6 +351,16 @@ impl Config {
         }
     }
 
+    /// Sets the `--config` arguments, which take precedence over every other
+    /// source of configuration.
+    ///
+    /// Any values loaded so far are discarded, so this should be called
+    /// before the configuration is used for anything else.
+    pub fn set_cli_config(&mut self, args: Vec<String>) {
+        self.cli_config = args;
+        self.values = LazyCell::new();
+    }
+
     pub fn cwd(&self) -> &Path {
         &self.cwd
     }
@@ -288,7 +368,9 @@ impl Config {
     pub fn target_dir(&self) -> CargoResult<Option<Filesystem>> {
         if let Some(ref dir) = self.target_dir {
             Ok(Some(dir.clone()))
-        } else if let Some(dir) = env::var_os("CARGO_TARGET_DIR") {
+        } else if let Some(dir) = env::var_os("CARGO_TARGET_DIR")
+            .filter(|_| !self.is_cli_key(&ConfigKey::from_str("build.target-dir")))
+        {
             Ok(Some(Filesystem::new(self.cwd.join(dir))))
         } else if let Some(val) = self.get_path("build.target-dir")? {
             let val = self.cwd.join(val.val);
@@ -313,10 +395,10 @@ impl Config {
                         None => return Ok(None),
                     }
                 }
-                CV::Integer(_, ref path)
-                | CV::String(_, ref path)
-                | CV::List(_, ref path)
-                | CV::Boolean(_, ref path) => {
+                CV::Integer(_, ref def)
+                | CV::String(_, ref def)
+                | CV::List(_, ref def)
+                | CV::Boolean(_, ref def) => {
                     let idx = key.split('.').take(i).fold(0, |n, s| n + s.len()) + i - 1;
                     let key_so_far = &key[..idx];
                     bail!(
@@ -324,7 +406,7 @@ impl Config {
                          but found {} in {}",
                         key_so_far,
                         val.desc(),
-                        path.display()
+                        def
                     )
                 }
             }
@@ -337,11 +419,20 @@ impl Config {
         self.env = env;
     }
 
+    /// The environment variables `CARGO_*` overrides are read from.
+    pub fn env_vars(&self) -> &HashMap<String, String> {
+        &self.env
+    }
+
     fn get_env<T>(&self, key: &ConfigKey) -> Result<OptValue<T>, ConfigError>
     where
         T: FromStr,
         <T as FromStr>::Err: fmt::Display,
     {
+        // Values from `--config` take precedence over the environment.
+        if self.is_cli_key(key) {
+            return Ok(None);
+        }
         let key = key.to_env();
         match self.env.get(&key) {
             Some(value) => {
@@ -357,6 +448,24 @@ impl Config {
         }
     }
 
+    /// The value of the `CARGO_*` environment variable which overrides the
+    /// config key `key`, like `CARGO_BUILD_JOBS` for `build.jobs`, if it's set.
+    pub fn get_env_string(&self, key: &str) -> CargoResult<OptValue<String>> {
+        self.get_env(&ConfigKey::from_str(key))
+            .map_err(|e| e.into())
+    }
+
+    /// Whether `key` was set with `--config`.
+    fn is_cli_key(&self, key: &ConfigKey) -> bool {
+        match self.get_cv(&key.to_config()) {
+            Ok(Some(cv)) => match *cv.definition() {
+                Definition::Cli(_) => true,
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
     fn has_key(&self, key: &ConfigKey) -> bool {
         let env_key = key.to_env();
         if self.env.get(&env_key).is_some() {
@@ -386,9 +495,9 @@ impl Config {
                 let config_key = key.to_config();
                 let o_cv = self.get_cv(&config_key)?;
                 match o_cv {
-                    Some(CV::String(s, path)) => Ok(Some(Value {
+                    Some(CV::String(s, def)) => Ok(Some(Value {
                         val: s,
-                        definition: Definition::Path(path),
+                        definition: def,
                     })),
                     Some(cv) => Err(ConfigError::expected(&config_key, "a string", &cv)),
                     None => Ok(None),
@@ -409,9 +518,9 @@ impl Config {
                 let config_key = key.to_config();
                 let o_cv = self.get_cv(&config_key)?;
                 match o_cv {
-                    Some(CV::Boolean(b, path)) => Ok(Some(Value {
+                    Some(CV::Boolean(b, def)) => Ok(Some(Value {
                         val: b,
-                        definition: Definition::Path(path),
+                        definition: def,
                     })),
                     Some(cv) => Err(ConfigError::expected(&config_key, "true/false", &cv)),
                     None => Ok(None),
@@ -461,11 +570,11 @@ impl Config {
 
     // NOTE: This does *not* support environment variables.  Use `get` instead
     // if you want that.
-    pub fn get_list(&self, key: &str) -> CargoResult<OptValue<Vec<(String, PathBuf)>>> {
+    pub fn get_list(&self, key: &str) -> CargoResult<OptValue<Vec<(String, Definition)>>> {
         match self.get_cv(key)? {
-            Some(CV::List(i, path)) => Ok(Some(Value {
+            Some(CV::List(i, def)) => Ok(Some(Value {
                 val: i,
-                definition: Definition::Path(path),
+                definition: def,
             })),
             Some(val) => self.expected("list", key, &val),
             None => Ok(None),
@@ -481,13 +590,13 @@ impl Config {
         }
 
         match self.get_cv(key)? {
-            Some(CV::List(i, path)) => Ok(Some(Value {
+            Some(CV::List(i, def)) => Ok(Some(Value {
                 val: i.into_iter().map(|(s, _)| s).collect(),
-                definition: Definition::Path(path),
+                definition: def,
             })),
-            Some(CV::String(i, path)) => Ok(Some(Value {
+            Some(CV::String(i, def)) => Ok(Some(Value {
                 val: i.split(' ').map(str::to_string).collect(),
-                definition: Definition::Path(path),
+                definition: def,
             })),
             Some(val) => self.expected("list or string", key, &val),
             None => Ok(None),
@@ -496,9 +605,9 @@ impl Config {
 
     pub fn get_table(&self, key: &str) -> CargoResult<OptValue<HashMap<String, CV>>> {
         match self.get_cv(key)? {
-            Some(CV::Table(i, path)) => Ok(Some(Value {
+            Some(CV::Table(i, def)) => Ok(Some(Value {
                 val: i,
-                definition: Definition::Path(path),
+                definition: def,
             })),
             Some(val) => self.expected("table", key, &val),
             None => Ok(None),
@@ -517,9 +626,9 @@ impl Config {
         match self.get_env::<i64>(key)? {
             Some(v) => Ok(Some(v)),
             None => match self.get_cv(&config_key)? {
-                Some(CV::Integer(i, path)) => Ok(Some(Value {
+                Some(CV::Integer(i, def)) => Ok(Some(Value {
                     val: i,
-                    definition: Definition::Path(path),
+                    definition: def,
                 })),
                 Some(cv) => Err(ConfigError::expected(&config_key, "an integer", &cv)),
                 None => Ok(None),
@@ -539,6 +648,7 @@ impl Config {
         color: &Option<String>,
         frozen: bool,
         locked: bool,
+        offline: bool,
         target_dir: &Option<PathBuf>,
         unstable_flags: &[String],
     ) -> CargoResult<()> {
@@ -548,6 +658,7 @@ impl Config {
         // Ignore errors in the configuration files.
         let cfg_verbose = self.get_bool("term.verbose").unwrap_or(None).map(|v| v.val);
         let cfg_color = self.get_string("term.color").unwrap_or(None).map(|v| v.val);
+        let cfg_offline = self.get_bool("net.offline")?.map(|v| v.val).unwrap_or(false);
 
         let color = color.as_ref().or_else(|| cfg_color.as_ref());
 
@@ -583,6 +694,7 @@ impl Config {
         self.extra_verbose = extra_verbose;
         self.frozen = frozen;
         self.locked = locked;
+        self.offline = offline || cfg_offline;
         self.target_dir = cli_target_dir;
         self.cli_flags.parse(unstable_flags)?;
 
@@ -598,7 +710,13 @@ impl Config {
     }
 
     pub fn network_allowed(&self) -> bool {
-        !self.frozen() && !self.cli_unstable().offline
+        !self.frozen() && !self.offline()
+    }
+
+    /// Whether Cargo is in offline mode, and should make do with what's
+    /// already been downloaded.
+    pub fn offline(&self) -> bool {
+        self.offline
     }
 
     pub fn frozen(&self) -> bool {
@@ -611,34 +729,157 @@ impl Config {
 
     /// Loads configuration from the filesystem
     pub fn load_values(&self) -> CargoResult<HashMap<String, ConfigValue>> {
-        let mut cfg = CV::Table(HashMap::new(), PathBuf::from("."));
+        let mut cfg = CV::Table(HashMap::new(), Definition::Path(PathBuf::from(".")));
 
         walk_tree(&self.cwd, |path| {
-            let mut contents = String::new();
-            let mut file = File::open(&path)?;
-            file.read_to_string(&mut contents)
-                .chain_err(|| format!("failed to read configuration file `{}`", path.display()))?;
-            let toml = cargo_toml::parse(&contents, path, self).chain_err(|| {
-                format!("could not parse TOML configuration in `{}`", path.display())
-            })?;
-            let value = CV::from_toml(path, toml).chain_err(|| {
-                format!(
-                    "failed to load TOML configuration from `{}`",
-                    path.display()
-                )
-            })?;
+            let value = self.load_file(path)?;
             cfg.merge(value)
                 .chain_err(|| format!("failed to merge configuration at `{}`", path.display()))?;
             Ok(())
         }).chain_err(|| "could not load Cargo configuration")?;
 
         self.load_credentials(&mut cfg)?;
+        self.load_cli(&mut cfg)?;
         match cfg {
             CV::Table(map, _) => Ok(map),
             _ => unreachable!(),
         }
     }
 
+    /// Loads a single configuration file, along with the files it includes.
+    fn load_file(&self, path: &Path) -> CargoResult<ConfigValue> {
+        self.load_file_with_includes(path, &mut Vec::new())
+    }
+
+    /// Loads the configuration file at `path`, where `stack` is the chain of
+    /// files which included it.
+    ///
+    /// The files named by the `include` key, relative to the directory of the
+    /// file, are loaded and merged below its own values, so they take
+    /// precedence over the files it includes. Of the included files, later
+    /// ones take precedence over earlier ones.
+    fn load_file_with_includes(
+        &self,
+        path: &Path,
+        stack: &mut Vec<PathBuf>,
+    ) -> CargoResult<ConfigValue> {
+        // The same file may be reached through different paths, such as
+        // through `..`, so they're compared once resolved.
+        let canonical = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        if stack.contains(&canonical) {
+            let chain = stack
+                .iter()
+                .chain(Some(&canonical))
+                .map(|p| p.display().to_string())
+                .collect::<Vec<_>>();
+            bail!(
+                "config `include` cycle detected:\n  {}",
+                chain.join("\n  includes ")
+            );
+        }
+
+        let mut contents = String::new();
+        File::open(&path)
+            .and_then(|mut file| file.read_to_string(&mut contents))
+            .chain_err(|| format!("failed to read configuration file `{}`", path.display()))?;
+        let toml = cargo_toml::parse(&contents, path, self).chain_err(|| {
+            format!("could not parse TOML configuration in `{}`", path.display())
+        })?;
+        let mut value = CV::from_toml(&Definition::Path(path.to_path_buf()), toml).chain_err(|| {
+            format!(
+                "failed to load TOML configuration from `{}`",
+                path.display()
+            )
+        })?;
+
+        let includes = match value {
+            CV::Table(ref mut table, _) => table.remove("include"),
+            _ => None,
+        };
+        let includes = match includes {
+            Some(CV::String(s, def)) => vec![(s, def)],
+            Some(CV::List(list, _)) => list,
+            Some(cv) => bail!(
+                "expected a string or list of strings for `include` in `{}`, but found a {}",
+                path.display(),
+                cv.desc()
+            ),
+            None => return Ok(value),
+        };
+
+        stack.push(canonical);
+        for (include, _) in includes.into_iter().rev() {
+            let include_path = path.parent().unwrap().join(&include);
+            let included = self.load_file_with_includes(&include_path, stack)
+                .chain_err(|| {
+                    format!(
+                        "failed to load config include `{}` from `{}`",
+                        include,
+                        path.display()
+                    )
+                })?;
+            value.merge(included).chain_err(|| {
+                format!(
+                    "failed to merge config include `{}` into `{}`",
+                    include,
+                    path.display()
+                )
+            })?;
+        }
+        stack.pop();
+        Ok(value)
+    }
+
+    /// Merges the values given with `--config` into `cfg`, overriding the
+    /// values from the config files. Later arguments override earlier ones.
+    fn load_cli(&self, cfg: &mut ConfigValue) -> CargoResult<()> {
+        if self.cli_config.is_empty() {
+            return Ok(());
+        }
+        let mut cli = CV::Table(HashMap::new(), Definition::Cli(None));
+        for arg in self.cli_config.iter().rev() {
+            let value = self.load_cli_arg(arg)
+                .chain_err(|| format!("failed to load --config argument `{}`", arg))?;
+            cli.merge(value)
+                .chain_err(|| format!("failed to merge --config argument `{}`", arg))?;
+        }
+
+        // we want cli to override cfg, so swap these
+        mem::swap(cfg, &mut cli);
+        cfg.merge(cli)?;
+        Ok(())
+    }
+
+    fn load_cli_arg(&self, arg: &str) -> CargoResult<ConfigValue> {
+        let path = self.cwd.join(arg);
+        if path.is_file() {
+            return Ok(self.load_file(&path)?.into_cli());
+        }
+        let (key, value) = match arg.find('=') {
+            Some(i) => (&arg[..i], &arg[i + 1..]),
+            None => bail!(
+                "expected a `KEY=VALUE` pair or the path of a config file, \
+                 but no such file exists"
+            ),
+        };
+
+        // The value is parsed as the value of a key in a TOML document, which
+        // must then be the only thing in it.
+        let doc: toml::Value = format!("value = {}", value.trim())
+            .parse()
+            .chain_err(|| format!("could not parse `{}` as a TOML value", value.trim()))?;
+        let mut value = match doc {
+            toml::Value::Table(ref t) if t.len() == 1 => t["value"].clone(),
+            _ => bail!("expected a single TOML value, found `{}`", value.trim()),
+        };
+        for part in parse_cli_key(key)?.into_iter().rev() {
+            let mut table = toml::value::Table::new();
+            table.insert(part, value);
+            value = toml::Value::Table(table);
+        }
+        CV::from_toml(&Definition::Cli(None), value)
+    }
+
     /// Gets the index for a registry.
     pub fn get_registry_index(&self, registry: &str) -> CargoResult<Url> {
         Ok(
@@ -679,7 +920,8 @@ impl Config {
             )
         })?;
 
-        let mut value = CV::from_toml(&credentials, toml).chain_err(|| {
+        let definition = Definition::Path(credentials.clone());
+        let mut value = CV::from_toml(&definition, toml).chain_err(|| {
             format!(
                 "failed to load TOML configuration from `{}`",
                 credentials.display()
@@ -697,7 +939,7 @@ impl Config {
                 if let Vacant(entry) = value.entry("registry".into()) {
                     let mut map = HashMap::new();
                     map.insert("token".into(), token);
-                    let table = CV::Table(map, PathBuf::from("."));
+                    let table = CV::Table(map, Definition::Path(PathBuf::from(".")));
                     entry.insert(table);
                 }
             }
@@ -887,7 +1129,7 @@ impl ConfigError {
                 expected,
                 found.desc()
             ),
-            definition: Some(Definition::Path(found.definition_path().to_path_buf())),
+            definition: Some(found.definition().clone()),
         }
     }
 
@@ -983,7 +1225,12 @@ impl<'de, 'config> de::Deserializer<'de> for Deserializer<'config> {
         // Future note: If you ever need to deserialize a non-self describing
         // map type, this should implement a starts_with check (similar to how
         // ConfigMapAccess does).
-        if let Some(v) = self.config.env.get(&self.key.to_env()) {
+        let env = if self.config.is_cli_key(&self.key) {
+            None
+        } else {
+            self.config.env.get(&self.key.to_env())
+        };
+        if let Some(v) = env {
             let res: Result<V::Value, ConfigError> = if v == "true" || v == "false" {
                 visitor.visit_bool(v.parse().unwrap())
             } else if let Ok(v) = v.parse::<i64>() {
@@ -1006,22 +1253,21 @@ impl<'de, 'config> de::Deserializer<'de> for Deserializer<'config> {
 
         let o_cv = self.config.get_cv(&self.key.to_config())?;
         if let Some(cv) = o_cv {
-            let res: (Result<V::Value, ConfigError>, PathBuf) = match cv {
-                CV::Integer(i, path) => (visitor.visit_i64(i), path),
-                CV::String(s, path) => (visitor.visit_string(s), path),
-                CV::List(_, path) => (
+            let res: (Result<V::Value, ConfigError>, Definition) = match cv {
+                CV::Integer(i, def) => (visitor.visit_i64(i), def),
+                CV::String(s, def) => (visitor.visit_string(s), def),
+                CV::List(_, def) => (
                     visitor.visit_seq(ConfigSeqAccess::new(self.config, &self.key)?),
-                    path,
+                    def,
                 ),
-                CV::Table(_, path) => (
+                CV::Table(_, def) => (
                     visitor.visit_map(ConfigMapAccess::new_map(self.config, self.key.clone())?),
-                    path,
+                    def,
                 ),
-                CV::Boolean(b, path) => (visitor.visit_bool(b), path),
+                CV::Boolean(b, def) => (visitor.visit_bool(b), def),
             };
-            let (res, path) = res;
-            return res
-                .map_err(|e| e.with_key_context(&self.key.to_config(), Definition::Path(path)));
+            let (res, def) = res;
+            return res.map_err(|e| e.with_key_context(&self.key.to_config(), def));
         }
         Err(ConfigError::missing(&self.key.to_config()))
     }
@@ -1185,7 +1431,7 @@ impl<'config> ConfigMapAccess<'config> {
                     config.shell().warn(format!(
                         "unused key `{}` in config file `{}`",
                         key.join(part).to_config(),
-                        value.definition_path().display()
+                        value.definition()
                     ))?;
                 }
             }
@@ -1237,9 +1483,7 @@ impl ConfigSeqAccess {
     fn new(config: &Config, key: &ConfigKey) -> Result<ConfigSeqAccess, ConfigError> {
         let mut res = Vec::new();
         if let Some(v) = config.get_list(&key.to_config())? {
-            for (s, path) in v.val {
-                res.push((s, Definition::Path(path)));
-            }
+            res.extend(v.val);
         }
 
         if config.cli_unstable().advanced_env {
@@ -1312,11 +1556,11 @@ impl ConfigRelativePath {
 
 #[derive(Eq, PartialEq, Clone)]
 pub enum ConfigValue {
-    Integer(i64, PathBuf),
-    String(String, PathBuf),
-    List(Vec<(String, PathBuf)>, PathBuf),
-    Table(HashMap<String, ConfigValue>, PathBuf),
-    Boolean(bool, PathBuf),
+    Integer(i64, Definition),
+    String(String, Definition),
+    List(Vec<(String, Definition)>, Definition),
+    Table(HashMap<String, ConfigValue>, Definition),
+    Boolean(bool, Definition),
 }
 
 pub struct Value<T> {
@@ -1326,27 +1570,30 @@ pub struct Value<T> {
 
 pub type OptValue<T> = Option<Value<T>>;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Definition {
     Path(PathBuf),
     Environment(String),
+    /// Set with the `--config` command-line flag, from the given file if
+    /// it named one.
+    Cli(Option<PathBuf>),
 }
 
 impl fmt::Debug for ConfigValue {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
-            CV::Integer(i, ref path) => write!(f, "{} (from {})", i, path.display()),
-            CV::Boolean(b, ref path) => write!(f, "{} (from {})", b, path.display()),
-            CV::String(ref s, ref path) => write!(f, "{} (from {})", s, path.display()),
-            CV::List(ref list, ref path) => {
+            CV::Integer(i, ref def) => write!(f, "{} (from {})", i, def),
+            CV::Boolean(b, ref def) => write!(f, "{} (from {})", b, def),
+            CV::String(ref s, ref def) => write!(f, "{} (from {})", s, def),
+            CV::List(ref list, ref def) => {
                 write!(f, "[")?;
-                for (i, &(ref s, ref path)) in list.iter().enumerate() {
+                for (i, &(ref s, ref def)) in list.iter().enumerate() {
                     if i > 0 {
                         write!(f, ", ")?;
                     }
-                    write!(f, "{} (from {})", s, path.display())?;
+                    write!(f, "{} (from {})", s, def)?;
                 }
-                write!(f, "] (from {})", path.display())
+                write!(f, "] (from {})", def)
             }
             CV::Table(ref table, _) => write!(f, "{:?}", table),
         }
@@ -1354,29 +1601,29 @@ impl fmt::Debug for ConfigValue {
 }
 
 impl ConfigValue {
-    fn from_toml(path: &Path, toml: toml::Value) -> CargoResult<ConfigValue> {
+    fn from_toml(def: &Definition, toml: toml::Value) -> CargoResult<ConfigValue> {
         match toml {
-            toml::Value::String(val) => Ok(CV::String(val, path.to_path_buf())),
-            toml::Value::Boolean(b) => Ok(CV::Boolean(b, path.to_path_buf())),
-            toml::Value::Integer(i) => Ok(CV::Integer(i, path.to_path_buf())),
+            toml::Value::String(val) => Ok(CV::String(val, def.clone())),
+            toml::Value::Boolean(b) => Ok(CV::Boolean(b, def.clone())),
+            toml::Value::Integer(i) => Ok(CV::Integer(i, def.clone())),
             toml::Value::Array(val) => Ok(CV::List(
                 val.into_iter()
                     .map(|toml| match toml {
-                        toml::Value::String(val) => Ok((val, path.to_path_buf())),
+                        toml::Value::String(val) => Ok((val, def.clone())),
                         v => bail!("expected string but found {} in list", v.type_str()),
                     })
                     .collect::<CargoResult<_>>()?,
-                path.to_path_buf(),
+                def.clone(),
             )),
             toml::Value::Table(val) => Ok(CV::Table(
                 val.into_iter()
                     .map(|(key, value)| {
-                        let value = CV::from_toml(path, value)
+                        let value = CV::from_toml(def, value)
                             .chain_err(|| format!("failed to parse key `{}`", key))?;
                         Ok((key, value))
                     })
                     .collect::<CargoResult<_>>()?,
-                path.to_path_buf(),
+                def.clone(),
             )),
             v => bail!(
                 "found TOML configuration value of unknown type `{}`",
@@ -1410,7 +1657,7 @@ impl ConfigValue {
                 for (key, value) in new {
                     match old.entry(key.clone()) {
                         Occupied(mut entry) => {
-                            let path = value.definition_path().to_path_buf();
+                            let def = value.definition().clone();
                             let entry = entry.get_mut();
                             entry.merge(value).chain_err(|| {
                                 format!(
@@ -1419,8 +1666,8 @@ impl ConfigValue {
                                      file 1: {}\n  \
                                      file 2: {}",
                                     key,
-                                    entry.definition_path().display(),
-                                    path.display()
+                                    entry.definition(),
+                                    def
                                 )
                             })?;
                         }
@@ -1447,35 +1694,35 @@ impl ConfigValue {
         Ok(())
     }
 
-    pub fn i64(&self, key: &str) -> CargoResult<(i64, &Path)> {
+    pub fn i64(&self, key: &str) -> CargoResult<(i64, &Definition)> {
         match *self {
             CV::Integer(i, ref p) => Ok((i, p)),
             _ => self.expected("integer", key),
         }
     }
 
-    pub fn string(&self, key: &str) -> CargoResult<(&str, &Path)> {
+    pub fn string(&self, key: &str) -> CargoResult<(&str, &Definition)> {
         match *self {
             CV::String(ref s, ref p) => Ok((s, p)),
             _ => self.expected("string", key),
         }
     }
 
-    pub fn table(&self, key: &str) -> CargoResult<(&HashMap<String, ConfigValue>, &Path)> {
+    pub fn table(&self, key: &str) -> CargoResult<(&HashMap<String, ConfigValue>, &Definition)> {
         match *self {
             CV::Table(ref table, ref p) => Ok((table, p)),
             _ => self.expected("table", key),
         }
     }
 
-    pub fn list(&self, key: &str) -> CargoResult<&[(String, PathBuf)]> {
+    pub fn list(&self, key: &str) -> CargoResult<&[(String, Definition)]> {
         match *self {
             CV::List(ref list, _) => Ok(list),
             _ => self.expected("list", key),
         }
     }
 
-    pub fn boolean(&self, key: &str) -> CargoResult<(bool, &Path)> {
+    pub fn boolean(&self, key: &str) -> CargoResult<(bool, &Definition)> {
         match *self {
             CV::Boolean(b, ref p) => Ok((b, p)),
             _ => self.expected("bool", key),
@@ -1492,13 +1739,37 @@ impl ConfigValue {
         }
     }
 
-    pub fn definition_path(&self) -> &Path {
+    /// Marks a value loaded from a file given with `--config` as set on the
+    /// command line, so that it takes precedence over environment variables.
+    fn into_cli(self) -> ConfigValue {
+        fn cli(def: Definition) -> Definition {
+            match def {
+                Definition::Path(p) => Definition::Cli(Some(p)),
+                def => def,
+            }
+        }
+        match self {
+            CV::Boolean(b, def) => CV::Boolean(b, cli(def)),
+            CV::Integer(i, def) => CV::Integer(i, cli(def)),
+            CV::String(s, def) => CV::String(s, cli(def)),
+            CV::List(list, def) => CV::List(
+                list.into_iter().map(|(s, def)| (s, cli(def))).collect(),
+                cli(def),
+            ),
+            CV::Table(table, def) => CV::Table(
+                table.into_iter().map(|(k, v)| (k, v.into_cli())).collect(),
+                cli(def),
+            ),
+        }
+    }
+
+    pub fn definition(&self) -> &Definition {
         match *self {
-            CV::Boolean(_, ref p)
-            | CV::Integer(_, ref p)
-            | CV::String(_, ref p)
-            | CV::List(_, ref p)
-            | CV::Table(_, ref p) => p,
+            CV::Boolean(_, ref def)
+            | CV::Integer(_, ref def)
+            | CV::String(_, ref def)
+            | CV::List(_, ref def)
+            | CV::Table(_, ref def) => def,
         }
     }
 
@@ -1508,7 +1779,7 @@ impl ConfigValue {
             wanted,
             self.desc(),
             key,
-            self.definition_path().display()
+            self.definition()
         )
     }
 }
@@ -1516,8 +1787,10 @@ impl ConfigValue {
 impl Definition {
     pub fn root<'a>(&'a self, config: &'a Config) -> &'a Path {
         match *self {
-            Definition::Path(ref p) => p.parent().unwrap().parent().unwrap(),
-            Definition::Environment(_) => config.cwd(),
+            Definition::Path(ref p) | Definition::Cli(Some(ref p)) => {
+                p.parent().unwrap().parent().unwrap()
+            }
+            Definition::Environment(_) | Definition::Cli(None) => config.cwd(),
         }
     }
 }
@@ -1525,12 +1798,38 @@ impl Definition {
 impl fmt::Display for Definition {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
-            Definition::Path(ref p) => p.display().fmt(f),
+            Definition::Path(ref p) | Definition::Cli(Some(ref p)) => p.display().fmt(f),
             Definition::Environment(ref key) => write!(f, "environment variable `{}`", key),
+            Definition::Cli(None) => write!(f, "--config cli option"),
         }
     }
 }
 
+/// Splits the key of a `--config KEY=VALUE` argument into its parts, which
+/// may be quoted, as in `target.'cfg(unix)'.runner`.
+fn parse_cli_key(key: &str) -> CargoResult<Vec<String>> {
+    let mut parts = Vec::new();
+    let mut part = String::new();
+    let mut quote = None;
+    for c in key.trim().chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => part.push(c),
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '.' => parts.push(mem::replace(&mut part, String::new())),
+            None => part.push(c),
+        }
+    }
+    if quote.is_some() {
+        bail!("unterminated quote in key `{}`", key.trim());
+    }
+    parts.push(part);
+    if parts.iter().any(|p| p.is_empty()) {
+        bail!("invalid config key `{}`", key.trim());
+    }
+    Ok(parts)
+}
+
 pub fn homedir(cwd: &Path) -> Option<PathBuf> {
     ::home::cargo_home_with_cwd(cwd).ok()
 }
@@ -1575,17 +1874,18 @@ pub fn save_credentials(cfg: &Config, token: String, registry: Option<String>) -
 
     let (key, value) = {
         let key = "token".to_string();
-        let value = ConfigValue::String(token, file.path().to_path_buf());
+        let def = Definition::Path(file.path().to_path_buf());
+        let value = ConfigValue::String(token, def.clone());
         let mut map = HashMap::new();
         map.insert(key, value);
-        let table = CV::Table(map, file.path().to_path_buf());
+        let table = CV::Table(map, def.clone());
 
         if let Some(registry) = registry {
             let mut map = HashMap::new();
             map.insert(registry, table);
             (
                 "registries".into(),
-                CV::Table(map, file.path().to_path_buf()),
+                CV::Table(map, def),
             )
         } else {
             ("registry".into(), table)
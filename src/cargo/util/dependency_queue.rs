@@ -3,6 +3,10 @@
 //!
 //! This structure is used to store the dependency graph and dynamically update
 //! it to figure out when a dependency should be built.
+//!
+//! Each edge in the graph is labeled with which part of the dependency is
+//! needed, so that a package can start once, say, just the metadata of its
+//! dependencies is ready rather than waiting for them to finish completely.
 
 use std::collections::hash_map::Entry::{Occupied, Vacant};
 use std::collections::{HashMap, HashSet};
@@ -11,20 +15,20 @@ use std::hash::Hash;
 pub use self::Freshness::{Dirty, Fresh};
 
 #[derive(Debug)]
-pub struct DependencyQueue<K: Eq + Hash, V> {
+pub struct DependencyQueue<K: Eq + Hash, E: Eq + Hash, V> {
     /// A list of all known keys to build.
     ///
-    /// The value of the hash map is list of dependencies which still need to be
-    /// built before the package can be built. Note that the set is dynamically
-    /// updated as more dependencies are built.
-    dep_map: HashMap<K, (HashSet<K>, V)>,
+    /// The value of the hash map is list of dependencies, along with the edge
+    /// to each, which still need to be built before the package can be built.
+    /// Note that the set is dynamically updated as more dependencies are
+    /// built.
+    dep_map: HashMap<K, (HashSet<(K, E)>, V)>,
 
     /// A reverse mapping of a package to all packages that depend on that
-    /// package.
+    /// package, grouped by the edge they depend on it through.
     ///
-    /// This map is statically known and does not get updated throughout the
-    /// lifecycle of the DependencyQueue.
-    reverse_dep_map: HashMap<K, HashSet<K>>,
+    /// Each group of edges is removed once it's been finished.
+    reverse_dep_map: HashMap<K, HashMap<E, HashSet<K>>>,
 
     /// A set of dirty packages.
     ///
@@ -58,15 +62,15 @@ impl Freshness {
     }
 }
 
-impl<K: Hash + Eq + Clone, V> Default for DependencyQueue<K, V> {
-    fn default() -> DependencyQueue<K, V> {
+impl<K: Hash + Eq + Clone, E: Hash + Eq + Clone, V> Default for DependencyQueue<K, E, V> {
+    fn default() -> DependencyQueue<K, E, V> {
         DependencyQueue::new()
     }
 }
 
-impl<K: Hash + Eq + Clone, V> DependencyQueue<K, V> {
+impl<K: Hash + Eq + Clone, E: Hash + Eq + Clone, V> DependencyQueue<K, E, V> {
     /// Creates a new dependency queue with 0 packages.
-    pub fn new() -> DependencyQueue<K, V> {
+    pub fn new() -> DependencyQueue<K, E, V> {
         DependencyQueue {
             dep_map: HashMap::new(),
             reverse_dep_map: HashMap::new(),
@@ -80,7 +84,13 @@ impl<K: Hash + Eq + Clone, V> DependencyQueue<K, V> {
     ///
     /// It is assumed that any dependencies of this package will eventually also
     /// be added to the dependency queue.
-    pub fn queue(&mut self, fresh: Freshness, key: &K, value: V, dependencies: &[K]) -> &mut V {
+    pub fn queue(
+        &mut self,
+        fresh: Freshness,
+        key: &K,
+        value: V,
+        dependencies: &[(K, E)],
+    ) -> &mut V {
         let slot = match self.dep_map.entry(key.clone()) {
             Occupied(v) => return &mut v.into_mut().1,
             Vacant(v) => v,
@@ -91,12 +101,14 @@ impl<K: Hash + Eq + Clone, V> DependencyQueue<K, V> {
         }
 
         let mut my_dependencies = HashSet::new();
-        for dep in dependencies {
-            my_dependencies.insert(dep.clone());
-            let rev = self.reverse_dep_map
+        for &(ref dep, ref edge) in dependencies {
+            my_dependencies.insert((dep.clone(), edge.clone()));
+            self.reverse_dep_map
                 .entry(dep.clone())
-                .or_insert_with(HashSet::new);
-            rev.insert(key.clone());
+                .or_insert_with(HashMap::new)
+                .entry(edge.clone())
+                .or_insert_with(HashSet::new)
+                .insert(key.clone());
         }
         &mut slot.insert((my_dependencies, value)).1
     }
@@ -108,9 +120,9 @@ impl<K: Hash + Eq + Clone, V> DependencyQueue<K, V> {
             depth(key, &self.reverse_dep_map, &mut self.depth);
         }
 
-        fn depth<K: Hash + Eq + Clone>(
+        fn depth<K: Hash + Eq + Clone, E: Hash + Eq>(
             key: &K,
-            map: &HashMap<K, HashSet<K>>,
+            map: &HashMap<K, HashMap<E, HashSet<K>>>,
             results: &mut HashMap<K, usize>,
         ) -> usize {
             const IN_PROGRESS: usize = !0;
@@ -125,6 +137,7 @@ impl<K: Hash + Eq + Clone, V> DependencyQueue<K, V> {
             let depth = 1
                 + map.get(&key)
                     .into_iter()
+                    .flat_map(|it| it.values())
                     .flat_map(|it| it)
                     .map(|dep| depth(dep, map, results))
                     .max()
@@ -184,17 +197,42 @@ impl<K: Hash + Eq + Clone, V> DependencyQueue<K, V> {
     ///
     /// This function will update the dependency queue with this information,
     /// possibly allowing the next invocation of `dequeue` to return a package.
+    /// Every edge to the package which hasn't already been finished with
+    /// `finish_edge` is finished now.
     pub fn finish(&mut self, key: &K, fresh: Freshness) {
         assert!(self.pending.remove(key));
-        let reverse_deps = match self.reverse_dep_map.get(key) {
+        let reverse_deps = match self.reverse_dep_map.remove(key) {
             Some(deps) => deps,
             None => return,
         };
-        for dep in reverse_deps.iter() {
+        for (edge, deps) in reverse_deps {
+            self.finish_deps(key, &edge, deps, fresh);
+        }
+    }
+
+    /// Indicate that the part of a package which `edge` stands for is ready,
+    /// while the package itself is still being built.
+    ///
+    /// Packages which only depend on it through `edge` may be returned by the
+    /// next invocation of `dequeue`.
+    pub fn finish_edge(&mut self, key: &K, edge: &E, fresh: Freshness) {
+        assert!(self.pending.contains(key));
+        let deps = match self.reverse_dep_map.get_mut(key) {
+            Some(deps) => deps.remove(edge),
+            None => None,
+        };
+        if let Some(deps) = deps {
+            self.finish_deps(key, edge, deps, fresh);
+        }
+    }
+
+    fn finish_deps(&mut self, key: &K, edge: &E, deps: HashSet<K>, fresh: Freshness) {
+        let finished = (key.clone(), edge.clone());
+        for dep in deps {
             if fresh == Dirty {
                 self.dirty.insert(dep.clone());
             }
-            assert!(self.dep_map.get_mut(dep).unwrap().0.remove(key));
+            assert!(self.dep_map.get_mut(&dep).unwrap().0.remove(&finished));
         }
     }
 }
@@ -208,10 +246,10 @@ mod test {
         let mut q = DependencyQueue::new();
 
         q.queue(Freshness::Fresh, &1, (), &[]);
-        q.queue(Freshness::Fresh, &2, (), &[1]);
+        q.queue(Freshness::Fresh, &2, (), &[(1, ())]);
         q.queue(Freshness::Fresh, &3, (), &[]);
-        q.queue(Freshness::Fresh, &4, (), &[2, 3]);
-        q.queue(Freshness::Fresh, &5, (), &[4, 3]);
+        q.queue(Freshness::Fresh, &4, (), &[(2, ()), (3, ())]);
+        q.queue(Freshness::Fresh, &5, (), &[(4, ()), (3, ())]);
         q.queue_finished();
 
         assert_eq!(q.dequeue(), Some((Freshness::Fresh, 1, ())));
@@ -228,4 +266,26 @@ mod test {
         q.finish(&4, Freshness::Fresh);
         assert_eq!(q.dequeue(), Some((Freshness::Fresh, 5, ())));
     }
+
+    #[test]
+    fn finish_edge() {
+        let mut q = DependencyQueue::new();
+
+        q.queue(Freshness::Fresh, &1, (), &[]);
+        q.queue(Freshness::Fresh, &2, (), &[(1, "metadata")]);
+        q.queue(Freshness::Fresh, &3, (), &[(1, "all")]);
+        q.queue_finished();
+
+        assert_eq!(q.dequeue(), Some((Freshness::Fresh, 1, ())));
+        assert_eq!(q.dequeue(), None);
+        q.finish_edge(&1, &"metadata", Freshness::Dirty);
+        assert_eq!(q.dequeue(), Some((Freshness::Dirty, 2, ())));
+        assert_eq!(q.dequeue(), None);
+        q.finish(&1, Freshness::Dirty);
+        assert_eq!(q.dequeue(), Some((Freshness::Dirty, 3, ())));
+        assert!(!q.is_empty());
+        q.finish(&2, Freshness::Fresh);
+        q.finish(&3, Freshness::Fresh);
+        assert!(q.is_empty());
+    }
 }
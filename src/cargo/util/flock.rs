@@ -168,8 +168,28 @@ impl Filesystem {
             path.as_ref(),
             OpenOptions::new().read(true).write(true).create(true),
             State::Exclusive,
-            config,
-            msg,
+            &|| blocking(config, msg),
+        )
+    }
+
+    /// Like `open_rw`, but calls `on_block` instead of printing a message to
+    /// a `Config` if the process must block waiting for the lock.
+    ///
+    /// This is intended for locks taken off the main thread, which doesn't
+    /// have access to a `Config`.
+    pub fn open_rw_with<P>(
+        &self,
+        path: P,
+        on_block: &Fn() -> CargoResult<()>,
+    ) -> CargoResult<FileLock>
+    where
+        P: AsRef<Path>,
+    {
+        self.open(
+            path.as_ref(),
+            OpenOptions::new().read(true).write(true).create(true),
+            State::Exclusive,
+            on_block,
         )
     }
 
@@ -190,8 +210,25 @@ impl Filesystem {
             path.as_ref(),
             OpenOptions::new().read(true),
             State::Shared,
-            config,
-            msg,
+            &|| blocking(config, msg),
+        )
+    }
+
+    /// Like `open_ro`, but calls `on_block` instead of printing a message to
+    /// a `Config` if the process must block waiting for the lock.
+    pub fn open_ro_with<P>(
+        &self,
+        path: P,
+        on_block: &Fn() -> CargoResult<()>,
+    ) -> CargoResult<FileLock>
+    where
+        P: AsRef<Path>,
+    {
+        self.open(
+            path.as_ref(),
+            OpenOptions::new().read(true),
+            State::Shared,
+            on_block,
         )
     }
 
@@ -200,8 +237,7 @@ impl Filesystem {
         path: &Path,
         opts: &OpenOptions,
         state: State,
-        config: &Config,
-        msg: &str,
+        on_block: &Fn() -> CargoResult<()>,
     ) -> CargoResult<FileLock> {
         let path = self.root.join(path);
 
@@ -220,12 +256,12 @@ impl Filesystem {
             .chain_err(|| format!("failed to open: {}", path.display()))?;
         match state {
             State::Exclusive => {
-                acquire(config, msg, &path, &|| f.try_lock_exclusive(), &|| {
+                acquire(on_block, &path, &|| f.try_lock_exclusive(), &|| {
                     f.lock_exclusive()
                 })?;
             }
             State::Shared => {
-                acquire(config, msg, &path, &|| f.try_lock_shared(), &|| {
+                acquire(on_block, &path, &|| f.try_lock_shared(), &|| {
                     f.lock_shared()
                 })?;
             }
@@ -251,6 +287,13 @@ impl PartialEq<Filesystem> for Path {
     }
 }
 
+/// Prints the status message for a lock acquired through `config` which we
+/// have to wait for.
+fn blocking(config: &Config, msg: &str) -> CargoResult<()> {
+    let msg = format!("waiting for file lock on {}", msg);
+    config.shell().status_with_color("Blocking", &msg, Cyan)
+}
+
 /// Acquires a lock on a file in a "nice" manner.
 ///
 /// Almost all long-running blocking actions in Cargo have a status message
@@ -260,15 +303,14 @@ impl PartialEq<Filesystem> for Path {
 ///
 /// This function will acquire the lock on a `path`, printing out a nice message
 /// to the console if we have to wait for it. It will first attempt to use `try`
-/// to acquire a lock on the crate, and in the case of contention it will emit a
-/// status message based on `msg` to `config`'s shell, and then use `block` to
-/// block waiting to acquire a lock.
+/// to acquire a lock on the crate, and in the case of contention it will call
+/// `on_block` to emit a status message, and then use `block` to block waiting
+/// to acquire a lock.
 ///
 /// Returns an error if the lock could not be acquired or if any error other
 /// than a contention error happens.
 fn acquire(
-    config: &Config,
-    msg: &str,
+    on_block: &Fn() -> CargoResult<()>,
     path: &Path,
     try: &Fn() -> io::Result<()>,
     block: &Fn() -> io::Result<()>,
@@ -314,8 +356,7 @@ fn acquire(
             }
         }
     }
-    let msg = format!("waiting for file lock on {}", msg);
-    config.shell().status_with_color("Blocking", &msg, Cyan)?;
+    on_block()?;
 
     block().chain_err(|| format!("failed to lock file: {}", path.display()))?;
     return Ok(());
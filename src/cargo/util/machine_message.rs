@@ -1,7 +1,10 @@
+use std::path::Path;
+
 use serde::ser;
 use serde_json::{self, Value};
 
-use core::{PackageId, Target};
+use core::compiler::CompileMode;
+use core::{PackageId, Target, TargetKind};
 
 pub trait Message: ser::Serialize {
     fn reason(&self) -> &str;
@@ -68,3 +71,171 @@ impl<'a> Message for BuildScript<'a> {
         "build-script-executed"
     }
 }
+
+#[derive(Serialize)]
+pub struct TimingInfo<'a> {
+    pub package_id: &'a PackageId,
+    pub target: &'a Target,
+    pub mode: CompileMode,
+    /// How long the unit took to build, in seconds.
+    pub duration: f64,
+}
+
+impl<'a> Message for TimingInfo<'a> {
+    fn reason(&self) -> &str {
+        "timing-info"
+    }
+}
+
+/// A package name which appears in the dependency graph with more than one
+/// version, as reported by `cargo duplicates`.
+#[derive(Serialize)]
+pub struct DuplicatePackage<'a> {
+    pub name: &'a str,
+    pub versions: Vec<DuplicateVersion<'a>>,
+}
+
+#[derive(Serialize)]
+pub struct DuplicateVersion<'a> {
+    pub package_id: &'a PackageId,
+    /// The packages which depend on this version.
+    pub dependents: Vec<DuplicateDependent<'a>>,
+}
+
+#[derive(Serialize)]
+pub struct DuplicateDependent<'a> {
+    /// The shortest path from a workspace member to the dependent, followed
+    /// by the duplicated package itself.
+    pub path: Vec<&'a PackageId>,
+    /// The version requirement the dependent has on the package.
+    pub requirement: String,
+    /// The other versions of the package which `requirement` doesn't accept,
+    /// keeping this dependent from sharing one of them.
+    pub excludes: Vec<&'a PackageId>,
+}
+
+impl<'a> Message for DuplicatePackage<'a> {
+    fn reason(&self) -> &str {
+        "duplicate-package"
+    }
+}
+
+/// A dependency with newer versions than the locked one, as reported by
+/// `cargo outdated`.
+#[derive(Serialize)]
+pub struct OutdatedPackage<'a> {
+    pub package_id: &'a PackageId,
+    /// Whether a workspace member depends on the package directly.
+    pub direct: bool,
+    pub locked: String,
+    /// The newest version which every package depending on this one accepts.
+    pub compatible: String,
+    /// The newest version overall.
+    pub latest: String,
+}
+
+impl<'a> Message for OutdatedPackage<'a> {
+    fn reason(&self) -> &str {
+        "outdated-package"
+    }
+}
+
+/// A package in the lock file which a security advisory applies to, as
+/// reported by `cargo advisories`.
+#[derive(Serialize)]
+pub struct AdvisoryMatch<'a> {
+    pub package_id: &'a PackageId,
+    /// The ID of the advisory, like `RUSTSEC-2018-0001`.
+    pub id: &'a str,
+    pub title: &'a str,
+    pub date: Option<&'a str>,
+    pub url: Option<&'a str>,
+    /// The kind of advisory, like `unmaintained`, if it isn't about a
+    /// vulnerability.
+    pub informational: Option<&'a str>,
+    /// Requirements matching the versions in which the problem is fixed.
+    pub patched_versions: &'a [String],
+    /// Requirements matching the versions which never had the problem.
+    pub unaffected_versions: &'a [String],
+    /// For each package depending on the affected one, the shortest path
+    /// from a workspace member to it, followed by the affected package.
+    pub paths: Vec<Vec<&'a PackageId>>,
+}
+
+impl<'a> Message for AdvisoryMatch<'a> {
+    fn reason(&self) -> &str {
+        "advisory"
+    }
+}
+
+/// A test binary run by `cargo test` or `cargo bench`, or rustdoc running the
+/// doc tests of a library.
+#[derive(Serialize)]
+pub struct TestBinary<'a> {
+    pub package_id: &'a PackageId,
+    pub target_kind: &'a TargetKind,
+    pub target_name: &'a str,
+    /// `None` for doc tests.
+    pub executable: Option<&'a Path>,
+}
+
+#[derive(Serialize)]
+pub struct TestBinaryStarted<'a> {
+    pub binary: &'a TestBinary<'a>,
+}
+
+impl<'a> Message for TestBinaryStarted<'a> {
+    fn reason(&self) -> &str {
+        "test-binary-started"
+    }
+}
+
+/// The result of a single test or benchmark, parsed from libtest's output.
+#[derive(Serialize)]
+pub struct TestResult<'a> {
+    pub binary: &'a TestBinary<'a>,
+    pub name: &'a str,
+    /// One of `ok`, `failed`, `ignored` or `bench`.
+    pub outcome: &'a str,
+    /// Only set for benchmarks.
+    pub bench: Option<BenchResult>,
+}
+
+#[derive(Serialize)]
+pub struct BenchResult {
+    pub ns_per_iter: u64,
+    pub deviation: u64,
+}
+
+impl<'a> Message for TestResult<'a> {
+    fn reason(&self) -> &str {
+        "test-result"
+    }
+}
+
+#[derive(Serialize)]
+pub struct TestBinaryFinished<'a> {
+    pub binary: &'a TestBinary<'a>,
+    pub success: bool,
+    /// `None` if the process was killed by a signal.
+    pub exit_code: Option<i32>,
+    /// How long the process ran, in seconds.
+    pub duration: f64,
+    /// The counts from libtest's `test result:` line, if it printed one.
+    pub summary: Option<TestSummary>,
+}
+
+#[derive(Serialize, Default)]
+pub struct TestSummary {
+    pub passed: u32,
+    pub failed: u32,
+    pub ignored: u32,
+    pub measured: u32,
+    pub filtered_out: u32,
+}
+
+impl<'a> Message for TestBinaryFinished<'a> {
+    fn reason(&self) -> &str {
+        "test-binary-finished"
+    }
+}
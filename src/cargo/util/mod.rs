@@ -33,6 +33,7 @@ pub mod network;
 pub mod paths;
 pub mod process_builder;
 pub mod profile;
+pub mod spdx;
 pub mod to_semver;
 pub mod to_url;
 pub mod toml;
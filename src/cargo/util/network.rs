@@ -31,6 +31,48 @@ fn maybe_spurious(err: &Error) -> bool {
     false
 }
 
+/// Tracks how many more times a network operation may be retried after a
+/// spurious failure, according to `net.retry`.
+///
+/// This is the state behind `with_retry`, and is used directly by callers
+/// which can't retry inside a single closure, such as downloads driven by a
+/// curl `Multi` handle where each transfer is retried independently.
+pub struct Retry<'a> {
+    config: &'a Config,
+    remaining: u32,
+}
+
+impl<'a> Retry<'a> {
+    pub fn new(config: &'a Config) -> CargoResult<Retry<'a>> {
+        Ok(Retry {
+            config,
+            remaining: config.get::<Option<u32>>("net.retry")?.unwrap_or(2),
+        })
+    }
+
+    /// Runs `f` once. Returns `Ok(None)` if it failed with a spurious error
+    /// and should be tried again, after warning about the failure.
+    pub fn attempt<T, F>(&mut self, f: F) -> CargoResult<Option<T>>
+    where
+        F: FnOnce() -> CargoResult<T>,
+    {
+        match f() {
+            Ok(ret) => Ok(Some(ret)),
+            Err(ref e) if maybe_spurious(e) && self.remaining > 0 => {
+                let msg = format!(
+                    "spurious network error ({} tries \
+                     remaining): {}",
+                    self.remaining, e
+                );
+                self.config.shell().warn(msg)?;
+                self.remaining -= 1;
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
 /// Wrapper method for network call retry logic.
 ///
 /// Retry counts provided by Config object `net.retry`. Config shell outputs
@@ -48,21 +90,10 @@ pub fn with_retry<T, F>(config: &Config, mut callback: F) -> CargoResult<T>
 where
     F: FnMut() -> CargoResult<T>,
 {
-    let mut remaining = config.get::<Option<u32>>("net.retry")?.unwrap_or(2);
+    let mut retry = Retry::new(config)?;
     loop {
-        match callback() {
-            Ok(ret) => return Ok(ret),
-            Err(ref e) if maybe_spurious(e) && remaining > 0 => {
-                let msg = format!(
-                    "spurious network error ({} tries \
-                     remaining): {}",
-                    remaining, e
-                );
-                config.shell().warn(msg)?;
-                remaining -= 1;
-            }
-            //todo impl from
-            Err(e) => return Err(e),
+        if let Some(ret) = retry.attempt(&mut callback)? {
+            return Ok(ret);
         }
     }
 }
@@ -73,6 +73,14 @@ impl<'cfg> Progress<'cfg> {
         }
     }
 
+    /// Like `tick`, but with `msg` shown after the progress bar.
+    pub fn tick_with_msg(&mut self, cur: usize, max: usize, msg: &str) -> CargoResult<()> {
+        match self.state {
+            Some(ref mut s) => s.tick(cur, max, msg, true),
+            None => Ok(()),
+        }
+    }
+
     pub fn clear(&mut self) {
         if let Some(ref mut s) = self.state {
             s.clear();
@@ -6,9 +6,10 @@ use std::collections::hash_map::{Entry, HashMap};
 use std::sync::Mutex;
 use std::env;
 
+use semver::Version;
 use serde_json;
 
-use util::{self, internal, profile, CargoResult, ProcessBuilder};
+use util::{self, internal, profile, CargoResult, ProcessBuilder, ToSemver};
 use util::paths;
 
 /// Information on the `rustc` executable
@@ -77,6 +78,17 @@ impl Rustc {
         }
     }
 
+    /// The version of this compiler, from the `release:` line of
+    /// `verbose_version`.
+    pub fn version(&self) -> CargoResult<Version> {
+        let release = self.verbose_version
+            .lines()
+            .find(|l| l.starts_with("release: "))
+            .map(|l| &l[9..])
+            .ok_or_else(|| internal("rustc -v didn't have a line for `release:`"))?;
+        release.to_semver()
+    }
+
     pub fn cached_output(&self, cmd: &ProcessBuilder) -> CargoResult<(String, String)> {
         self.cache.lock().unwrap().cached_output(cmd)
     }
@@ -0,0 +1,281 @@
+//! SPDX license expressions, as used by the `license` field of a manifest.
+//!
+//! See <https://spdx.org/spdx-specification-21-web-version#h.jxpfx0ykyb60>
+//! for the grammar. The older `MIT/Apache-2.0` form, which many crates still
+//! use, is accepted too, with `/` meaning `OR`.
+
+use std::fmt;
+use std::iter;
+use std::str::{self, FromStr};
+
+use util::{CargoError, CargoResult};
+
+/// A single license in an expression, e.g. `GPL-2.0+` or
+/// `Apache-2.0 WITH LLVM-exception`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct License {
+    /// The identifier of the license, as written.
+    pub id: String,
+    /// Whether later versions of the license may be used too, written with a
+    /// trailing `+`.
+    pub or_later: bool,
+    /// The exception to the license after `WITH`, if any.
+    pub exception: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LicenseExpr {
+    License(License),
+    /// All of the licenses must be complied with.
+    And(Vec<LicenseExpr>),
+    /// Any one of the licenses may be chosen.
+    Or(Vec<LicenseExpr>),
+}
+
+#[derive(PartialEq)]
+enum Token<'a> {
+    LeftParen,
+    RightParen,
+    Plus,
+    Ident(&'a str),
+}
+
+struct Tokenizer<'a> {
+    s: iter::Peekable<str::CharIndices<'a>>,
+    orig: &'a str,
+}
+
+struct Parser<'a> {
+    t: iter::Peekable<Tokenizer<'a>>,
+}
+
+impl License {
+    /// Whether this is the license named by the identifier `id`.
+    ///
+    /// Identifiers are compared case-insensitively, and the `-only` and
+    /// `-or-later` suffixes of the GNU licenses are ignored on either side,
+    /// so `GPL-3.0` matches `GPL-3.0+` and `GPL-3.0-only` as well.
+    pub fn is(&self, id: &str) -> bool {
+        base_id(&self.id).eq_ignore_ascii_case(base_id(id))
+    }
+}
+
+fn base_id(id: &str) -> &str {
+    let id = id.trim_right_matches('+');
+    for suffix in &["-only", "-or-later"] {
+        if id.len() > suffix.len() && id.to_lowercase().ends_with(suffix) {
+            return &id[..id.len() - suffix.len()];
+        }
+    }
+    id
+}
+
+impl LicenseExpr {
+    /// Whether the expression can be complied with while only using the
+    /// licenses for which `ok` returns true.
+    pub fn satisfiable(&self, ok: &Fn(&License) -> bool) -> bool {
+        match *self {
+            LicenseExpr::License(ref l) => ok(l),
+            LicenseExpr::And(ref e) => e.iter().all(|e| e.satisfiable(ok)),
+            LicenseExpr::Or(ref e) => e.iter().any(|e| e.satisfiable(ok)),
+        }
+    }
+
+    /// Every license mentioned by the expression, in order.
+    pub fn licenses(&self) -> Vec<&License> {
+        match *self {
+            LicenseExpr::License(ref l) => vec![l],
+            LicenseExpr::And(ref e) | LicenseExpr::Or(ref e) => {
+                e.iter().flat_map(|e| e.licenses()).collect()
+            }
+        }
+    }
+}
+
+impl FromStr for License {
+    type Err = CargoError;
+
+    fn from_str(s: &str) -> CargoResult<License> {
+        match s.parse()? {
+            LicenseExpr::License(l) => Ok(l),
+            _ => bail!("expected a single license, found the expression `{}`", s),
+        }
+    }
+}
+
+impl FromStr for LicenseExpr {
+    type Err = CargoError;
+
+    fn from_str(s: &str) -> CargoResult<LicenseExpr> {
+        let mut p = Parser {
+            t: Tokenizer {
+                s: s.char_indices().peekable(),
+                orig: s,
+            }.peekable(),
+        };
+        let e = p.or()?;
+        match p.t.next() {
+            None => Ok(e),
+            Some(Ok(t)) => bail!("unexpected {} in license expression", t.classify()),
+            Some(Err(e)) => Err(e),
+        }
+    }
+}
+
+impl fmt::Display for License {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.id)?;
+        if self.or_later {
+            write!(f, "+")?;
+        }
+        if let Some(ref exception) = self.exception {
+            write!(f, " WITH {}", exception)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for LicenseExpr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (op, e) = match *self {
+            LicenseExpr::License(ref l) => return l.fmt(f),
+            LicenseExpr::And(ref e) => (" AND ", e),
+            LicenseExpr::Or(ref e) => (" OR ", e),
+        };
+        for (i, e) in e.iter().enumerate() {
+            if i > 0 {
+                write!(f, "{}", op)?;
+            }
+            match *e {
+                LicenseExpr::License(..) => write!(f, "{}", e)?,
+                _ => write!(f, "({})", e)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Parser<'a> {
+    // `AND` binds more tightly than `OR`, and `WITH` more tightly than both.
+    fn or(&mut self) -> CargoResult<LicenseExpr> {
+        let mut e = vec![self.and()?];
+        while self.try_op("OR") {
+            e.push(self.and()?);
+        }
+        Ok(if e.len() == 1 { e.pop().unwrap() } else { LicenseExpr::Or(e) })
+    }
+
+    fn and(&mut self) -> CargoResult<LicenseExpr> {
+        let mut e = vec![self.primary()?];
+        while self.try_op("AND") {
+            e.push(self.primary()?);
+        }
+        Ok(if e.len() == 1 { e.pop().unwrap() } else { LicenseExpr::And(e) })
+    }
+
+    fn primary(&mut self) -> CargoResult<LicenseExpr> {
+        match self.t.next() {
+            Some(Ok(Token::LeftParen)) => {
+                let e = self.or()?;
+                match self.t.next() {
+                    Some(Ok(Token::RightParen)) => Ok(e),
+                    Some(Ok(t)) => bail!("expected `)`, found {}", t.classify()),
+                    Some(Err(e)) => Err(e),
+                    None => bail!("expected `)`, but the license expression ended"),
+                }
+            }
+            Some(Ok(Token::Ident(id))) if !is_operator(id) => {
+                let or_later = match self.t.peek() {
+                    Some(&Ok(Token::Plus)) => true,
+                    _ => false,
+                };
+                if or_later {
+                    self.t.next();
+                }
+                let exception = if self.try_op("WITH") {
+                    match self.t.next() {
+                        Some(Ok(Token::Ident(id))) if !is_operator(id) => Some(id.to_string()),
+                        Some(Ok(t)) => bail!("expected a license exception, found {}", t.classify()),
+                        Some(Err(e)) => return Err(e),
+                        None => bail!("expected a license exception, found nothing"),
+                    }
+                } else {
+                    None
+                };
+                Ok(LicenseExpr::License(License {
+                    id: id.to_string(),
+                    or_later,
+                    exception,
+                }))
+            }
+            Some(Ok(t)) => bail!("expected a license, found {}", t.classify()),
+            Some(Err(e)) => Err(e),
+            None => bail!("expected a license, found nothing"),
+        }
+    }
+
+    /// Consumes the operator `op` if it's next, in any case, or a `/` if `op`
+    /// is `OR`.
+    fn try_op(&mut self, op: &str) -> bool {
+        match self.t.peek() {
+            Some(&Ok(Token::Ident(id))) if id.eq_ignore_ascii_case(op) => {}
+            Some(&Ok(Token::Ident("/"))) if op == "OR" => {}
+            _ => return false,
+        }
+        self.t.next();
+        true
+    }
+}
+
+fn is_operator(id: &str) -> bool {
+    id == "/" || ["AND", "OR", "WITH"].iter().any(|op| id.eq_ignore_ascii_case(op))
+}
+
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = CargoResult<Token<'a>>;
+
+    fn next(&mut self) -> Option<CargoResult<Token<'a>>> {
+        loop {
+            match self.s.next() {
+                Some((_, ch)) if ch.is_whitespace() => {}
+                Some((_, '(')) => return Some(Ok(Token::LeftParen)),
+                Some((_, ')')) => return Some(Ok(Token::RightParen)),
+                Some((_, '+')) => return Some(Ok(Token::Plus)),
+                Some((start, '/')) => return Some(Ok(Token::Ident(&self.orig[start..start + 1]))),
+                Some((start, ch)) if is_ident(ch) => {
+                    while let Some(&(end, ch)) = self.s.peek() {
+                        if !is_ident(ch) {
+                            return Some(Ok(Token::Ident(&self.orig[start..end])));
+                        } else {
+                            self.s.next();
+                        }
+                    }
+                    return Some(Ok(Token::Ident(&self.orig[start..])));
+                }
+                Some((_, ch)) => {
+                    return Some(Err(format_err!(
+                        "unexpected character `{}` in license expression",
+                        ch
+                    )))
+                }
+                None => return None,
+            }
+        }
+    }
+}
+
+fn is_ident(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || ch == '-' || ch == '.' || ch == ':'
+}
+
+impl<'a> Token<'a> {
+    fn classify(&self) -> &str {
+        match *self {
+            Token::LeftParen => "`(`",
+            Token::RightParen => "`)`",
+            Token::Plus => "`+`",
+            Token::Ident("/") => "`/`",
+            Token::Ident(..) => "an identifier",
+        }
+    }
+}
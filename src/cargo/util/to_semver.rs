@@ -31,3 +31,36 @@ impl<'a> ToSemver for &'a Version {
         Ok(self.clone())
     }
 }
+
+/// Parses the `rust-version` of a package, which is a bare `major.minor` or
+/// `major.minor.patch` version of Rust, with a missing patch level read as 0.
+pub fn parse_rust_version(s: &str) -> CargoResult<Version> {
+    let parts = s.split('.').collect::<Vec<_>>();
+    let valid = (parts.len() == 2 || parts.len() == 3)
+        && parts
+            .iter()
+            .all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit()));
+    if !valid {
+        bail!(
+            "`{}` is not a valid Rust version, expected a version like \
+             `1.30` or `1.30.1`",
+            s
+        )
+    }
+    let full = if parts.len() == 2 {
+        format!("{}.0", s)
+    } else {
+        s.to_string()
+    };
+    full.to_semver()
+}
+
+/// Whether a compiler of version `rustc` can build a package whose
+/// `rust-version` is `rust_version`.
+///
+/// Pre-release compilers, e.g. nightlies, count as the release they lead up
+/// to.
+pub fn rust_version_satisfied(rust_version: &Version, rustc: &Version) -> bool {
+    (rustc.major, rustc.minor, rustc.patch)
+        >= (rust_version.major, rust_version.minor, rust_version.patch)
+}
@@ -1,12 +1,14 @@
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fmt;
 use std::fs;
+use std::marker::PhantomData;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::str;
 
+use lazycell::LazyCell;
 use semver::{self, VersionReq};
-use serde::de::{self, Deserialize};
+use serde::de::{self, Deserialize, IntoDeserializer};
 use serde::ser;
 use serde_ignored;
 use toml;
@@ -15,12 +17,14 @@ use url::Url;
 use core::dependency::{Kind, Platform};
 use core::manifest::{LibKind, ManifestMetadata, Warnings};
 use core::profiles::Profiles;
+use core::resolver::ResolveBehavior;
 use core::{Dependency, Manifest, PackageId, Summary, Target};
 use core::{Edition, EitherManifest, Feature, Features, VirtualManifest};
 use core::{GitReference, PackageIdSpec, SourceId, WorkspaceConfig, WorkspaceRootConfig};
 use sources::CRATES_IO;
 use util::errors::{CargoError, CargoResult, CargoResultExt};
 use util::paths;
+use util::to_semver::parse_rust_version;
 use util::{self, Config, ToUrl};
 
 mod targets;
@@ -58,11 +62,18 @@ fn do_read_manifest(
     };
 
     let mut unused = BTreeSet::new();
-    let manifest: TomlManifest = serde_ignored::deserialize(toml, |path| {
+    let mut manifest: TomlManifest = serde_ignored::deserialize(toml, |path| {
         let mut key = String::new();
         stringify(&mut key, &path);
         unused.insert(key);
     })?;
+    // `[profile.debug]` can't be a custom profile, and is most likely meant
+    // to be `[profile.dev]`.
+    if let Some(ref mut profiles) = manifest.profile {
+        if profiles.custom.remove("debug").is_some() {
+            unused.insert("profile.debug".to_string());
+        }
+    }
     let add_unused = |warnings: &mut Warnings| {
         for key in unused {
             warnings.add_warning(format!("unused manifest key: {}", key));
@@ -143,16 +154,44 @@ in the future.",
     }
 
     let first_error = CargoError::from(first_error);
+    if let Some(key) = dotted_workspace_key(toml) {
+        let msg = format!(
+            "could not parse input as TOML\n\n\
+             dotted keys are not supported, `{0}.workspace = true` must be \
+             written as `{0} = {{ workspace = true }}`",
+            key
+        );
+        return Err(first_error.context(msg).into());
+    }
     Err(first_error.context("could not parse input as TOML").into())
 }
 
+/// Finds a key inherited with a dotted key, like `version.workspace = true`,
+/// which the TOML parser doesn't support.
+fn dotted_workspace_key(toml: &str) -> Option<&str> {
+    for line in toml.lines() {
+        let field = match line.find('=') {
+            Some(i) => line[..i].trim(),
+            None => continue,
+        };
+        if !field.ends_with(".workspace") {
+            continue;
+        }
+        let key = &field[..field.len() - ".workspace".len()];
+        if !key.is_empty() && key.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_') {
+            return Some(key);
+        }
+    }
+    None
+}
+
 type TomlLibTarget = TomlTarget;
 type TomlBinTarget = TomlTarget;
 type TomlExampleTarget = TomlTarget;
 type TomlTestTarget = TomlTarget;
 type TomlBenchTarget = TomlTarget;
 
-#[derive(Debug, Serialize)]
+#[derive(Clone, Debug, Serialize)]
 #[serde(untagged)]
 pub enum TomlDependency {
     Simple(String),
@@ -213,9 +252,10 @@ pub struct DetailedTomlDependency {
     #[serde(rename = "default_features")]
     default_features2: Option<bool>,
     package: Option<String>,
+    workspace: Option<bool>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct TomlManifest {
     cargo_features: Option<Vec<String>>,
@@ -242,13 +282,57 @@ pub struct TomlManifest {
     badges: Option<BTreeMap<String, BTreeMap<String, String>>>,
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct TomlProfiles {
     pub test: Option<TomlProfile>,
     pub doc: Option<TomlProfile>,
     pub bench: Option<TomlProfile>,
     pub dev: Option<TomlProfile>,
     pub release: Option<TomlProfile>,
+    /// Custom profiles, which `inherits` from another profile, by name.
+    pub custom: BTreeMap<String, TomlProfile>,
+}
+
+impl<'de> de::Deserialize<'de> for TomlProfiles {
+    fn deserialize<D>(d: D) -> Result<TomlProfiles, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let mut custom = BTreeMap::<String, TomlProfile>::deserialize(d)?;
+        Ok(TomlProfiles {
+            test: custom.remove("test"),
+            doc: custom.remove("doc"),
+            bench: custom.remove("bench"),
+            dev: custom.remove("dev"),
+            release: custom.remove("release"),
+            custom,
+        })
+    }
+}
+
+impl ser::Serialize for TomlProfiles {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        let mut profiles = self.custom
+            .iter()
+            .map(|(name, profile)| (name.as_str(), profile))
+            .collect::<BTreeMap<_, _>>();
+        let builtin = [
+            ("test", &self.test),
+            ("doc", &self.doc),
+            ("bench", &self.bench),
+            ("dev", &self.dev),
+            ("release", &self.release),
+        ];
+        for &(name, profile) in builtin.iter() {
+            if let Some(ref profile) = *profile {
+                profiles.insert(name, profile);
+            }
+        }
+        profiles.serialize(s)
+    }
 }
 
 impl TomlProfiles {
@@ -268,6 +352,9 @@ impl TomlProfiles {
         if let Some(ref release) = self.release {
             release.validate("release", features, warnings)?;
         }
+        for (name, profile) in self.custom.iter() {
+            profile.validate(name, features, warnings)?;
+        }
         Ok(())
     }
 }
@@ -389,6 +476,7 @@ pub struct TomlProfile {
     pub incremental: Option<bool>,
     pub overrides: Option<BTreeMap<ProfilePackageSpec, TomlProfile>>,
     pub build_override: Option<Box<TomlProfile>>,
+    pub inherits: Option<String>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Ord, PartialOrd, Hash)]
@@ -444,16 +532,28 @@ impl TomlProfile {
         }
 
         match name {
-            "dev" | "release" => {}
-            _ => {
+            "dev" | "release" | "test" | "bench" | "doc" => {
+                if self.inherits.is_some() {
+                    bail!(
+                        "`inherits` may only be specified for a custom profile, not `{}`.",
+                        name
+                    );
+                }
+            }
+            _ => validate_custom_profile_name(name)?,
+        }
+
+        match name {
+            "test" | "bench" | "doc" => {
                 if self.overrides.is_some() || self.build_override.is_some() {
                     bail!(
                         "Profile overrides may only be specified for \
-                         `dev` or `release` profile, not `{}`.",
+                         `dev`, `release` or custom profiles, not `{}`.",
                         name
                     );
                 }
             }
+            _ => {}
         }
 
         match name {
@@ -483,10 +583,35 @@ impl TomlProfile {
         if self.rpath.is_some() {
             bail!("`rpath` may not be specified in a profile override.")
         }
+        if self.inherits.is_some() {
+            bail!("`inherits` may not be specified in a profile override.")
+        }
         Ok(())
     }
 }
 
+/// Checks the name of a custom profile, which is also the name of its
+/// directory in `target`.
+fn validate_custom_profile_name(name: &str) -> CargoResult<()> {
+    if name.is_empty()
+        || !name.chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        bail!(
+            "invalid profile name `{}`, profile names may only contain \
+             letters, numbers, `-` and `_`",
+            name
+        );
+    }
+    match name {
+        "debug" | "package" => bail!(
+            "profile name `{}` is reserved, as it's a directory Cargo uses in `target`",
+            name
+        ),
+        _ => Ok(()),
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Eq, PartialEq)]
 #[serde(untagged)]
 pub enum StringOrBool {
@@ -571,13 +696,13 @@ impl<'de> de::Deserialize<'de> for VecStringOrBool {
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct TomlProject {
     name: String,
-    version: semver::Version,
-    authors: Option<Vec<String>>,
+    version: MaybeWorkspace<semver::Version>,
+    authors: Option<MaybeWorkspace<Vec<String>>>,
     build: Option<StringOrBool>,
     links: Option<String>,
-    exclude: Option<Vec<String>>,
-    include: Option<Vec<String>>,
-    publish: Option<VecStringOrBool>,
+    exclude: Option<MaybeWorkspace<Vec<String>>>,
+    include: Option<MaybeWorkspace<Vec<String>>>,
+    publish: Option<MaybeWorkspace<VecStringOrBool>>,
     #[serde(rename = "publish-lockfile")]
     publish_lockfile: Option<bool>,
     workspace: Option<String>,
@@ -591,8 +716,43 @@ pub struct TomlProject {
     namespaced_features: Option<bool>,
     #[serde(rename = "default-run")]
     default_run: Option<String>,
+    resolver: Option<String>,
+    #[serde(rename = "rust-version")]
+    rust_version: Option<MaybeWorkspace<String>>,
 
     // package metadata
+    description: Option<MaybeWorkspace<String>>,
+    homepage: Option<MaybeWorkspace<String>>,
+    documentation: Option<MaybeWorkspace<String>>,
+    readme: Option<MaybeWorkspace<String>>,
+    keywords: Option<MaybeWorkspace<Vec<String>>>,
+    categories: Option<MaybeWorkspace<Vec<String>>>,
+    license: Option<MaybeWorkspace<String>>,
+    #[serde(rename = "license-file")]
+    license_file: Option<MaybeWorkspace<String>>,
+    repository: Option<MaybeWorkspace<String>>,
+    metadata: Option<toml::Value>,
+    edition: Option<MaybeWorkspace<String>>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TomlWorkspace {
+    members: Option<Vec<String>>,
+    #[serde(rename = "default-members")]
+    default_members: Option<Vec<String>>,
+    exclude: Option<Vec<String>>,
+    package: Option<InheritableFields>,
+    dependencies: Option<BTreeMap<String, TomlDependency>>,
+    resolver: Option<String>,
+}
+
+/// The `[workspace.package]` table, holding the keys which members can
+/// inherit with `key.workspace = true`.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct InheritableFields {
+    version: Option<semver::Version>,
+    authors: Option<Vec<String>>,
     description: Option<String>,
     homepage: Option<String>,
     documentation: Option<String>,
@@ -600,24 +760,317 @@ pub struct TomlProject {
     keywords: Option<Vec<String>>,
     categories: Option<Vec<String>>,
     license: Option<String>,
-    #[serde(rename = "license-file")]
     license_file: Option<String>,
     repository: Option<String>,
-    metadata: Option<toml::Value>,
     edition: Option<String>,
+    rust_version: Option<String>,
+    publish: Option<VecStringOrBool>,
+    exclude: Option<Vec<String>>,
+    include: Option<Vec<String>>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
-pub struct TomlWorkspace {
-    members: Option<Vec<String>>,
-    #[serde(rename = "default-members")]
-    default_members: Option<Vec<String>>,
-    exclude: Option<Vec<String>>,
+/// A `[package]` value which is either given directly, or inherited from
+/// `[workspace.package]` by writing `key.workspace = true`.
+#[derive(Clone, Debug, Serialize)]
+#[serde(untagged)]
+pub enum MaybeWorkspace<T> {
+    Defined(T),
+    Workspace(TomlWorkspaceField),
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TomlWorkspaceField {
+    workspace: bool,
+}
+
+impl<'de, T: de::Deserialize<'de>> de::Deserialize<'de> for MaybeWorkspace<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        // Not `#[serde(untagged)]`, so errors from parsing the value itself
+        // (like an invalid version) aren't swallowed.
+        struct Visitor<T>(PhantomData<T>);
+
+        impl<'de, T: de::Deserialize<'de>> de::Visitor<'de> for Visitor<T> {
+            type Value = MaybeWorkspace<T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a value or `{ workspace = true }`")
+            }
+
+            fn visit_bool<E>(self, b: bool) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                T::deserialize(b.into_deserializer()).map(MaybeWorkspace::Defined)
+            }
+
+            fn visit_str<E>(self, s: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                T::deserialize(s.into_deserializer()).map(MaybeWorkspace::Defined)
+            }
+
+            fn visit_seq<V>(self, seq: V) -> Result<Self::Value, V::Error>
+            where
+                V: de::SeqAccess<'de>,
+            {
+                let seq = de::value::SeqAccessDeserializer::new(seq);
+                T::deserialize(seq).map(MaybeWorkspace::Defined)
+            }
+
+            fn visit_map<V>(self, map: V) -> Result<Self::Value, V::Error>
+            where
+                V: de::MapAccess<'de>,
+            {
+                let mvd = de::value::MapAccessDeserializer::new(map);
+                TomlWorkspaceField::deserialize(mvd).map(MaybeWorkspace::Workspace)
+            }
+        }
+
+        deserializer.deserialize_any(Visitor(PhantomData))
+    }
+}
+
+impl<T: Clone> MaybeWorkspace<T> {
+    /// Replaces `key.workspace = true` with the value from the workspace,
+    /// returning whether anything was inherited.
+    fn inherit<F>(&mut self, key: &str, cx: &InheritContext, get: F) -> CargoResult<bool>
+    where
+        F: FnOnce(&InheritableFields) -> &Option<T>,
+    {
+        let value = match *self {
+            MaybeWorkspace::Defined(_) => return Ok(false),
+            MaybeWorkspace::Workspace(ref field) => {
+                if !field.workspace {
+                    bail!("`package.{}.workspace` cannot be `false`", key)
+                }
+                match *get(&cx.workspace()?.package) {
+                    Some(ref value) => value.clone(),
+                    None => bail!(
+                        "`package.{}` was inherited from the workspace, but \
+                         `workspace.package.{}` is not defined",
+                        key,
+                        key
+                    ),
+                }
+            }
+        };
+        *self = MaybeWorkspace::Defined(value);
+        Ok(true)
+    }
+
+    fn as_defined(&self) -> Option<&T> {
+        match *self {
+            MaybeWorkspace::Defined(ref value) => Some(value),
+            MaybeWorkspace::Workspace(_) => None,
+        }
+    }
+}
+
+/// For a `readme` or `license-file` outside of the package directory, like one
+/// inherited from the workspace, the name it's packaged under instead, in the
+/// root of the package.
+pub fn packaged_file_name(package_root: &Path, path: &str) -> Option<String> {
+    let full = paths::normalize_path(&package_root.join(path));
+    if full.starts_with(package_root) {
+        return None;
+    }
+    full.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+}
+
+/// Returns the value of an inheritable field, once inheritance has been
+/// resolved by `TomlManifest::inherit_from_workspace`.
+fn defined<T: Clone>(field: &Option<MaybeWorkspace<T>>) -> Option<T> {
+    field.as_ref().and_then(|f| f.as_defined()).cloned()
 }
 
 impl TomlProject {
     pub fn to_package_id(&self, source_id: &SourceId) -> CargoResult<PackageId> {
-        PackageId::new(&self.name, self.version.clone(), source_id)
+        let version = self.version.as_defined().ok_or_else(|| {
+            format_err!("`package.version` has not been inherited from the workspace")
+        })?;
+        PackageId::new(&self.name, version.clone(), source_id)
+    }
+}
+
+/// Lazily locates and loads the workspace root a member inherits from, so
+/// manifests which don't use inheritance never have to find it.
+struct InheritContext<'a> {
+    manifest: &'a TomlManifest,
+    package_root: &'a Path,
+    config: &'a Config,
+    workspace: LazyCell<WorkspaceInheritance>,
+}
+
+/// Everything a member can inherit from its workspace root.
+struct WorkspaceInheritance {
+    /// The directory containing the workspace root manifest.
+    root: PathBuf,
+    package: InheritableFields,
+    dependencies: BTreeMap<String, TomlDependency>,
+}
+
+impl<'a> InheritContext<'a> {
+    fn workspace(&self) -> CargoResult<&WorkspaceInheritance> {
+        self.workspace.try_borrow_with(|| {
+            let (root, ws) = self.find_workspace()?;
+            Ok(WorkspaceInheritance {
+                root,
+                package: ws.package.unwrap_or_default(),
+                dependencies: ws.dependencies.unwrap_or_default(),
+            })
+        })
+    }
+
+    /// Finds the `[workspace]` table of the workspace root, using the same
+    /// rules as `Workspace::find_root`.
+    fn find_workspace(&self) -> CargoResult<(PathBuf, TomlWorkspace)> {
+        if let Some(ref ws) = self.manifest.workspace {
+            return Ok((self.package_root.to_path_buf(), ws.clone()));
+        }
+
+        let project = self.manifest.package.as_ref().or(self.manifest.project.as_ref());
+        if let Some(root) = project.and_then(|p| p.workspace.as_ref()) {
+            let root = paths::normalize_path(&self.package_root.join(root));
+            return match self.read_workspace(&root)? {
+                Some(ws) => Ok((root, ws)),
+                None => bail!(
+                    "`package.workspace` points to `{}`, which is not a workspace root",
+                    root.display()
+                ),
+            };
+        }
+
+        for path in paths::ancestors(self.package_root).skip(1) {
+            if path.ends_with("target/package") {
+                break;
+            }
+            if path.join("Cargo.toml").exists() {
+                if let Some(ws) = self.read_workspace(path)? {
+                    return Ok((path.to_path_buf(), ws));
+                }
+            }
+            // Don't walk across `CARGO_HOME`, see `Workspace::find_root`.
+            if self.config.home() == path {
+                break;
+            }
+        }
+
+        bail!(
+            "the package at `{}` inherits from a workspace, but no workspace \
+             root could be found",
+            self.package_root.display()
+        )
+    }
+
+    fn read_workspace(&self, root: &Path) -> CargoResult<Option<TomlWorkspace>> {
+        let path = root.join("Cargo.toml");
+        let contents = paths::read(&path)?;
+        let manifest: TomlManifest = parse(&contents, &path, self.config)?
+            .try_into()
+            .chain_err(|| format!("failed to parse manifest at `{}`", path.display()))?;
+        Ok(manifest.workspace)
+    }
+
+    /// Rewrites a path relative to the workspace root so that it's relative
+    /// to the package root instead.
+    fn rebase_path(&self, path: &str) -> CargoResult<String> {
+        let ws_root = &self.workspace()?.root;
+        let depth = match self.package_root.strip_prefix(ws_root) {
+            Ok(rest) => rest.components().count(),
+            Err(_) => return Ok(ws_root.join(path).display().to_string()),
+        };
+        let mut rebased = "../".repeat(depth);
+        rebased.push_str(path);
+        Ok(rebased)
+    }
+
+    fn inherit_dependencies(
+        &self,
+        deps: &Option<BTreeMap<String, TomlDependency>>,
+    ) -> CargoResult<Option<BTreeMap<String, TomlDependency>>> {
+        let deps = match *deps {
+            Some(ref deps) => deps,
+            None => return Ok(None),
+        };
+        let deps = deps
+            .iter()
+            .map(|(name, dep)| Ok((name.clone(), self.inherit_dependency(name, dep)?)))
+            .collect::<CargoResult<BTreeMap<_, _>>>()?;
+        Ok(Some(deps))
+    }
+
+    fn inherit_dependency(&self, name: &str, dep: &TomlDependency) -> CargoResult<TomlDependency> {
+        let member = match *dep {
+            TomlDependency::Detailed(ref d) if d.workspace.is_some() => d,
+            _ => return Ok(dep.clone()),
+        };
+        if member.workspace != Some(true) {
+            bail!("`{}.workspace` cannot be `false`", name)
+        }
+        let not_inheritable = [
+            ("version", member.version.is_some()),
+            ("registry", member.registry.is_some()),
+            ("registry-index", member.registry_index.is_some()),
+            ("path", member.path.is_some()),
+            ("git", member.git.is_some()),
+            ("branch", member.branch.is_some()),
+            ("tag", member.tag.is_some()),
+            ("rev", member.rev.is_some()),
+            ("package", member.package.is_some()),
+            (
+                "default-features",
+                member.default_features.is_some() || member.default_features2.is_some(),
+            ),
+        ];
+        if let Some(&(key, _)) = not_inheritable.iter().find(|&&(_, set)| set) {
+            bail!(
+                "dependency `{}` is inherited from the workspace, so it cannot \
+                 also specify `{}`",
+                name,
+                key
+            )
+        }
+
+        let ws = self.workspace()?;
+        let mut inherited = match ws.dependencies.get(name) {
+            Some(&TomlDependency::Simple(ref version)) => DetailedTomlDependency {
+                version: Some(version.clone()),
+                ..Default::default()
+            },
+            Some(&TomlDependency::Detailed(ref d)) => d.clone(),
+            None => bail!(
+                "dependency `{}` was inherited from the workspace, but is not \
+                 found in `workspace.dependencies`",
+                name
+            ),
+        };
+        if inherited.workspace.is_some() {
+            bail!("`workspace.dependencies.{}` cannot specify `workspace`", name)
+        }
+        if inherited.optional.is_some() {
+            bail!(
+                "`workspace.dependencies.{}` cannot be optional, \
+                 mark it optional in the member instead",
+                name
+            )
+        }
+        if let Some(path) = inherited.path.take() {
+            inherited.path = Some(self.rebase_path(&path)?);
+        }
+        // Features are additive on top of those the workspace enables.
+        if let Some(ref features) = member.features {
+            inherited
+                .features
+                .get_or_insert_with(Vec::new)
+                .extend(features.iter().cloned());
+        }
+        inherited.optional = member.optional;
+        Ok(TomlDependency::Detailed(inherited))
     }
 }
 
@@ -634,7 +1087,11 @@ struct Context<'a, 'b> {
 }
 
 impl TomlManifest {
-    pub fn prepare_for_publish(&self, config: &Config) -> CargoResult<TomlManifest> {
+    pub fn prepare_for_publish(
+        &self,
+        config: &Config,
+        package_root: &Path,
+    ) -> CargoResult<TomlManifest> {
         let mut package = self
             .package
             .as_ref()
@@ -642,6 +1099,15 @@ impl TomlManifest {
             .unwrap()
             .clone();
         package.workspace = None;
+        // Files outside of the package are packaged in its root.
+        for file in [&mut package.readme, &mut package.license_file].iter_mut() {
+            if let Some(MaybeWorkspace::Defined(ref mut path)) = **file {
+                let name = packaged_file_name(package_root, path);
+                if let Some(name) = name {
+                    *path = name;
+                }
+            }
+        }
         return Ok(TomlManifest {
             package: Some(package),
             project: None,
@@ -742,6 +1208,97 @@ impl TomlManifest {
         }
     }
 
+    /// Returns a copy of this manifest with every `workspace = true` replaced
+    /// by the value inherited from the workspace root.
+    fn inherit_from_workspace(
+        &self,
+        package_root: &Path,
+        config: &Config,
+    ) -> CargoResult<TomlManifest> {
+        let cx = InheritContext {
+            manifest: self,
+            package_root,
+            config,
+            workspace: LazyCell::new(),
+        };
+        let mut me = self.clone();
+
+        if let Some(project) = me.package.as_mut().or(me.project.as_mut()) {
+            project.version.inherit("version", &cx, |p| &p.version)?;
+            if let Some(ref mut authors) = project.authors {
+                authors.inherit("authors", &cx, |p| &p.authors)?;
+            }
+            if let Some(ref mut description) = project.description {
+                description.inherit("description", &cx, |p| &p.description)?;
+            }
+            if let Some(ref mut homepage) = project.homepage {
+                homepage.inherit("homepage", &cx, |p| &p.homepage)?;
+            }
+            if let Some(ref mut documentation) = project.documentation {
+                documentation.inherit("documentation", &cx, |p| &p.documentation)?;
+            }
+            if let Some(ref mut keywords) = project.keywords {
+                keywords.inherit("keywords", &cx, |p| &p.keywords)?;
+            }
+            if let Some(ref mut categories) = project.categories {
+                categories.inherit("categories", &cx, |p| &p.categories)?;
+            }
+            if let Some(ref mut license) = project.license {
+                license.inherit("license", &cx, |p| &p.license)?;
+            }
+            if let Some(ref mut repository) = project.repository {
+                repository.inherit("repository", &cx, |p| &p.repository)?;
+            }
+            if let Some(ref mut edition) = project.edition {
+                edition.inherit("edition", &cx, |p| &p.edition)?;
+            }
+            if let Some(ref mut rust_version) = project.rust_version {
+                rust_version.inherit("rust-version", &cx, |p| &p.rust_version)?;
+            }
+            if let Some(ref mut publish) = project.publish {
+                publish.inherit("publish", &cx, |p| &p.publish)?;
+            }
+            if let Some(ref mut exclude) = project.exclude {
+                exclude.inherit("exclude", &cx, |p| &p.exclude)?;
+            }
+            if let Some(ref mut include) = project.include {
+                include.inherit("include", &cx, |p| &p.include)?;
+            }
+            // Files are given relative to the workspace root, but are read
+            // relative to the package.
+            if let Some(ref mut readme) = project.readme {
+                if readme.inherit("readme", &cx, |p| &p.readme)? {
+                    let path = cx.rebase_path(readme.as_defined().unwrap())?;
+                    *readme = MaybeWorkspace::Defined(path);
+                }
+            }
+            if let Some(ref mut license_file) = project.license_file {
+                if license_file.inherit("license-file", &cx, |p| &p.license_file)? {
+                    let path = cx.rebase_path(license_file.as_defined().unwrap())?;
+                    *license_file = MaybeWorkspace::Defined(path);
+                }
+            }
+        }
+
+        me.dependencies = cx.inherit_dependencies(&me.dependencies)?;
+        me.dev_dependencies = cx.inherit_dependencies(&me.dev_dependencies)?;
+        me.dev_dependencies2 = cx.inherit_dependencies(&me.dev_dependencies2)?;
+        me.build_dependencies = cx.inherit_dependencies(&me.build_dependencies)?;
+        me.build_dependencies2 = cx.inherit_dependencies(&me.build_dependencies2)?;
+        if let Some(ref mut target) = me.target {
+            for platform in target.values_mut() {
+                platform.dependencies = cx.inherit_dependencies(&platform.dependencies)?;
+                platform.dev_dependencies = cx.inherit_dependencies(&platform.dev_dependencies)?;
+                platform.dev_dependencies2 = cx.inherit_dependencies(&platform.dev_dependencies2)?;
+                platform.build_dependencies =
+                    cx.inherit_dependencies(&platform.build_dependencies)?;
+                platform.build_dependencies2 =
+                    cx.inherit_dependencies(&platform.build_dependencies2)?;
+            }
+        }
+        Ok(me)
+    }
+
     fn to_real_manifest(
         me: &Rc<TomlManifest>,
         source_id: &SourceId,
@@ -752,6 +1309,11 @@ impl TomlManifest {
         let mut warnings = vec![];
         let mut errors = vec![];
 
+        // Fill in everything inherited from the workspace first, so the rest
+        // of this function, and `prepare_for_publish` later on, only ever see
+        // concrete values.
+        let me = &Rc::new(me.inherit_from_workspace(package_root, config)?);
+
         // Parse features first so they will be available when parsing other parts of the toml
         let empty = Vec::new();
         let cargo_features = me.cargo_features.as_ref().unwrap_or(&empty);
@@ -767,7 +1329,7 @@ impl TomlManifest {
 
         let pkgid = project.to_package_id(source_id)?;
 
-        let edition = if let Some(ref edition) = project.edition {
+        let edition = if let Some(edition) = defined(&project.edition) {
             features
                 .require(Feature::edition())
                 .chain_err(|| "editions are unstable")?;
@@ -778,6 +1340,14 @@ impl TomlManifest {
             Edition::Edition2015
         };
 
+        let rust_version = match defined(&project.rust_version) {
+            Some(rust_version) => Some(
+                parse_rust_version(&rust_version)
+                    .chain_err(|| "failed to parse the `rust-version` key")?,
+            ),
+            None => None,
+        };
+
         // If we have no lib at all, use the inferred lib if available
         // If we have a lib with a path, we're done
         // If we have a lib with no path, use the inferred lib or_else package name
@@ -886,8 +1456,8 @@ impl TomlManifest {
             }
         }
 
-        let exclude = project.exclude.clone().unwrap_or_default();
-        let include = project.include.clone().unwrap_or_default();
+        let exclude = defined(&project.exclude).unwrap_or_default();
+        let include = defined(&project.include).unwrap_or_default();
         if project.namespaced_features.is_some() {
             features.require(Feature::namespaced_features())?;
         }
@@ -906,17 +1476,21 @@ impl TomlManifest {
             project.links.as_ref().map(|x| x.as_str()),
             project.namespaced_features.unwrap_or(false),
         )?;
+        let summary = match rust_version {
+            Some(rust_version) => summary.set_rust_version(rust_version),
+            None => summary,
+        };
         let metadata = ManifestMetadata {
-            description: project.description.clone(),
-            homepage: project.homepage.clone(),
-            documentation: project.documentation.clone(),
-            readme: project.readme.clone(),
-            authors: project.authors.clone().unwrap_or_default(),
-            license: project.license.clone(),
-            license_file: project.license_file.clone(),
-            repository: project.repository.clone(),
-            keywords: project.keywords.clone().unwrap_or_default(),
-            categories: project.categories.clone().unwrap_or_default(),
+            description: defined(&project.description),
+            homepage: defined(&project.homepage),
+            documentation: defined(&project.documentation),
+            readme: defined(&project.readme),
+            authors: defined(&project.authors).unwrap_or_default(),
+            license: defined(&project.license),
+            license_file: defined(&project.license_file),
+            repository: defined(&project.repository),
+            keywords: defined(&project.keywords).unwrap_or_default(),
+            categories: defined(&project.categories).unwrap_or_default(),
             badges: me.badges.clone().unwrap_or_default(),
             links: project.links.clone(),
         };
@@ -937,14 +1511,14 @@ impl TomlManifest {
             ),
         };
         let profiles = Profiles::new(me.profile.as_ref(), config, &features, &mut warnings)?;
-        let publish = match project.publish {
-            Some(VecStringOrBool::VecString(ref vecstring)) => {
+        let publish = match defined(&project.publish) {
+            Some(VecStringOrBool::VecString(vecstring)) => {
                 features
                     .require(Feature::alternative_registries())
                     .chain_err(|| {
                         "the `publish` manifest key is unstable for anything other than a value of true or false"
                     })?;
-                Some(vecstring.clone())
+                Some(vecstring)
             }
             Some(VecStringOrBool::Bool(false)) => Some(vec![]),
             None | Some(VecStringOrBool::Bool(true)) => None,
@@ -958,6 +1532,17 @@ impl TomlManifest {
             None => false,
         };
 
+        let resolve_behavior = match (
+            project.resolver.as_ref(),
+            me.workspace.as_ref().and_then(|ws| ws.resolver.as_ref()),
+        ) {
+            (None, None) => None,
+            (Some(s), None) | (None, Some(s)) => Some(ResolveBehavior::from_manifest(s)?),
+            (Some(..), Some(..)) => bail!(
+                "cannot specify `resolver` field in both `[workspace]` and `[package]`"
+            ),
+        };
+
         let custom_metadata = project.metadata.clone();
         let mut manifest = Manifest::new(
             summary,
@@ -978,6 +1563,7 @@ impl TomlManifest {
             project.im_a_teapot,
             project.default_run.clone(),
             Rc::clone(me),
+            resolve_behavior,
         );
         if project.license_file.is_some() && project.license.is_some() {
             manifest.warnings_mut().add_warning(
@@ -1048,6 +1634,10 @@ impl TomlManifest {
             (me.replace(&mut cx)?, me.patch(&mut cx)?)
         };
         let profiles = Profiles::new(me.profile.as_ref(), config, &features, &mut warnings)?;
+        let resolve_behavior = match me.workspace.as_ref().and_then(|ws| ws.resolver.as_ref()) {
+            Some(s) => Some(ResolveBehavior::from_manifest(s)?),
+            None => None,
+        };
         let workspace_config = match me.workspace {
             Some(ref config) => WorkspaceConfig::Root(WorkspaceRootConfig::new(
                 &root,
@@ -1060,7 +1650,7 @@ impl TomlManifest {
             }
         };
         Ok((
-            VirtualManifest::new(replace, patch, workspace_config, profiles),
+            VirtualManifest::new(replace, patch, workspace_config, profiles, resolve_behavior),
             nested_paths,
         ))
     }
@@ -1189,6 +1779,14 @@ impl DetailedTomlDependency {
         cx: &mut Context,
         kind: Option<Kind>,
     ) -> CargoResult<Dependency> {
+        if self.workspace.is_some() {
+            bail!(
+                "dependency ({}) cannot be inherited from the workspace here, \
+                 `workspace = true` is only allowed in dependency tables",
+                name
+            )
+        }
+
         if self.version.is_none() && self.path.is_none() && self.git.is_none() {
             let msg = format!(
                 "dependency ({}) specified without \
@@ -1384,7 +1982,7 @@ impl ser::Serialize for PathValue {
 }
 
 /// Corresponds to a `target` entry, but `TomlTarget` is already used.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 struct TomlPlatform {
     dependencies: Option<BTreeMap<String, TomlDependency>>,
     #[serde(rename = "build-dependencies")]
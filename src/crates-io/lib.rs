@@ -57,6 +57,7 @@ pub struct NewCrate {
     pub repository: Option<String>,
     pub badges: BTreeMap<String, BTreeMap<String, String>>,
     #[serde(default)] pub links: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")] pub rust_version: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -0,0 +1,181 @@
+use support::registry::Package;
+use support::{basic_manifest, execs, project};
+use support::hamcrest::assert_that;
+
+#[test]
+fn add_latest_version() {
+    Package::new("log", "0.3.9").publish();
+    Package::new("log", "0.4.1").publish();
+
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "")
+        .build();
+
+    assert_that(
+        p.cargo("add log"),
+        execs().with_stderr(
+            "\
+[UPDATING] registry `[..]`
+[ADDING] log v0.4.1 to dependencies
+",
+        ),
+    );
+    assert!(p.read_file("Cargo.toml").contains("[dependencies]\nlog = \"0.4.1\"\n"));
+}
+
+#[test]
+fn add_version_requirement() {
+    Package::new("log", "0.3.9").publish();
+    Package::new("log", "0.4.1").publish();
+
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "")
+        .build();
+
+    assert_that(p.cargo("add log@0.3"), execs());
+    assert!(p.read_file("Cargo.toml").contains("log = \"0.3\""));
+
+    assert_that(
+        p.cargo("add log@2.0"),
+        execs().with_status(101).with_stderr_contains(
+            "[ERROR] no version of `log` matching `2.0` could be found in registry `[..]`",
+        ),
+    );
+}
+
+#[test]
+fn add_to_other_tables() {
+    Package::new("a", "1.0.0").publish();
+    Package::new("b", "1.0.0").publish();
+    Package::new("c", "1.0.0").publish();
+
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "")
+        .build();
+
+    assert_that(p.cargo("add --dev a"), execs());
+    assert_that(p.cargo("add --build b"), execs());
+    assert_that(p.cargo("add --target cfg(unix) c --optional"), execs());
+
+    let manifest = p.read_file("Cargo.toml");
+    assert!(manifest.contains("[dev-dependencies]\na = \"1.0.0\"\n"));
+    assert!(manifest.contains("[build-dependencies]\nb = \"1.0.0\"\n"));
+    assert!(manifest.contains(
+        "[target.\"cfg(unix)\".dependencies]\nc = { version = \"1.0.0\", optional = true }\n"
+    ));
+    assert!(!manifest.contains("[target]"));
+}
+
+#[test]
+fn preserves_formatting() {
+    Package::new("log", "0.4.1").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"   # the name
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            # logging
+            log = { version = "0.3", features = ["std"] }
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    assert_that(p.cargo("add log"), execs());
+    assert_eq!(
+        p.read_file("Cargo.toml"),
+        r#"
+            [package]
+            name = "foo"   # the name
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            # logging
+            log = { version = "0.4.1", features = ["std"] }
+        "#
+    );
+}
+
+#[test]
+fn add_path_dependency() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [workspace]
+            members = ["a", "b"]
+        "#,
+        )
+        .file("a/Cargo.toml", &basic_manifest("a", "0.1.0"))
+        .file("a/src/lib.rs", "")
+        .file("b/Cargo.toml", &basic_manifest("b", "0.1.0"))
+        .file("b/src/lib.rs", "")
+        .build();
+
+    assert_that(
+        p.cargo("add -p a --path b --rename bee --no-default-features"),
+        execs(),
+    );
+    assert!(p.read_file("a/Cargo.toml").contains(
+        "[dependencies]\nbee = { path = \"../b\", package = \"b\", default-features = false }\n"
+    ));
+    assert_that(p.cargo("build -p a"), execs());
+
+    assert_that(
+        p.cargo("add -p a --path b c"),
+        execs().with_status(101).with_stderr_contains(
+            "[ERROR] the package at `[..]b` is named `b`, not `c`",
+        ),
+    );
+}
+
+#[test]
+fn remove_dependencies() {
+    Package::new("a", "1.0.0").publish();
+    Package::new("b", "1.0.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            a = "1.0"
+
+            [dev-dependencies]
+            b = "1.0"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    assert_that(
+        p.cargo("rm a"),
+        execs().with_stderr("[REMOVING] a from dependencies\n"),
+    );
+    assert_that(p.cargo("rm --dev b"), execs());
+    let manifest = p.read_file("Cargo.toml");
+    assert!(manifest.contains("[package]"));
+    assert!(!manifest.contains("dependencies"));
+
+    assert_that(
+        p.cargo("rm b"),
+        execs().with_status(101).with_stderr_contains(
+            "[ERROR] the dependency `b` could not be found in `dependencies`",
+        ),
+    );
+}
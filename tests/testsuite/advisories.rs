@@ -0,0 +1,365 @@
+use support::registry::Package;
+use support::{execs, project};
+use support::hamcrest::assert_that;
+
+const ADVISORY: &str = r#"
+[advisory]
+id = "RUSTSEC-2018-0001"
+package = "untrusted"
+title = "An integer underflow could lead to panic"
+date = "2018-06-21"
+url = "https://github.com/briansmith/untrusted/pull/20"
+patched_versions = [">= 0.6.2"]
+unaffected_versions = ["< 0.6.0"]
+"#;
+
+#[test]
+fn report_advisory() {
+    Package::new("untrusted", "0.6.1").publish();
+    Package::new("ring", "0.13.0").dep("untrusted", "0.6").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            ring = "0.13"
+            untrusted = "0.6"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .file("db/crates/untrusted/RUSTSEC-2018-0001.toml", ADVISORY)
+        .build();
+    assert_that(p.cargo("generate-lockfile"), execs().with_status(0));
+
+    assert_that(
+        p.cargo("advisories --db db"),
+        execs()
+            .with_status(101)
+            .with_stdout(
+                "\
+RUSTSEC-2018-0001: untrusted v0.6.1
+  title: An integer underflow could lead to panic
+  date: 2018-06-21
+  url: https://github.com/briansmith/untrusted/pull/20
+  patched versions: >= 0.6.2
+  dependency paths:
+    foo v0.1.0 ([..]) -> untrusted v0.6.1
+    foo v0.1.0 ([..]) -> ring v0.13.0 -> untrusted v0.6.1
+",
+            )
+            .with_stderr("[ERROR] found 1 advisory for packages in Cargo.lock"),
+    );
+}
+
+#[test]
+fn patched_version() {
+    Package::new("untrusted", "0.6.2").publish();
+    Package::new("ring", "0.13.0").dep("untrusted", "0.6").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            ring = "0.13"
+            untrusted = "0.6"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .file("db/crates/untrusted/RUSTSEC-2018-0001.toml", ADVISORY)
+        .build();
+    assert_that(p.cargo("generate-lockfile"), execs().with_status(0));
+
+    assert_that(
+        p.cargo("advisories --db db"),
+        execs()
+            .with_status(0)
+            .with_stdout("")
+            .with_stderr("     Checked 3 packages against 1 advisory"),
+    );
+}
+
+#[test]
+fn versions_table_and_obsolete() {
+    Package::new("untrusted", "0.6.1").publish();
+    Package::new("ring", "0.13.0").dep("untrusted", "0.6").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            ring = "0.13"
+            untrusted = "0.6"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "db/crates/untrusted/RUSTSEC-2018-0001.toml",
+            r#"
+            [advisory]
+            id = "RUSTSEC-2018-0001"
+            package = "untrusted"
+            title = "An integer underflow could lead to panic"
+
+            [versions]
+            patched = [">= 0.6.1"]
+        "#,
+        )
+        .file(
+            "db/crates/untrusted/RUSTSEC-2018-0002.toml",
+            r#"
+            [advisory]
+            id = "RUSTSEC-2018-0002"
+            package = "untrusted"
+            title = "Withdrawn"
+            obsolete = true
+        "#,
+        )
+        .file(
+            "db/crates/untrusted/RUSTSEC-2018-0003.toml",
+            r#"
+            [advisory]
+            id = "RUSTSEC-2018-0003"
+            package = "untrusted"
+            title = "Also withdrawn"
+            withdrawn = "2018-07-01"
+        "#,
+        )
+        .build();
+    assert_that(p.cargo("generate-lockfile"), execs().with_status(0));
+
+    assert_that(
+        p.cargo("advisories --db db"),
+        execs()
+            .with_status(0)
+            .with_stderr("     Checked 3 packages against 1 advisory"),
+    );
+}
+
+#[test]
+fn markdown_advisory() {
+    Package::new("untrusted", "0.6.1").publish();
+    Package::new("ring", "0.13.0").dep("untrusted", "0.6").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            ring = "0.13"
+            untrusted = "0.6"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "db/crates/untrusted/RUSTSEC-2018-0001.md",
+            r#"```toml
+[advisory]
+id = "RUSTSEC-2018-0001"
+package = "untrusted"
+date = "2018-06-21"
+
+[versions]
+patched = [">= 0.6.2"]
+```
+
+# An integer underflow could lead to panic
+
+The `untrusted` crate could underflow when reading input.
+"#,
+        )
+        .build();
+    assert_that(p.cargo("generate-lockfile"), execs().with_status(0));
+
+    assert_that(
+        p.cargo("advisories --db db"),
+        execs()
+            .with_status(101)
+            .with_stdout_contains("RUSTSEC-2018-0001: untrusted v0.6.1")
+            .with_stdout_contains("  title: An integer underflow could lead to panic")
+            .with_stderr("[ERROR] found 1 advisory for packages in Cargo.lock"),
+    );
+}
+
+#[test]
+fn informational_advisory() {
+    Package::new("untrusted", "0.6.1").publish();
+    Package::new("ring", "0.13.0").dep("untrusted", "0.6").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            ring = "0.13"
+            untrusted = "0.6"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "db/crates/untrusted/RUSTSEC-2018-0001.toml",
+            r#"
+            [advisory]
+            id = "RUSTSEC-2018-0001"
+            package = "untrusted"
+            title = "untrusted is unmaintained"
+            informational = "unmaintained"
+        "#,
+        )
+        .build();
+    assert_that(p.cargo("generate-lockfile"), execs().with_status(0));
+
+    assert_that(
+        p.cargo("advisories --db db"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("RUSTSEC-2018-0001: untrusted v0.6.1")
+            .with_stdout_contains("  informational: unmaintained")
+            .with_stderr(
+                "\
+[WARNING] found 1 informational advisory for packages in Cargo.lock
+     Checked 3 packages against 1 advisory
+",
+            ),
+    );
+}
+
+#[test]
+fn no_advisories_found() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file("db/crates/untrusted/README.txt", "")
+        .build();
+
+    assert_that(
+        p.cargo("advisories --db db"),
+        execs().with_status(101).with_stderr(
+            "\
+[ERROR] failed to load the advisory database at `[..]db`
+
+Caused by:
+  no advisories found in `[..]db`, advisories are expected in `.toml` files or \
+`RUSTSEC-*.md` files
+",
+        ),
+    );
+}
+
+#[test]
+fn json_output() {
+    Package::new("untrusted", "0.6.1").publish();
+    Package::new("ring", "0.13.0").dep("untrusted", "0.6").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            ring = "0.13"
+            untrusted = "0.6"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .file("db/crates/untrusted/RUSTSEC-2018-0001.toml", ADVISORY)
+        .build();
+    assert_that(p.cargo("generate-lockfile"), execs().with_status(0));
+
+    assert_that(
+        p.cargo("advisories --db db --message-format json"),
+        execs().with_status(101).with_json(
+            r#"
+{
+    "reason": "advisory",
+    "package_id": "untrusted 0.6.1 [..]",
+    "id": "RUSTSEC-2018-0001",
+    "title": "An integer underflow could lead to panic",
+    "date": "2018-06-21",
+    "url": "https://github.com/briansmith/untrusted/pull/20",
+    "informational": null,
+    "patched_versions": [">= 0.6.2"],
+    "unaffected_versions": ["< 0.6.0"],
+    "paths": [
+        ["foo 0.1.0 [..]", "untrusted 0.6.1 [..]"],
+        ["foo 0.1.0 [..]", "ring 0.13.0 [..]", "untrusted 0.6.1 [..]"]
+    ]
+}
+"#,
+        ),
+    );
+}
+
+#[test]
+fn db_from_config() {
+    Package::new("untrusted", "0.6.1").publish();
+    Package::new("ring", "0.13.0").dep("untrusted", "0.6").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            ring = "0.13"
+            untrusted = "0.6"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .file("db/crates/untrusted/RUSTSEC-2018-0001.toml", ADVISORY)
+        .file(".cargo/config", "[advisories]\ndb = \"./db\"")
+        .build();
+    assert_that(p.cargo("generate-lockfile"), execs().with_status(0));
+
+    assert_that(
+        p.cargo("advisories"),
+        execs()
+            .with_status(101)
+            .with_stdout_contains("RUSTSEC-2018-0001: untrusted v0.6.1"),
+    );
+}
+
+#[test]
+fn no_lockfile() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file("db/crates/untrusted/RUSTSEC-2018-0001.toml", ADVISORY)
+        .build();
+
+    assert_that(
+        p.cargo("advisories --db db"),
+        execs().with_status(101).with_stderr(
+            "\
+[ERROR] a Cargo.lock must exist to check it against advisories, run \
+`cargo generate-lockfile` to create one
+",
+        ),
+    );
+}
@@ -0,0 +1,251 @@
+use support::registry::Package;
+use support::{execs, project};
+use support::hamcrest::assert_that;
+
+#[test]
+fn deny_licenses() {
+    Package::new("gpl", "1.0.0").license("GPL-3.0-or-later").publish();
+    Package::new("dual", "1.0.0").license("MIT OR GPL-3.0").publish();
+    Package::new("mit", "1.0.0").license("MIT").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            gpl = "1.0"
+            dual = "1.0"
+            mit = "1.0"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config",
+            r#"
+            [policy]
+            deny-licenses = ["GPL-3.0"]
+        "#,
+        )
+        .build();
+
+    assert_that(
+        p.cargo("audit-policy"),
+        execs().with_status(101).with_stderr_contains(
+            "\
+[ERROR] 1 package violates the dependency policy:
+  gpl v1.0.0
+    license `GPL-3.0-or-later` can't be complied with: `GPL-3.0-or-later` is denied
+",
+        ),
+    );
+}
+
+#[test]
+fn allow_licenses() {
+    Package::new("both", "1.0.0").license("Apache-2.0 AND MIT").publish();
+    Package::new("either", "1.0.0").license("MIT/Apache-2.0").publish();
+    Package::new("nothing", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            both = "1.0"
+            either = "1.0"
+            nothing = "1.0"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config",
+            r#"
+            [policy]
+            allow-licenses = ["mit"]
+        "#,
+        )
+        .build();
+
+    assert_that(
+        p.cargo("audit-policy"),
+        execs().with_status(101).with_stderr_contains(
+            "\
+[ERROR] 2 packages violate the dependency policy:
+  both v1.0.0
+    license `Apache-2.0 AND MIT` can't be complied with: `Apache-2.0` is not allowed
+  nothing v1.0.0
+    has no `license` field
+",
+        ),
+    );
+}
+
+#[test]
+fn invalid_license_expression() {
+    Package::new("bad", "1.0.0").license("MIT OR (Apache-2.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            bad = "1.0"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config",
+            r#"
+            [policy]
+            deny-licenses = ["GPL-3.0"]
+        "#,
+        )
+        .build();
+
+    assert_that(
+        p.cargo("audit-policy"),
+        execs().with_status(101).with_stderr_contains(
+            "\
+[ERROR] 1 package violates the dependency policy:
+  bad v1.0.0
+    license `MIT OR (Apache-2.0` is not a valid SPDX expression: expected `)`, \
+but the license expression ended
+",
+        ),
+    );
+}
+
+#[test]
+fn invalid_policy_license() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config",
+            r#"
+            [policy]
+            deny-licenses = ["GPL-2.0 OR GPL-3.0"]
+        "#,
+        )
+        .build();
+
+    assert_that(
+        p.cargo("audit-policy"),
+        execs().with_status(101).with_stderr(
+            "\
+[ERROR] invalid license `GPL-2.0 OR GPL-3.0` in `policy.deny-licenses`
+
+Caused by:
+  expected a single license, found the expression `GPL-2.0 OR GPL-3.0`
+",
+        ),
+    );
+}
+
+#[test]
+fn deny_crates() {
+    Package::new("openssl", "0.9.0").publish();
+    Package::new("openssl", "0.10.0").publish();
+    Package::new("a", "0.1.0").dep("openssl", "0.10").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            a = "0.1"
+            openssl = "0.9"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config",
+            r#"
+            [policy]
+            [policy.deny-crates]
+            openssl = "<0.10"
+        "#,
+        )
+        .build();
+
+    assert_that(
+        p.cargo("audit-policy"),
+        execs().with_status(101).with_stderr_contains(
+            "\
+[ERROR] 1 package violates the dependency policy:
+  openssl v0.9.0
+    `openssl = \"<0.10\"` is denied by `policy.deny-crates`
+",
+        ),
+    );
+}
+
+#[test]
+fn allow_sources() {
+    Package::new("a", "0.1.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            a = "0.1"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config",
+            r#"
+            [policy]
+            allow-sources = ["git"]
+        "#,
+        )
+        .build();
+
+    assert_that(
+        p.cargo("audit-policy"),
+        execs().with_status(101).with_stderr_contains(
+            "\
+[ERROR] 1 package violates the dependency policy:
+  a v0.1.0
+    source `registry `[..]`` is not allowed by `policy.allow-sources`
+",
+        ),
+    );
+
+    p.change_file(".cargo/config", "[policy]\nallow-sources = [\"registry\"]");
+    assert_that(
+        p.cargo("audit-policy"),
+        execs()
+            .with_status(0)
+            .with_stderr_contains("     Checked 1 package against the dependency policy"),
+    );
+}
@@ -1685,3 +1685,36 @@ fn bench_virtual_manifest_all_implied() {
             .with_stdout_contains("test bench_bar ... bench: [..]"),
     );
 }
+
+#[test]
+fn bench_json_results() {
+    if !is_nightly() {
+        return;
+    }
+
+    let p = project()
+        .file("Cargo.toml", &basic_lib_manifest("foo"))
+        .file(
+            "src/lib.rs",
+            r#"
+            #![feature(test)]
+            #[cfg(test)]
+            extern crate test;
+
+            #[bench]
+            fn bench_hello(_b: &mut test::Bencher) {}
+            "#,
+        )
+        .build();
+
+    assert_that(
+        p.cargo("bench --message-format json"),
+        execs()
+            .with_stdout_contains(
+                r#"{"bench":{"deviation":[..],"ns_per_iter":[..]},"binary":{[..]},"name":"bench_hello","outcome":"bench","reason":"test-result"}"#,
+            )
+            .with_stdout_contains(
+                r#"{"binary":{[..]},"duration":[..],"exit_code":0,"reason":"test-binary-finished","success":true,"summary":{[..]"measured":1[..]}}"#,
+            ),
+    );
+}
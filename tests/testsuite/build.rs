@@ -973,8 +973,7 @@ fn cargo_compile_path_with_offline() {
 
     assert_that(
         p.cargo("build")
-            .masquerade_as_nightly_cargo()
-            .arg("-Zoffline"),
+            .arg("--offline"),
         execs(),
     );
 }
@@ -1022,8 +1021,7 @@ fn cargo_compile_with_downloaded_dependency_with_offline() {
 
     assert_that(
         p2.cargo("build")
-            .masquerade_as_nightly_cargo()
-            .arg("-Zoffline"),
+            .arg("--offline"),
         execs().with_stderr(
             "\
 [COMPILING] present_dep v1.2.3
@@ -1052,14 +1050,13 @@ fn cargo_compile_offline_not_try_update() {
 
     assert_that(
         p.cargo("build")
-            .masquerade_as_nightly_cargo()
-            .arg("-Zoffline"),
+            .arg("--offline"),
         execs().with_status(101).with_stderr(
             "\
 error: no matching package named `not_cached_dep` found
 location searched: registry `[..]`
 required by package `bar v0.1.0 ([..])`
-As a reminder, you're using offline mode (-Z offline) \
+As a reminder, you're using offline mode (--offline) \
 which can sometimes cause surprising resolution failures, \
 if this error is too confusing you may with to retry \
 without the offline flag.",
@@ -1125,8 +1122,7 @@ fn main(){
 
     assert_that(
         p2.cargo("run")
-            .masquerade_as_nightly_cargo()
-            .arg("-Zoffline"),
+            .arg("--offline"),
         execs()
             .with_stderr(format!(
                 "\
@@ -1276,18 +1272,67 @@ fn compile_offline_while_transitive_dep_not_cached() {
 
     assert_that(
         p.cargo("build")
-            .masquerade_as_nightly_cargo()
-            .arg("-Zoffline"),
+            .arg("--offline"),
         execs().with_status(101).with_stderr(
             "\
-error: no matching package named `baz` found
-location searched: registry `[..]`
-required by package `bar v0.1.0`
-    ... which is depended on by `foo v0.0.1 ([..]/foo)`
-As a reminder, you're using offline mode (-Z offline) \
-which can sometimes cause surprising resolution failures, \
-if this error is too confusing you may with to retry \
-without the offline flag.",
+error: the following packages are not in the local cache and can't be \
+downloaded in offline mode (--offline):
+  baz v1.0.0[..]
+",
+        ),
+    );
+}
+
+#[test]
+fn compile_offline_with_net_offline_config() {
+    Package::new("bar", "0.1.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+
+            [dependencies]
+            bar = "0.1"
+        "#,
+        )
+        .file("src/main.rs", "fn main(){}")
+        .build();
+    assert_that(p.cargo("build"), execs());
+
+    // A crate that is not in the cache is never picked while offline, even
+    // when it is a newer match.
+    Package::new("bar", "0.1.1").publish();
+    fs::remove_file(p.root().join("Cargo.lock")).unwrap();
+    p.change_file(
+        ".cargo/config",
+        r#"
+            [net]
+            offline = true
+        "#,
+    );
+    assert_that(
+        p.cargo("build"),
+        execs().with_stderr(
+            "\
+[COMPILING] bar v0.1.0
+[COMPILING] foo v0.0.1 ([..])
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+",
+        ),
+    );
+}
+
+#[test]
+fn offline_unstable_flag_stabilized() {
+    let p = project().file("src/lib.rs", "").build();
+    assert_that(
+        p.cargo("build -Zoffline").masquerade_as_nightly_cargo(),
+        execs().with_status(101).with_stderr_contains(
+            "[..]`-Z offline` has been stabilized, use `--offline` instead",
         ),
     );
 }
@@ -4866,3 +4911,144 @@ fn target_filters_workspace_not_found() {
         ),
     );
 }
+
+#[test]
+fn pipelining_works() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                authors = []
+
+                [dependencies]
+                bar = { path = "bar" }
+            "#,
+        ).file("src/lib.rs", "extern crate bar; pub fn foo() { bar::bar() }")
+        .file("src/main.rs", "extern crate foo; fn main() { foo::foo() }")
+        .file("bar/Cargo.toml", &basic_lib_manifest("bar"))
+        .file("bar/src/lib.rs", "pub fn bar() { let unused = 1; }")
+        .file(
+            ".cargo/config",
+            r#"
+                [build]
+                pipelining = true
+            "#,
+        ).build();
+
+    assert_that(
+        p.cargo("build -v"),
+        execs()
+            .with_stderr_contains(
+                "[RUNNING] `rustc --crate-name bar [..]--emit=dep-info,metadata,link [..]",
+            ).with_stderr_contains(
+                "[RUNNING] `rustc --crate-name foo src/lib.rs [..]\
+                 --extern bar=[..]/libbar-[..].rmeta[..]",
+            ).with_stderr_contains(
+                "[RUNNING] `rustc --crate-name foo src/main.rs [..]\
+                 --extern foo=[..]/libfoo-[..].rlib[..]",
+            ).with_stderr_contains("[..]unused variable: `unused`"),
+    );
+
+    // Settling the fingerprint of `bar` once its metadata was ready means
+    // `foo` isn't rebuilt.
+    assert_that(
+        p.cargo("build"),
+        execs().with_stderr("[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]"),
+    );
+}
+
+#[test]
+fn pipelining_links_transitive_code() {
+    // `b` is slow to generate code for, so `a` finishes, having been started
+    // off `b`'s metadata, long before `b`'s rlib exists. Linking `foo` has to
+    // wait for that rlib too.
+    let mut slow = String::new();
+    for i in 0..2000 {
+        slow.push_str(&format!(
+            "#[inline(never)] pub fn f{0}(x: u64) -> u64 {{ (0..x).map(|y| y * {0}).sum() }}\n",
+            i
+        ));
+    }
+    slow.push_str("pub fn b() -> u64 { f1(10) }\n");
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                authors = []
+
+                [dependencies]
+                a = { path = "a" }
+            "#,
+        ).file("src/main.rs", "extern crate a; fn main() { println!(\"{}\", a::a()) }")
+        .file(
+            "a/Cargo.toml",
+            r#"
+                [package]
+                name = "a"
+                version = "0.1.0"
+                authors = []
+
+                [dependencies]
+                b = { path = "../b" }
+            "#,
+        ).file("a/src/lib.rs", "extern crate b; pub fn a() -> u64 { b::b() }")
+        .file("b/Cargo.toml", &basic_lib_manifest("b"))
+        .file("b/src/lib.rs", &slow)
+        .file(
+            ".cargo/config",
+            r#"
+                [build]
+                pipelining = true
+            "#,
+        ).build();
+
+    assert_that(
+        p.cargo("build -v"),
+        execs()
+            .with_stderr_contains(
+                "[RUNNING] `rustc --crate-name a [..]--extern b=[..]/libb-[..].rmeta[..]",
+            ).with_stderr_contains("[RUNNING] `rustc --crate-name foo src/main.rs [..]"),
+    );
+    assert_that(p.cargo("run"), execs().with_stdout("45"));
+}
+
+#[test]
+fn pipelining_json_messages() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                authors = []
+
+                [dependencies]
+                bar = { path = "bar" }
+            "#,
+        ).file("src/lib.rs", "extern crate bar; pub fn foo() { bar::bar() }")
+        .file("src/main.rs", "extern crate foo; fn main() { foo::foo() }")
+        .file("bar/Cargo.toml", &basic_lib_manifest("bar"))
+        .file("bar/src/lib.rs", "pub fn bar() { let unused = 1; }")
+        .file(
+            ".cargo/config",
+            r#"
+                [build]
+                pipelining = true
+            "#,
+        ).build();
+
+    assert_that(
+        p.cargo("build --message-format json"),
+        execs()
+            .with_stdout_contains(
+                r#"{"message":{[..]"unused variable: `unused`"[..]},"package_id":"bar [..]","reason":"compiler-message",[..]}"#,
+            ).with_stdout_does_not_contain("[..]\"artifact\":[..]"),
+    );
+}
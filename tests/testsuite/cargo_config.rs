@@ -0,0 +1,164 @@
+use std::fs;
+
+use support::hamcrest::assert_that;
+use support::{execs, paths, project};
+
+fn write_root_config(config: &str) {
+    let path = paths::root().join(".cargo/config");
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, config).unwrap();
+}
+
+#[test]
+fn get_single_key() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config",
+            r#"
+            [build]
+            jobs = 3
+            rustflags = ["-C", "debuginfo=1"]
+        "#,
+        )
+        .build();
+
+    assert_that(
+        p.cargo("config get build.jobs"),
+        execs().with_stdout("build.jobs = 3"),
+    );
+    assert_that(
+        p.cargo("config get build"),
+        execs().with_stdout(
+            "\
+build.jobs = 3
+build.rustflags = [\"-C\", \"debuginfo=1\"]
+",
+        ),
+    );
+}
+
+#[test]
+fn get_merged_show_origin() {
+    write_root_config(
+        r#"
+        [build]
+        rustflags = ["--cfg", "outer"]
+        incremental = false
+    "#,
+    );
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config",
+            r#"
+            [build]
+            rustflags = ["--cfg", "inner"]
+            target-dir = "out"
+        "#,
+        )
+        .build();
+
+    assert_that(
+        p.cargo("config get build --show-origin"),
+        execs().with_stdout(
+            "\
+build.incremental = false # [..]/.cargo/config
+build.rustflags = [
+    \"--cfg\", # [..]/foo/.cargo/config
+    \"inner\", # [..]/foo/.cargo/config
+    \"--cfg\", # [..]/.cargo/config
+    \"outer\", # [..]/.cargo/config
+]
+build.target-dir = \"out\" # [..]/foo/.cargo/config
+",
+        ),
+    );
+}
+
+#[test]
+fn get_env_override() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config",
+            r#"
+            [build]
+            jobs = 3
+        "#,
+        )
+        .build();
+
+    assert_that(
+        p.cargo("config get build.jobs --show-origin")
+            .env("CARGO_BUILD_JOBS", "5"),
+        execs().with_stdout("build.jobs = 5 # environment variable `CARGO_BUILD_JOBS`"),
+    );
+    // A key no file sets.
+    assert_that(
+        p.cargo("config get build.target-dir --show-origin")
+            .env("CARGO_BUILD_TARGET_DIR", "out"),
+        execs().with_stdout(
+            "build.target-dir = \"out\" # environment variable `CARGO_BUILD_TARGET_DIR`",
+        ),
+    );
+}
+
+#[test]
+fn get_all_env_only() {
+    let p = project().file("src/lib.rs", "").build();
+
+    assert_that(
+        p.cargo("config get --show-origin")
+            .env("CARGO_BUILD_TARGET_DIR", "out"),
+        execs().with_stdout(
+            "build.target-dir = \"out\" # environment variable `CARGO_BUILD_TARGET_DIR`",
+        ),
+    );
+}
+
+#[test]
+fn get_json() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config",
+            r#"
+            [build]
+            jobs = 3
+            rustflags = ["-C", "debuginfo=1"]
+
+            [target.'cfg(unix)']
+            runner = "run-it"
+        "#,
+        )
+        .build();
+
+    assert_that(
+        p.cargo("config get build --format json"),
+        execs().with_json(r#"{"build":{"jobs":3,"rustflags":["-C","debuginfo=1"]}}"#),
+    );
+    assert_that(
+        p.cargo("config get target"),
+        execs().with_stdout("target.\"cfg(unix)\".runner = \"run-it\""),
+    );
+    assert_that(
+        p.cargo("config get build --format json --show-origin"),
+        execs().with_status(101).with_stderr(
+            "[ERROR] the `json` format does not support --show-origin, \
+             try the `toml` format instead",
+        ),
+    );
+}
+
+#[test]
+fn get_missing_key() {
+    let p = project().file("src/lib.rs", "").build();
+
+    assert_that(
+        p.cargo("config get build.not-a-key"),
+        execs()
+            .with_status(101)
+            .with_stderr("[ERROR] config value `build.not-a-key` is not set"),
+    );
+}
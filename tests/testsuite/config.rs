@@ -190,6 +190,7 @@ codegen-units = 9
         incremental: None,
         overrides: None,
         build_override: None,
+        inherits: None,
     };
     overrides.insert(key, o_profile);
     let key = toml::ProfilePackageSpec::Spec(::cargo::core::PackageIdSpec::parse("env").unwrap());
@@ -205,6 +206,7 @@ codegen-units = 9
         incremental: None,
         overrides: None,
         build_override: None,
+        inherits: None,
     };
     overrides.insert(key, o_profile);
 
@@ -232,8 +234,10 @@ codegen-units = 9
                 overflow_checks: None,
                 incremental: None,
                 overrides: None,
-                build_override: None
-            }))
+                build_override: None,
+                inherits: None,
+            })),
+            inherits: None,
         }
     );
 }
@@ -0,0 +1,158 @@
+use support::hamcrest::assert_that;
+use support::{execs, project};
+
+#[test]
+fn cli_overrides_file_and_env() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config",
+            r#"
+            [build]
+            jobs = 3
+        "#,
+        )
+        .build();
+
+    assert_that(
+        p.cargo("config get build.jobs --show-origin --config build.jobs=5"),
+        execs().with_stdout("build.jobs = 5 # --config cli option"),
+    );
+    assert_that(
+        p.cargo("config get build.jobs --show-origin --config build.jobs=5")
+            .env("CARGO_BUILD_JOBS", "4"),
+        execs().with_stdout("build.jobs = 5 # --config cli option"),
+    );
+    // The last of several arguments wins.
+    assert_that(
+        p.cargo("--config build.jobs=5 config get build.jobs --config build.jobs=6"),
+        execs().with_stdout("build.jobs = 6"),
+    );
+}
+
+#[test]
+fn cli_tables_and_lists() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config",
+            r#"
+            [build]
+            rustflags = ["--cfg", "from_file"]
+        "#,
+        )
+        .build();
+
+    assert_that(
+        p.cargo("config get build.rustflags")
+            .arg("--config")
+            .arg("build.rustflags = ['--cfg', 'from_cli']"),
+        execs().with_stdout(
+            "build.rustflags = [\"--cfg\", \"from_cli\", \"--cfg\", \"from_file\"]",
+        ),
+    );
+    assert_that(
+        p.cargo("config get target")
+            .arg("--config")
+            .arg("target.'cfg(unix)'.runner = \"run-it\""),
+        execs().with_stdout("target.\"cfg(unix)\".runner = \"run-it\""),
+    );
+    assert_that(
+        p.cargo("config get alias")
+            .arg("--config")
+            .arg("alias = { x = 'build' }"),
+        execs().with_stdout("alias.x = \"build\""),
+    );
+}
+
+#[test]
+fn cli_affects_build() {
+    let p = project()
+        .file(
+            "src/main.rs",
+            r#"
+            fn main() {
+                if cfg!(from_cli) {
+                    println!("from cli");
+                }
+            }
+        "#,
+        )
+        .build();
+
+    assert_that(
+        p.cargo("run")
+            .arg("--config")
+            .arg("build.rustflags=['--cfg', 'from_cli']"),
+        execs().with_stdout("from cli"),
+    );
+}
+
+#[test]
+fn cli_config_file() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            "extra.toml",
+            r#"
+            [build]
+            jobs = 7
+        "#,
+        )
+        .build();
+
+    assert_that(
+        p.cargo("config get build.jobs --show-origin --config extra.toml"),
+        execs().with_stdout("build.jobs = 7 # [..]extra.toml"),
+    );
+}
+
+#[test]
+fn cli_config_file_overrides_env() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            "extra.toml",
+            r#"
+            [build]
+            jobs = 7
+        "#,
+        )
+        .build();
+
+    assert_that(
+        p.cargo("config get build.jobs --show-origin --config extra.toml")
+            .env("CARGO_BUILD_JOBS", "4"),
+        execs().with_stdout("build.jobs = 7 # [..]extra.toml"),
+    );
+}
+
+#[test]
+fn cli_bad_values() {
+    let p = project().file("src/lib.rs", "").build();
+
+    assert_that(
+        p.cargo("config get build.jobs --config missing.toml"),
+        execs().with_status(101).with_stderr(
+            "\
+[ERROR] failed to load --config argument `missing.toml`
+
+Caused by:
+  expected a `KEY=VALUE` pair or the path of a config file, but no such file exists
+",
+        ),
+    );
+    assert_that(
+        p.cargo("config get build.jobs --config build.jobs=two"),
+        execs().with_status(101).with_stderr_contains(
+            "[ERROR] failed to load --config argument `build.jobs=two`",
+        ),
+    );
+    assert_that(
+        p.cargo("build --config build.jobs='two'"),
+        execs().with_status(101).with_stderr_contains(
+            "[ERROR] error in --config cli option: `build.jobs` expected an integer, \
+             but found a string",
+        ),
+    );
+}
@@ -0,0 +1,270 @@
+use support::hamcrest::assert_that;
+use support::{basic_bin_manifest, execs, project};
+
+#[test]
+fn env_basic() {
+    let p = project()
+        .file("Cargo.toml", &basic_bin_manifest("foo"))
+        .file(
+            "src/main.rs",
+            r#"
+            fn main() {
+                println!("compile-time:{}", env!("ENV_TEST_1233"));
+                println!("run-time:{}", std::env::var("ENV_TEST_1233").unwrap());
+            }
+        "#,
+        )
+        .file(
+            ".cargo/config",
+            r#"
+            [env]
+            ENV_TEST_1233 = "Hello"
+        "#,
+        )
+        .build();
+
+    assert_that(
+        p.cargo("run"),
+        execs()
+            .with_stdout_contains("compile-time:Hello")
+            .with_stdout_contains("run-time:Hello"),
+    );
+}
+
+#[test]
+fn env_build_script() {
+    let p = project()
+        .file("src/main.rs", "fn main() {}")
+        .file(
+            "build.rs",
+            r#"
+            fn main() {
+                assert_eq!(std::env::var("ENV_TEST_BUILD").unwrap(), "from config");
+            }
+        "#,
+        )
+        .file(
+            ".cargo/config",
+            r#"
+            [env]
+            ENV_TEST_BUILD = "from config"
+        "#,
+        )
+        .build();
+
+    assert_that(p.cargo("build"), execs());
+}
+
+#[test]
+fn env_test() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            "tests/env.rs",
+            r#"
+            #[test]
+            fn env() {
+                assert_eq!(env!("ENV_TEST_TEST"), "set");
+                assert_eq!(std::env::var("ENV_TEST_TEST").unwrap(), "set");
+            }
+        "#,
+        )
+        .file(
+            ".cargo/config",
+            r#"
+            [env]
+            ENV_TEST_TEST = "set"
+        "#,
+        )
+        .build();
+
+    assert_that(
+        p.cargo("test"),
+        execs().with_stdout_contains("test env ... ok"),
+    );
+}
+
+#[test]
+fn env_no_override() {
+    let p = project()
+        .file("Cargo.toml", &basic_bin_manifest("foo"))
+        .file(
+            "src/main.rs",
+            r#"
+            fn main() {
+                println!("override:{}", env!("ENV_TEST_NO_OVERRIDE"));
+            }
+        "#,
+        )
+        .file(
+            ".cargo/config",
+            r#"
+            [env]
+            ENV_TEST_NO_OVERRIDE = "from config"
+        "#,
+        )
+        .build();
+
+    assert_that(
+        p.cargo("run").env("ENV_TEST_NO_OVERRIDE", "from env"),
+        execs().with_stdout_contains("override:from env"),
+    );
+}
+
+#[test]
+fn env_force() {
+    let p = project()
+        .file("Cargo.toml", &basic_bin_manifest("foo"))
+        .file(
+            "src/main.rs",
+            r#"
+            fn main() {
+                println!("forced:{}", env!("ENV_TEST_FORCED"));
+                println!("unforced:{}", env!("ENV_TEST_UNFORCED"));
+            }
+        "#,
+        )
+        .file(
+            ".cargo/config",
+            r#"
+            [env]
+            ENV_TEST_FORCED = { value = "from config", force = true }
+            ENV_TEST_UNFORCED = { value = "from config", force = false }
+        "#,
+        )
+        .build();
+
+    assert_that(
+        p.cargo("run")
+            .env("ENV_TEST_FORCED", "from env")
+            .env("ENV_TEST_UNFORCED", "from env"),
+        execs()
+            .with_stdout_contains("forced:from config")
+            .with_stdout_contains("unforced:from env"),
+    );
+}
+
+#[test]
+fn env_relative() {
+    let p = project()
+        .file("Cargo.toml", &basic_bin_manifest("foo"))
+        .file(
+            "src/main.rs",
+            r#"
+            use std::path::Path;
+
+            fn main() {
+                let relative = env!("ENV_TEST_RELATIVE");
+                let absolute = env!("ENV_TEST_ABSOLUTE");
+                println!("relative:{}", relative);
+                assert!(Path::new(absolute).is_absolute());
+                assert!(Path::new(absolute).ends_with("foo/data"));
+            }
+        "#,
+        )
+        .file(
+            ".cargo/config",
+            r#"
+            [env]
+            ENV_TEST_RELATIVE = "data"
+            ENV_TEST_ABSOLUTE = { value = "data", relative = true }
+        "#,
+        )
+        .build();
+
+    assert_that(
+        p.cargo("run"),
+        execs().with_stdout_contains("relative:data"),
+    );
+}
+
+#[test]
+fn env_changes_rebuild() {
+    let p = project()
+        .file("Cargo.toml", &basic_bin_manifest("foo"))
+        .file(
+            "src/main.rs",
+            r#"
+            fn main() {
+                println!("value:{}", env!("ENV_TEST_CHANGE"));
+            }
+        "#,
+        )
+        .file(
+            ".cargo/config",
+            r#"
+            [env]
+            ENV_TEST_CHANGE = "one"
+        "#,
+        )
+        .build();
+
+    assert_that(
+        p.cargo("run"),
+        execs().with_stdout_contains("value:one"),
+    );
+    assert_that(
+        p.cargo("build"),
+        execs().with_stderr("[FINISHED] [..]"),
+    );
+
+    p.change_file(
+        ".cargo/config",
+        r#"
+        [env]
+        ENV_TEST_CHANGE = "two"
+    "#,
+    );
+    assert_that(
+        p.cargo("run"),
+        execs()
+            .with_stderr_contains("[COMPILING] foo v0.5.0 ([..])")
+            .with_stdout_contains("value:two"),
+    );
+}
+
+#[test]
+fn env_invalid() {
+    let p = project()
+        .file("src/main.rs", "fn main() {}")
+        .file(
+            ".cargo/config",
+            r#"
+            [env]
+            ENV_TEST_INVALID = 1
+        "#,
+        )
+        .build();
+
+    assert_that(
+        p.cargo("build"),
+        execs()
+            .with_status(101)
+            .with_stderr_contains("[ERROR] invalid configuration for key `env.ENV_TEST_INVALID`")
+            .with_stderr_contains(
+                "expected a string or table, but found a integer for \
+                 `env.ENV_TEST_INVALID` in [..]config",
+            ),
+    );
+}
+
+#[test]
+fn env_missing_value() {
+    let p = project()
+        .file("src/main.rs", "fn main() {}")
+        .file(
+            ".cargo/config",
+            r#"
+            [env]
+            ENV_TEST_MISSING = { force = true }
+        "#,
+        )
+        .build();
+
+    assert_that(
+        p.cargo("build"),
+        execs().with_status(101).with_stderr_contains(
+            "[ERROR] missing `value` for `env.ENV_TEST_MISSING` in [..]config",
+        ),
+    );
+}
@@ -0,0 +1,222 @@
+use support::hamcrest::assert_that;
+use support::{execs, project};
+
+#[test]
+fn include_basic() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config",
+            r#"
+            include = "shared.toml"
+
+            [build]
+            jobs = 2
+        "#,
+        )
+        .file(
+            ".cargo/shared.toml",
+            r#"
+            [build]
+            jobs = 4
+            incremental = false
+
+            [alias]
+            b2 = "build"
+        "#,
+        )
+        .build();
+
+    assert_that(
+        p.cargo("config get build --show-origin"),
+        execs().with_stdout(
+            "\
+build.incremental = false # [..]/.cargo/shared.toml
+build.jobs = 2 # [..]/.cargo/config
+",
+        ),
+    );
+    assert_that(
+        p.cargo("b2"),
+        execs().with_stderr(
+            "\
+[COMPILING] foo v0.0.1 ([..])
+[FINISHED] [..]
+",
+        ),
+    );
+}
+
+#[test]
+fn include_list_precedence() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config",
+            r#"
+            include = ["a.toml", "b.toml"]
+        "#,
+        )
+        .file(
+            ".cargo/a.toml",
+            r#"
+            [build]
+            jobs = 1
+            incremental = true
+        "#,
+        )
+        .file(
+            ".cargo/b.toml",
+            r#"
+            [build]
+            jobs = 2
+        "#,
+        )
+        .build();
+
+    assert_that(
+        p.cargo("config get build --show-origin"),
+        execs().with_stdout(
+            "\
+build.incremental = true # [..]/.cargo/a.toml
+build.jobs = 2 # [..]/.cargo/b.toml
+",
+        ),
+    );
+}
+
+#[test]
+fn include_nested_relative() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config",
+            r#"
+            include = "../shared/team.toml"
+        "#,
+        )
+        .file(
+            "shared/team.toml",
+            r#"
+            include = "more/extra.toml"
+
+            [build]
+            jobs = 3
+        "#,
+        )
+        .file(
+            "shared/more/extra.toml",
+            r#"
+            [build]
+            incremental = false
+        "#,
+        )
+        .build();
+
+    assert_that(
+        p.cargo("config get build --show-origin"),
+        execs().with_stdout(
+            "\
+build.incremental = false # [..]/shared/more/extra.toml
+build.jobs = 3 # [..]/shared/team.toml
+",
+        ),
+    );
+}
+
+#[test]
+fn include_cycle() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config",
+            r#"
+            include = "other.toml"
+        "#,
+        )
+        .file(
+            ".cargo/other.toml",
+            r#"
+            include = "../.cargo/config"
+        "#,
+        )
+        .build();
+
+    assert_that(
+        p.cargo("build"),
+        execs().with_status(101).with_stderr(
+            "\
+[ERROR] could not load Cargo configuration
+
+Caused by:
+  failed to load config include `other.toml` from `[..]/.cargo/config`
+
+Caused by:
+  failed to load config include `../.cargo/config` from `[..]/.cargo/other.toml`
+
+Caused by:
+  config `include` cycle detected:
+  [..]/.cargo/config
+  includes [..]/.cargo/other.toml
+  includes [..]/.cargo/config
+",
+        ),
+    );
+}
+
+#[test]
+fn include_missing() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config",
+            r#"
+            include = ["missing.toml"]
+        "#,
+        )
+        .build();
+
+    assert_that(
+        p.cargo("build"),
+        execs().with_status(101).with_stderr(
+            "\
+[ERROR] could not load Cargo configuration
+
+Caused by:
+  failed to load config include `missing.toml` from `[..]/.cargo/config`
+
+Caused by:
+  failed to read configuration file `[..]/.cargo/missing.toml`
+
+Caused by:
+  [..]
+",
+        ),
+    );
+}
+
+#[test]
+fn include_bad_type() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config",
+            r#"
+            include = 1
+        "#,
+        )
+        .build();
+
+    assert_that(
+        p.cargo("build"),
+        execs().with_status(101).with_stderr(
+            "\
+[ERROR] could not load Cargo configuration
+
+Caused by:
+  expected a string or list of strings for `include` in `[..]/.cargo/config`, \
+but found a integer
+",
+        ),
+    );
+}
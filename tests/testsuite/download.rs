@@ -0,0 +1,275 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::prelude::*;
+use std::io::BufReader;
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use support::paths::{self, CargoPathExt};
+use support::registry::{registry_path, Package};
+use support::{execs, project, Project};
+use support::hamcrest::assert_that;
+
+/// Serves both a sparse index and the `.crate` files of the test registry
+/// over HTTP, so that crates are downloaded with curl rather than read from
+/// `file://` URLs.
+struct Server {
+    addr: String,
+    log: Arc<Mutex<Vec<String>>>,
+    /// How many more times requests for a path should fail with a 500.
+    failures: Arc<Mutex<HashMap<String, usize>>>,
+}
+
+impl Server {
+    fn start() -> Server {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let failures = Arc::new(Mutex::new(HashMap::new()));
+        let root = paths::root();
+        let thread_log = log.clone();
+        let thread_failures = failures.clone();
+        thread::spawn(move || {
+            for conn in listener.incoming() {
+                let entry = respond(conn.unwrap(), &root, &thread_failures);
+                thread_log.lock().unwrap().push(entry);
+            }
+        });
+
+        // Point the registry's downloads at the server too.
+        fs::write(
+            registry_path().join("config.json"),
+            format!(r#"{{"dl":"http://{0}/dl","api":"http://{0}/api"}}"#, addr),
+        ).unwrap();
+
+        Server {
+            addr,
+            log,
+            failures,
+        }
+    }
+
+    fn index_url(&self) -> String {
+        format!("sparse+http://{}/registry/", self.addr)
+    }
+
+    fn crate_url(&self, name: &str, vers: &str) -> String {
+        format!("http://{}/dl/{}/{}/download", self.addr, name, vers)
+    }
+
+    /// Makes the next `times` downloads of the crate fail.
+    fn fail(&self, name: &str, vers: &str, times: usize) {
+        let path = format!("/dl/{}/{}/download", name, vers);
+        self.failures.lock().unwrap().insert(path, times);
+    }
+
+    /// The download requests made so far, like `GET /dl/bar/0.0.1/download 200`.
+    fn downloads(&self) -> Vec<String> {
+        let log = self.log.lock().unwrap();
+        log.iter()
+            .filter(|entry| entry.starts_with("GET /dl/"))
+            .cloned()
+            .collect()
+    }
+
+    fn configure(&self, p: &Project, retries: u32) {
+        p.change_file(
+            ".cargo/config",
+            &format!(
+                r#"
+                [net]
+                retry = {}
+
+                [source.crates-io]
+                replace-with = 'http-registry'
+
+                [source.http-registry]
+                registry = '{}'
+            "#,
+                retries,
+                self.index_url()
+            ),
+        );
+    }
+}
+
+fn respond(conn: TcpStream, root: &Path, failures: &Mutex<HashMap<String, usize>>) -> String {
+    let mut reader = BufReader::new(conn.try_clone().unwrap());
+    let mut request = String::new();
+    reader.read_line(&mut request).unwrap();
+    let path = request.split_whitespace().nth(1).unwrap().to_string();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        if line.trim().is_empty() {
+            break;
+        }
+    }
+
+    let fail = match failures.lock().unwrap().get_mut(&path) {
+        Some(ref mut remaining) if **remaining > 0 => {
+            **remaining -= 1;
+            true
+        }
+        _ => false,
+    };
+    let (status, status_line, body) = if fail {
+        (500, "500 Internal Server Error", Vec::new())
+    } else {
+        match fs::read(root.join(&path[1..])) {
+            Ok(contents) => (200, "200 OK", contents),
+            Err(_) => (404, "404 Not Found", Vec::new()),
+        }
+    };
+    let mut conn = conn;
+    write!(
+        conn,
+        "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status_line,
+        body.len()
+    ).unwrap();
+    conn.write_all(&body).unwrap();
+    format!("GET {} {}", path, status)
+}
+
+#[test]
+fn downloads_all_dependencies() {
+    Package::new("bar", "0.0.1").publish();
+    Package::new("baz", "0.0.1").publish();
+    Package::new("qux", "0.0.1")
+        .dep("bar", "0.0.1")
+        .publish();
+    let server = Server::start();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            baz = "0.0.1"
+            qux = "0.0.1"
+        "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+    server.configure(&p, 2);
+
+    assert_that(
+        p.cargo("build"),
+        execs().with_stderr_unordered(&format!(
+            "\
+[UPDATING] registry `{reg}`
+[DOWNLOADING] bar v0.0.1 (registry `{reg}`)
+[DOWNLOADING] baz v0.0.1 (registry `{reg}`)
+[DOWNLOADING] qux v0.0.1 (registry `{reg}`)
+[COMPILING] bar v0.0.1
+[COMPILING] baz v0.0.1
+[COMPILING] qux v0.0.1
+[COMPILING] foo v0.0.1 ([..])
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]s
+",
+            reg = server.index_url()
+        )),
+    );
+
+    let mut downloads = server.downloads();
+    downloads.sort();
+    assert_eq!(
+        downloads,
+        vec![
+            "GET /dl/bar/0.0.1/download 200",
+            "GET /dl/baz/0.0.1/download 200",
+            "GET /dl/qux/0.0.1/download 200",
+        ]
+    );
+
+    // Everything is cached now, so nothing is downloaded again.
+    p.root().join("target").rm_rf();
+    assert_that(p.cargo("build"), execs());
+    assert_eq!(server.downloads().len(), 3);
+}
+
+#[test]
+fn retries_failed_download() {
+    Package::new("bar", "0.0.1").publish();
+    Package::new("baz", "0.0.1").publish();
+    let server = Server::start();
+    server.fail("bar", "0.0.1", 1);
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = "0.0.1"
+            baz = "0.0.1"
+        "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+    server.configure(&p, 2);
+
+    assert_that(
+        p.cargo("build"),
+        execs().with_stderr_contains(&format!(
+            "[WARNING] spurious network error (2 tries remaining): \
+             failed to get 200 response from `{}`, got 500",
+            server.crate_url("bar", "0.0.1")
+        )),
+    );
+
+    let downloads = server.downloads();
+    assert!(downloads.contains(&"GET /dl/bar/0.0.1/download 500".to_string()));
+    assert!(downloads.contains(&"GET /dl/bar/0.0.1/download 200".to_string()));
+    assert!(downloads.contains(&"GET /dl/baz/0.0.1/download 200".to_string()));
+}
+
+#[test]
+fn gives_up_after_retries() {
+    Package::new("bar", "0.0.1").publish();
+    let server = Server::start();
+    server.fail("bar", "0.0.1", 2);
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = "0.0.1"
+        "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+    server.configure(&p, 1);
+
+    assert_that(
+        p.cargo("build"),
+        execs()
+            .with_status(101)
+            .with_stderr_contains(
+                "[WARNING] spurious network error (1 tries remaining): [..]",
+            )
+            .with_stderr_contains(&format!(
+                "[ERROR] failed to download from `{}`",
+                server.crate_url("bar", "0.0.1")
+            ))
+            .with_stderr_contains(&format!(
+                "  failed to get 200 response from `{}`, got 500",
+                server.crate_url("bar", "0.0.1")
+            )),
+    );
+}
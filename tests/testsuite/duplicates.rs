@@ -0,0 +1,211 @@
+use support::registry::Package;
+use support::{execs, project};
+use support::hamcrest::assert_that;
+
+#[test]
+fn report_duplicates() {
+    Package::new("log", "0.3.9").publish();
+    Package::new("log", "0.4.6").publish();
+    Package::new("a", "0.1.0").dep("log", "0.4").publish();
+    Package::new("b", "0.1.0").dep("log", "0.4").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            a = "0.1"
+            b = "0.1"
+            log = "0.3"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    assert_that(
+        p.cargo("duplicates"),
+        execs().with_stdout(
+            "\
+package `log` is used in 2 versions
+  log v0.3.9
+    foo v0.1.0 ([..]) -> log v0.3.9
+      requirement `^0.3` excludes 0.4.6
+  log v0.4.6
+    foo v0.1.0 ([..]) -> a v0.1.0 -> log v0.4.6
+      requirement `^0.4` excludes 0.3.9
+    foo v0.1.0 ([..]) -> b v0.1.0 -> log v0.4.6
+      requirement `^0.4` excludes 0.3.9
+",
+        ),
+    );
+}
+
+#[test]
+fn report_duplicates_json() {
+    Package::new("log", "0.3.9").publish();
+    Package::new("log", "0.4.6").publish();
+    Package::new("a", "0.1.0").dep("log", "0.4").publish();
+    Package::new("b", "0.1.0").dep("log", "0.4").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            a = "0.1"
+            b = "0.1"
+            log = "0.3"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    assert_that(
+        p.cargo("duplicates --message-format json"),
+        execs().with_json(
+            r#"
+{
+    "reason": "duplicate-package",
+    "name": "log",
+    "versions": [
+        {
+            "package_id": "log 0.3.9 [..]",
+            "dependents": [
+                {
+                    "path": ["foo 0.1.0 [..]", "log 0.3.9 [..]"],
+                    "requirement": "^0.3",
+                    "excludes": ["log 0.4.6 [..]"]
+                }
+            ]
+        },
+        {
+            "package_id": "log 0.4.6 [..]",
+            "dependents": [
+                {
+                    "path": ["foo 0.1.0 [..]", "a 0.1.0 [..]", "log 0.4.6 [..]"],
+                    "requirement": "^0.4",
+                    "excludes": ["log 0.3.9 [..]"]
+                },
+                {
+                    "path": ["foo 0.1.0 [..]", "b 0.1.0 [..]", "log 0.4.6 [..]"],
+                    "requirement": "^0.4",
+                    "excludes": ["log 0.3.9 [..]"]
+                }
+            ]
+        }
+    ]
+}
+"#,
+        ),
+    );
+}
+
+#[test]
+fn deny_duplicates() {
+    Package::new("log", "0.3.9").publish();
+    Package::new("log", "0.4.6").publish();
+    Package::new("a", "0.1.0").dep("log", "0.4").publish();
+    Package::new("b", "0.1.0").dep("log", "0.4").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            a = "0.1"
+            b = "0.1"
+            log = "0.3"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    assert_that(
+        p.cargo("duplicates --deny-duplicates"),
+        execs()
+            .with_status(101)
+            .with_stdout_contains("package `log` is used in 2 versions")
+            .with_stderr_contains("error: found 1 package with more than one version"),
+    );
+}
+
+#[test]
+fn no_duplicates() {
+    Package::new("log", "0.4.6").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            log = "0.4"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    assert_that(
+        p.cargo("duplicates --deny-duplicates"),
+        execs().with_stdout(""),
+    );
+}
+
+#[test]
+fn requirement_accepting_both_versions() {
+    Package::new("log", "0.3.9").publish();
+    Package::new("log", "0.4.6").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            log = "0.3"
+            bar = { path = "bar" }
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "bar/Cargo.toml",
+            r#"
+            [package]
+            name = "bar"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            log = ">= 0.3"
+        "#,
+        )
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    // `bar` gets the newest `log`, although its requirement would allow it
+    // to share the older one with `foo`.
+    assert_that(
+        p.cargo("duplicates"),
+        execs().with_stdout_contains(
+            "      requirement `>=[..]0.3` also accepts the other versions",
+        ),
+    );
+}
@@ -0,0 +1,342 @@
+use support::{execs, project};
+use support::hamcrest::assert_that;
+
+#[test]
+fn build_dep_features_unified_by_default() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            common = { path = "common" }
+
+            [build-dependencies]
+            common = { path = "common", features = ["std"] }
+        "#,
+        )
+        .file(
+            "build.rs",
+            r#"
+                extern crate common;
+                fn main() { assert!(common::is_std()); }
+            "#,
+        )
+        .file(
+            "src/main.rs",
+            r#"
+                extern crate common;
+                fn main() { println!("std={}", common::is_std()); }
+            "#,
+        )
+        .file(
+            "common/Cargo.toml",
+            r#"
+                [package]
+                name = "common"
+                version = "0.1.0"
+                authors = []
+
+                [features]
+                std = []
+            "#,
+        )
+        .file(
+            "common/src/lib.rs",
+            r#"
+                pub fn is_std() -> bool { cfg!(feature = "std") }
+            "#,
+        )
+        .build();
+
+    assert_that(p.cargo("run"), execs().with_stdout("std=true"));
+}
+
+#[test]
+fn build_dep_features_separate() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+            resolver = "2"
+
+            [dependencies]
+            common = { path = "common" }
+
+            [build-dependencies]
+            common = { path = "common", features = ["std"] }
+        "#,
+        )
+        .file(
+            "build.rs",
+            r#"
+                extern crate common;
+                fn main() { assert!(common::is_std()); }
+            "#,
+        )
+        .file(
+            "src/main.rs",
+            r#"
+                extern crate common;
+                fn main() { println!("std={}", common::is_std()); }
+            "#,
+        )
+        .file(
+            "common/Cargo.toml",
+            r#"
+                [package]
+                name = "common"
+                version = "0.1.0"
+                authors = []
+
+                [features]
+                std = []
+            "#,
+        )
+        .file(
+            "common/src/lib.rs",
+            r#"
+                pub fn is_std() -> bool { cfg!(feature = "std") }
+            "#,
+        )
+        .build();
+
+    assert_that(
+        p.cargo("run"),
+        execs().with_stdout("std=false").with_stderr_unordered(
+            "\
+[COMPILING] common v0.1.0 ([..])
+[COMPILING] common v0.1.0 ([..])
+[COMPILING] foo v0.1.0 ([..])
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+[RUNNING] `target/debug/foo[EXE]`
+",
+        ),
+    );
+
+    // Both copies of `common` are fresh the second time around.
+    assert_that(
+        p.cargo("run"),
+        execs().with_stdout("std=false").with_stderr(
+            "\
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+[RUNNING] `target/debug/foo[EXE]`
+",
+        ),
+    );
+}
+
+#[test]
+fn proc_macro_features_separate() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["foo", "pm"]
+                resolver = "2"
+            "#,
+        )
+        .file(
+            "foo/Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                authors = []
+
+                [dependencies]
+                common = { path = "../common" }
+                pm = { path = "../pm" }
+            "#,
+        )
+        .file(
+            "foo/src/main.rs",
+            r#"
+                extern crate common;
+                #[macro_use]
+                extern crate pm;
+
+                #[derive(Std)]
+                struct S;
+
+                fn main() { println!("{} std={}", S::STD, common::is_std()); }
+            "#,
+        )
+        .file(
+            "pm/Cargo.toml",
+            r#"
+                [package]
+                name = "pm"
+                version = "0.1.0"
+                authors = []
+
+                [lib]
+                proc-macro = true
+
+                [dependencies]
+                common = { path = "../common", features = ["std"] }
+            "#,
+        )
+        .file(
+            "pm/src/lib.rs",
+            r#"
+                extern crate common;
+                extern crate proc_macro;
+                use proc_macro::TokenStream;
+
+                #[proc_macro_derive(Std)]
+                pub fn derive(_input: TokenStream) -> TokenStream {
+                    format!("impl S {{ const STD: bool = {}; }}", common::is_std())
+                        .parse()
+                        .unwrap()
+                }
+            "#,
+        )
+        .file(
+            "common/Cargo.toml",
+            r#"
+                [package]
+                name = "common"
+                version = "0.1.0"
+                authors = []
+
+                [features]
+                std = []
+            "#,
+        )
+        .file(
+            "common/src/lib.rs",
+            r#"
+                pub fn is_std() -> bool { cfg!(feature = "std") }
+            "#,
+        )
+        .build();
+
+    assert_that(p.cargo("run -p foo"), execs().with_stdout("true std=false"));
+}
+
+#[test]
+fn dev_dep_features_only_for_tests() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                authors = []
+                resolver = "2"
+
+                [dependencies]
+                common = { path = "common" }
+
+                [dev-dependencies]
+                common = { path = "common", features = ["std"] }
+            "#,
+        )
+        .file(
+            "src/main.rs",
+            r#"
+                extern crate common;
+                fn main() { println!("std={}", common::is_std()); }
+
+                #[test]
+                fn dev_dep_feature() { assert!(common::is_std()); }
+            "#,
+        )
+        .file(
+            "common/Cargo.toml",
+            r#"
+                [package]
+                name = "common"
+                version = "0.1.0"
+                authors = []
+
+                [features]
+                std = []
+            "#,
+        )
+        .file(
+            "common/src/lib.rs",
+            r#"
+                pub fn is_std() -> bool { cfg!(feature = "std") }
+            "#,
+        )
+        .build();
+
+    assert_that(p.cargo("run"), execs().with_stdout("std=false"));
+    assert_that(
+        p.cargo("test"),
+        execs().with_stdout_contains("test dev_dep_feature ... ok"),
+    );
+}
+
+#[test]
+fn invalid_resolver() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                authors = []
+                resolver = "3"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    assert_that(
+        p.cargo("build"),
+        execs().with_status(101).with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[..]`
+
+Caused by:
+  `resolver` setting `3` is not valid, valid options are \"1\" or \"2\"
+",
+        ),
+    );
+}
+
+#[test]
+fn resolver_in_package_and_workspace() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                authors = []
+                resolver = "2"
+
+                [workspace]
+                resolver = "2"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    assert_that(
+        p.cargo("build"),
+        execs().with_status(101).with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[..]`
+
+Caused by:
+  cannot specify `resolver` field in both `[workspace]` and `[package]`
+",
+        ),
+    );
+}
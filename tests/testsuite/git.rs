@@ -94,7 +94,7 @@ fn cargo_compile_forbird_git_httpsrepo_offline() {
         .file("src/main.rs", "")
         .build();
 
-    assert_that(p.cargo("build").masquerade_as_nightly_cargo().arg("-Zoffline"),
+    assert_that(p.cargo("build").arg("--offline"),
                 execs().with_status(101).
                     with_stderr("\
 error: failed to load source for a dependency on `dep1`
@@ -103,7 +103,7 @@ Caused by:
   Unable to update https://github.com/some_user/dep1.git
 
 Caused by:
-  can't checkout from 'https://github.com/some_user/dep1.git': you are in the offline mode (-Z offline)"));
+  can't checkout from 'https://github.com/some_user/dep1.git': you are in the offline mode (--offline)"));
 }
 
 #[test]
@@ -199,8 +199,7 @@ fn cargo_compile_offline_with_cached_git_dep() {
     assert_that(
         p
             .cargo("build")
-            .masquerade_as_nightly_cargo()
-            .arg("-Zoffline"),
+            .arg("--offline"),
         execs().with_stderr(format!(
             "\
 [COMPILING] dep1 v0.5.0 ({}#[..])
@@ -239,8 +238,7 @@ fn cargo_compile_offline_with_cached_git_dep() {
 
     let _out = p
         .cargo("build")
-        .masquerade_as_nightly_cargo()
-        .arg("-Zoffline")
+        .arg("--offline")
         .exec_with_output();
     assert_that(
         process(&p.bin("foo")),
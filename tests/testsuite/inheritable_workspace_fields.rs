@@ -0,0 +1,431 @@
+use std::fs::File;
+use std::io::prelude::*;
+
+use flate2::read::GzDecoder;
+use support::registry::Package;
+use support::{basic_manifest, execs, project, Project};
+use support::hamcrest::assert_that;
+use tar::Archive;
+
+fn packaged_manifest(p: &Project, krate: &str) -> String {
+    let f = File::open(&p.root().join("target/package").join(krate)).unwrap();
+    let mut rdr = GzDecoder::new(f);
+    let mut contents = Vec::new();
+    rdr.read_to_end(&mut contents).unwrap();
+    let mut ar = Archive::new(&contents[..]);
+    let mut entry = ar.entries()
+        .unwrap()
+        .map(|f| f.unwrap())
+        .find(|e| e.path().unwrap().ends_with("Cargo.toml"))
+        .unwrap();
+    let mut contents = String::new();
+    entry.read_to_string(&mut contents).unwrap();
+    contents
+}
+
+#[test]
+fn inherit_package_fields() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [workspace]
+            members = ["bar"]
+
+            [workspace.package]
+            version = "1.2.3"
+            authors = ["Rustaceans"]
+            description = "This is a crate"
+            license = "MIT"
+            keywords = ["cli"]
+        "#,
+        )
+        .file(
+            "bar/Cargo.toml",
+            r#"
+            [package]
+            name = "bar"
+            version = { workspace = true }
+            authors = { workspace = true }
+            description = { workspace = true }
+            license = { workspace = true }
+            keywords = { workspace = true }
+        "#,
+        )
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    assert_that(
+        p.cargo("build"),
+        execs().with_stderr(
+            "\
+[COMPILING] bar v1.2.3 ([..])
+[FINISHED] [..]
+",
+        ),
+    );
+
+    assert_that(
+        p.cargo("package --no-verify").cwd(p.root().join("bar")),
+        execs(),
+    );
+    assert_eq!(
+        packaged_manifest(&p, "bar-1.2.3.crate"),
+        r#"# THIS FILE IS AUTOMATICALLY GENERATED BY CARGO
+#
+# When uploading crates to the registry Cargo will automatically
+# "normalize" Cargo.toml files for maximal compatibility
+# with all versions of Cargo and also rewrite `path` dependencies
+# to registry (e.g. crates.io) dependencies
+#
+# If you believe there's an error in this file please file an
+# issue against the rust-lang/cargo repository. If you're
+# editing this file be aware that the upstream Cargo.toml
+# will likely look very different (and much more reasonable)
+
+[package]
+name = "bar"
+version = "1.2.3"
+authors = ["Rustaceans"]
+description = "This is a crate"
+keywords = ["cli"]
+license = "MIT"
+"#
+    );
+}
+
+#[test]
+fn inherit_readme_and_license_file() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [workspace]
+            members = ["bar"]
+
+            [workspace.package]
+            readme = "README.md"
+            license-file = "LICENSE"
+        "#,
+        )
+        .file("README.md", "# bar")
+        .file("LICENSE", "license text")
+        .file(
+            "bar/Cargo.toml",
+            r#"
+            [package]
+            name = "bar"
+            version = "0.1.0"
+            authors = []
+            description = "bar"
+            readme = { workspace = true }
+            license-file = { workspace = true }
+        "#,
+        )
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    assert_that(
+        p.cargo("package --list").cwd(p.root().join("bar")),
+        execs().with_stdout(
+            "\
+Cargo.toml
+LICENSE
+README.md
+src/lib.rs
+",
+        ),
+    );
+
+    assert_that(
+        p.cargo("package --no-verify").cwd(p.root().join("bar")),
+        execs(),
+    );
+    let manifest = packaged_manifest(&p, "bar-0.1.0.crate");
+    assert!(manifest.contains("readme = \"README.md\"\n"));
+    assert!(manifest.contains("license-file = \"LICENSE\"\n"));
+
+    let f = File::open(&p.root().join("target/package/bar-0.1.0.crate")).unwrap();
+    let mut rdr = GzDecoder::new(f);
+    let mut contents = Vec::new();
+    rdr.read_to_end(&mut contents).unwrap();
+    let mut ar = Archive::new(&contents[..]);
+    let mut files = Vec::new();
+    for entry in ar.entries().unwrap() {
+        let mut entry = entry.unwrap();
+        let name = entry.path().unwrap().into_owned();
+        let mut contents = String::new();
+        entry.read_to_string(&mut contents).unwrap();
+        files.push((name, contents));
+    }
+    assert!(files.contains(&("bar-0.1.0/README.md".into(), "# bar".to_string())));
+    assert!(files.contains(&("bar-0.1.0/LICENSE".into(), "license text".to_string())));
+}
+
+#[test]
+fn inherit_dependencies() {
+    Package::new("dep", "0.1.2").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [workspace]
+            members = ["bar"]
+
+            [workspace.dependencies]
+            dep = "0.1"
+            baz = { path = "baz", version = "0.1.0", features = ["a"] }
+        "#,
+        )
+        .file(
+            "bar/Cargo.toml",
+            r#"
+            [package]
+            name = "bar"
+            version = "0.2.0"
+            authors = []
+
+            [dependencies]
+            dep = { workspace = true }
+            baz = { workspace = true, features = ["b"], optional = true }
+        "#,
+        )
+        .file("bar/src/lib.rs", "")
+        .file(
+            "baz/Cargo.toml",
+            r#"
+            [package]
+            name = "baz"
+            version = "0.1.0"
+            authors = []
+
+            [features]
+            a = []
+            b = []
+        "#,
+        )
+        .file(
+            "baz/src/lib.rs",
+            r#"
+            #[cfg(not(all(feature = "a", feature = "b")))]
+            compile_error!("features were not inherited");
+        "#,
+        )
+        .build();
+
+    assert_that(
+        p.cargo("build --features baz").cwd(p.root().join("bar")),
+        execs(),
+    );
+
+    assert_that(
+        p.cargo("package --no-verify").cwd(p.root().join("bar")),
+        execs(),
+    );
+    let manifest = packaged_manifest(&p, "bar-0.2.0.crate");
+    assert!(manifest.contains(
+        r#"[dependencies.baz]
+version = "0.1.0"
+features = ["a", "b"]
+optional = true
+
+[dependencies.dep]
+version = "0.1"
+"#
+    ));
+    assert!(!manifest.contains("workspace"));
+}
+
+#[test]
+fn package_workspace_pointer() {
+    let p = project()
+        .file(
+            "ws/Cargo.toml",
+            r#"
+            [workspace]
+            members = ["../bar"]
+
+            [workspace.package]
+            version = "0.3.0"
+        "#,
+        )
+        .file(
+            "bar/Cargo.toml",
+            r#"
+            [package]
+            name = "bar"
+            version = { workspace = true }
+            authors = []
+            workspace = "../ws"
+        "#,
+        )
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    assert_that(
+        p.cargo("build").cwd(p.root().join("bar")),
+        execs().with_stderr(
+            "\
+[COMPILING] bar v0.3.0 ([..])
+[FINISHED] [..]
+",
+        ),
+    );
+}
+
+#[test]
+fn missing_workspace_value() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [workspace]
+            members = ["bar"]
+
+            [workspace.package]
+            version = "1.0.0"
+        "#,
+        )
+        .file(
+            "bar/Cargo.toml",
+            r#"
+            [package]
+            name = "bar"
+            version = { workspace = true }
+            authors = { workspace = true }
+
+            [dependencies]
+            dep = { workspace = true }
+        "#,
+        )
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    assert_that(
+        p.cargo("build"),
+        execs().with_status(101).with_stderr_contains(
+            "  `package.authors` was inherited from the workspace, but \
+             `workspace.package.authors` is not defined",
+        ),
+    );
+
+    p.change_file(
+        "bar/Cargo.toml",
+        r#"
+            [package]
+            name = "bar"
+            version = { workspace = true }
+            authors = []
+
+            [dependencies]
+            dep = { workspace = true }
+        "#,
+    );
+    assert_that(
+        p.cargo("build"),
+        execs().with_status(101).with_stderr_contains(
+            "  dependency `dep` was inherited from the workspace, but is not \
+             found in `workspace.dependencies`",
+        ),
+    );
+}
+
+#[test]
+fn dotted_workspace_key_rejected() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [workspace]
+            members = ["bar"]
+
+            [workspace.package]
+            version = "1.0.0"
+            edition = "2018"
+        "#,
+        )
+        .file(
+            "bar/Cargo.toml",
+            r#"
+            [package]
+            name = "bar"
+            version.workspace = true
+            edition.workspace = true
+            authors = []
+        "#,
+        )
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    assert_that(
+        p.cargo("build"),
+        execs().with_status(101).with_stderr_contains(
+            "dotted keys are not supported, `version.workspace = true` must be \
+             written as `version = { workspace = true }`",
+        ),
+    );
+}
+
+#[test]
+fn inherited_dependency_cannot_override_source() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [workspace]
+            members = ["bar"]
+
+            [workspace.dependencies]
+            baz = { path = "baz" }
+        "#,
+        )
+        .file(
+            "bar/Cargo.toml",
+            r#"
+            [package]
+            name = "bar"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            baz = { workspace = true, version = "0.1" }
+        "#,
+        )
+        .file("bar/src/lib.rs", "")
+        .file("baz/Cargo.toml", &basic_manifest("baz", "0.1.0"))
+        .file("baz/src/lib.rs", "")
+        .build();
+
+    assert_that(
+        p.cargo("build"),
+        execs().with_status(101).with_stderr_contains(
+            "  dependency `baz` is inherited from the workspace, so it cannot \
+             also specify `version`",
+        ),
+    );
+}
+
+#[test]
+fn no_workspace_root() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = { workspace = true }
+            authors = []
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    assert_that(
+        p.cargo("build"),
+        execs().with_status(101).with_stderr_contains(
+            "  the package at `[..]` inherits from a workspace, but no workspace \
+             root could be found",
+        ),
+    );
+}
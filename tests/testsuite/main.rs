@@ -23,7 +23,10 @@ extern crate winapi;
 #[macro_use]
 mod support;
 
+mod add;
+mod advisories;
 mod alt_registry;
+mod audit_policy;
 mod bad_config;
 mod bad_manifest_path;
 mod bench;
@@ -36,11 +39,15 @@ mod build_script;
 mod cargo_alias_config;
 mod cargo_features;
 mod cargo_command;
+mod cargo_config;
 mod cfg;
 mod check;
 mod clean;
 mod concurrent;
 mod config;
+mod config_cli;
+mod config_env;
+mod config_include;
 mod corrupt_git;
 mod cross_compile;
 mod cross_publish;
@@ -49,13 +56,17 @@ mod death;
 mod dep_info;
 mod directory;
 mod doc;
+mod duplicates;
+mod download;
 mod edition;
 mod features;
+mod features2;
 mod fetch;
 mod fix;
 mod freshness;
 mod generate_lockfile;
 mod git;
+mod inheritable_workspace_fields;
 mod init;
 mod install;
 mod jobserver;
@@ -66,6 +77,7 @@ mod metadata;
 mod net_config;
 mod new;
 mod out_dir;
+mod outdated;
 mod overrides;
 mod package;
 mod patch;
@@ -74,6 +86,7 @@ mod plugins;
 mod proc_macro;
 mod profiles;
 mod profile_config;
+mod profile_custom;
 mod profile_overrides;
 mod profile_targets;
 mod publish;
@@ -83,17 +96,23 @@ mod rename_deps;
 mod required_features;
 mod resolve;
 mod run;
+mod rust_version;
 mod rustc;
 mod rustc_info_cache;
 mod rustdocflags;
 mod rustdoc;
 mod rustflags;
 mod search;
+mod shared_cache;
 mod shell_quoting;
 mod small_fd_limits;
+mod sparse_registry;
 mod test;
+mod timings;
 mod tool_paths;
+mod tree;
 mod update;
+mod vendor;
 mod verify_project;
 mod version;
 mod warn_on_failure;
@@ -0,0 +1,195 @@
+use support::registry::Package;
+use support::{execs, project};
+use support::hamcrest::assert_that;
+
+#[test]
+fn direct_dependencies() {
+    Package::new("log", "0.1.0").publish();
+    Package::new("serde", "1.0.0").publish();
+    Package::new("a", "0.1.0").dep("serde", "1.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            a = "0.1"
+            log = "0.1"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    assert_that(p.cargo("generate-lockfile"), execs().with_status(0));
+    Package::new("log", "0.1.1").publish();
+    Package::new("log", "0.2.0").publish();
+    Package::new("serde", "2.0.0").publish();
+
+    assert_that(
+        p.cargo("outdated"),
+        execs().with_status(0).with_stdout(
+            "\
+Name  Locked  Compatible  Latest
+log   0.1.0   0.1.1       0.2.0
+",
+        ),
+    );
+}
+
+#[test]
+fn transitive_dependencies() {
+    Package::new("log", "0.1.0").publish();
+    Package::new("serde", "1.0.0").publish();
+    Package::new("a", "0.1.0").dep("serde", "1.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            a = "0.1"
+            log = "0.1"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    assert_that(p.cargo("generate-lockfile"), execs().with_status(0));
+    Package::new("log", "0.1.1").publish();
+    Package::new("log", "0.2.0").publish();
+    Package::new("serde", "2.0.0").publish();
+
+    assert_that(
+        p.cargo("outdated --transitive"),
+        execs().with_status(0).with_stdout(
+            "\
+Name   Locked  Compatible  Latest  Kind
+log    0.1.0   0.1.1       0.2.0   direct
+serde  1.0.0   1.0.0       2.0.0   transitive
+",
+        ),
+    );
+}
+
+#[test]
+fn json_output() {
+    Package::new("log", "0.1.0").publish();
+    Package::new("serde", "1.0.0").publish();
+    Package::new("a", "0.1.0").dep("serde", "1.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            a = "0.1"
+            log = "0.1"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    assert_that(p.cargo("generate-lockfile"), execs().with_status(0));
+    Package::new("log", "0.1.1").publish();
+    Package::new("log", "0.2.0").publish();
+    Package::new("serde", "2.0.0").publish();
+
+    assert_that(
+        p.cargo("outdated --transitive --message-format json"),
+        execs().with_status(0).with_json(
+            r#"
+{
+    "reason": "outdated-package",
+    "package_id": "log 0.1.0 [..]",
+    "direct": true,
+    "locked": "0.1.0",
+    "compatible": "0.1.1",
+    "latest": "0.2.0"
+}
+
+{
+    "reason": "outdated-package",
+    "package_id": "serde 1.0.0 [..]",
+    "direct": false,
+    "locked": "1.0.0",
+    "compatible": "1.0.0",
+    "latest": "2.0.0"
+}
+"#,
+        ),
+    );
+}
+
+#[test]
+fn up_to_date() {
+    Package::new("log", "0.1.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            log = "0.1"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    assert_that(
+        p.cargo("outdated"),
+        execs()
+            .with_status(0)
+            .with_stdout("")
+            .with_stderr_contains("[FINISHED] all dependencies are up to date"),
+    );
+}
+
+#[test]
+fn prereleases() {
+    Package::new("log", "0.1.0").publish();
+    Package::new("serde", "2.0.0-alpha.1").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            log = "0.1"
+            serde = "2.0.0-alpha.1"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    assert_that(p.cargo("generate-lockfile"), execs().with_status(0));
+    Package::new("log", "0.2.0").publish();
+    Package::new("log", "0.3.0-alpha.1").publish();
+    Package::new("serde", "2.0.0-alpha.2").publish();
+
+    assert_that(
+        p.cargo("outdated"),
+        execs().with_status(0).with_stdout(
+            "\
+Name   Locked         Compatible     Latest
+log    0.1.0          0.1.0          0.2.0
+serde  2.0.0-alpha.1  2.0.0-alpha.2  2.0.0-alpha.2
+",
+        ),
+    );
+}
@@ -0,0 +1,449 @@
+use support::{basic_lib_manifest, execs, project};
+use support::ChannelChanger;
+use support::hamcrest::{assert_that, existing_file};
+
+#[test]
+fn inherits_release() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [profile.release-lto]
+            inherits = "release"
+            lto = true
+        "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    assert_that(
+        p.cargo("build -v --profile release-lto"),
+        execs()
+            .with_stderr_contains("[..]-C opt-level=3[..]")
+            .with_stderr_contains("[..]-C lto[..]")
+            .with_stderr_contains("[..]--out-dir [..]/target/release-lto/deps[..]")
+            .with_stderr_contains("[FINISHED] release-lto [optimized] target(s) in [..]"),
+    );
+    assert_that(
+        &p.root()
+            .join("target/release-lto")
+            .join(format!("foo{}", ::std::env::consts::EXE_SUFFIX)),
+        existing_file(),
+    );
+}
+
+#[test]
+fn inherits_custom_profile() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [profile.fast-dev]
+            inherits = "dev"
+            opt-level = 1
+
+            [profile.fast-dev-nodebug]
+            inherits = "fast-dev"
+            debug = false
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    assert_that(
+        p.cargo("build -v --profile fast-dev-nodebug"),
+        execs().with_stderr(&format!(
+            "\
+[COMPILING] foo v0.0.1 ([..])
+[RUNNING] `rustc --crate-name foo src/lib.rs --crate-type lib \
+        --emit=dep-info,link \
+        -C opt-level=1 \
+        -C debug-assertions=on \
+        -C metadata=[..] \
+        --out-dir [..] \
+        -L dependency={dir}/target/fast-dev-nodebug/deps`
+[FINISHED] fast-dev-nodebug [optimized] target(s) in [..]
+",
+            dir = p.root().display(),
+        )),
+    );
+}
+
+#[test]
+fn test_and_check_with_profile() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [profile.ci]
+            inherits = "dev"
+            debug = false
+        "#,
+        )
+        .file("src/lib.rs", "#[test] fn t() {}")
+        .build();
+
+    assert_that(
+        p.cargo("test -v --profile ci"),
+        execs()
+            .with_stderr_contains("[..]--test[..]-L dependency=[..]/target/ci/deps[..]")
+            .with_stderr_contains("[FINISHED] ci [unoptimized] target(s) in [..]")
+            .with_stdout_contains("test t ... ok"),
+    );
+    assert_that(
+        p.cargo("check --profile ci"),
+        execs().with_stderr(
+            "\
+[CHECKING] foo v0.0.1 ([..])
+[FINISHED] ci [unoptimized] target(s) in [..]
+",
+        ),
+    );
+}
+
+#[test]
+fn build_script_profile_env() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [profile.small]
+            inherits = "release"
+            opt-level = "s"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "build.rs",
+            r#"
+            fn main() {
+                assert_eq!(std::env::var("PROFILE").unwrap(), "release");
+                assert_eq!(std::env::var("OPT_LEVEL").unwrap(), "s");
+            }
+        "#,
+        )
+        .build();
+
+    assert_that(p.cargo("build --profile small"), execs());
+}
+
+#[test]
+fn missing_inherits() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [profile.fast]
+            opt-level = 1
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    assert_that(
+        p.cargo("build"),
+        execs().with_status(101).with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[..]`
+
+Caused by:
+  profile `fast` is missing an `inherits` key, which is required for every \
+profile other than `dev`, `release`, `test`, `bench` and `doc`
+",
+        ),
+    );
+}
+
+#[test]
+fn inherits_errors() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [profile.a]
+            inherits = "b"
+
+            [profile.b]
+            inherits = "a"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    assert_that(
+        p.cargo("build"),
+        execs().with_status(101).with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[..]`
+
+Caused by:
+  profile inheritance loop detected: a -> b -> a
+",
+        ),
+    );
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [profile.a]
+            inherits = "missing"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    assert_that(
+        p.cargo("build"),
+        execs().with_status(101).with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[..]`
+
+Caused by:
+  profile `a` inherits from `missing`, but that profile is not defined
+",
+        ),
+    );
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [profile.release]
+            inherits = "dev"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    assert_that(
+        p.cargo("build"),
+        execs().with_status(101).with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[..]`
+
+Caused by:
+  `inherits` may only be specified for a custom profile, not `release`.
+",
+        ),
+    );
+}
+
+#[test]
+fn invalid_names() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [profile."a/b"]
+            inherits = "dev"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    assert_that(
+        p.cargo("build"),
+        execs().with_status(101).with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[..]`
+
+Caused by:
+  invalid profile name `a/b`, profile names may only contain letters, numbers, `-` and `_`
+",
+        ),
+    );
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [profile.package]
+            inherits = "dev"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    assert_that(
+        p.cargo("build"),
+        execs().with_status(101).with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[..]`
+
+Caused by:
+  profile name `package` is reserved, as it's a directory Cargo uses in `target`
+",
+        ),
+    );
+}
+
+#[test]
+fn select_errors() {
+    let p = project()
+        .file("Cargo.toml", &basic_lib_manifest("foo"))
+        .file("src/lib.rs", "")
+        .build();
+
+    assert_that(
+        p.cargo("build --profile nope"),
+        execs().with_status(101).with_stderr(
+            "\
+[ERROR] profile `nope` is not defined, custom profiles are defined with a \
+`[profile.nope]` table in the manifest
+",
+        ),
+    );
+    assert_that(
+        p.cargo("build --profile bench"),
+        execs().with_status(101).with_stderr(
+            "\
+[ERROR] the `bench` profile can't be selected with `--profile`, only `dev`, \
+`release` or a custom profile can
+",
+        ),
+    );
+    assert_that(
+        p.cargo("build --profile dev --release"),
+        execs().with_status(101).with_stderr(
+            "[ERROR] conflicting usage of --profile and --release\n",
+        ),
+    );
+    assert_that(
+        p.cargo("build -v --profile release"),
+        execs()
+            .with_stderr_contains("[..]-C opt-level=3[..]")
+            .with_stderr_contains("[FINISHED] release [optimized] target(s) in [..]"),
+    );
+}
+
+#[test]
+fn overrides() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["profile-overrides"]
+
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = { path = "bar" }
+
+            [profile.fast]
+            inherits = "dev"
+
+            [profile.fast.overrides.bar]
+            opt-level = 2
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", &basic_lib_manifest("bar"))
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    assert_that(
+        p.cargo("build -v --profile fast").masquerade_as_nightly_cargo(),
+        execs()
+            .with_stderr_contains("[RUNNING] `rustc --crate-name bar [..]-C opt-level=2[..]")
+            .with_stderr_contains("[FINISHED] fast [unoptimized + debuginfo] target(s) in [..]"),
+    );
+}
+
+#[test]
+fn config_profile() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [profile.fast]
+            inherits = "dev"
+            opt-level = 1
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config",
+            r#"
+            [profile.fast]
+            opt-level = 2
+
+            [profile.from-config]
+            inherits = "release"
+            debug = true
+        "#,
+        )
+        .build();
+
+    assert_that(
+        p.cargo("build -v --profile fast -Z config-profile").masquerade_as_nightly_cargo(),
+        execs()
+            .with_stderr_contains("[..]-C opt-level=2[..]")
+            .with_stderr_contains("[FINISHED] fast [optimized + debuginfo] target(s) in [..]"),
+    );
+    assert_that(
+        p.cargo("build -v --profile from-config -Z config-profile").masquerade_as_nightly_cargo(),
+        execs()
+            .with_stderr_contains("[..]-C opt-level=3[..]")
+            .with_stderr_contains("[..]--out-dir [..]/target/from-config/deps[..]")
+            .with_stderr_contains("[FINISHED] from-config [optimized + debuginfo] target(s) in [..]"),
+    );
+}
@@ -179,7 +179,7 @@ fn profile_override_dev_release_only() {
         execs().with_status(101).with_stderr_contains(
             "\
 Caused by:
-  Profile overrides may only be specified for `dev` or `release` profile, not `test`.
+  Profile overrides may only be specified for `dev`, `release` or custom profiles, not `test`.
 ",
         ),
     );
@@ -772,8 +772,7 @@ fn update_offline() {
         .build();
     assert_that(
         p.cargo("update")
-            .masquerade_as_nightly_cargo()
-            .arg("-Zoffline"),
+            .arg("--offline"),
         execs()
             .with_status(101)
             .with_stderr("error: you can't update in the offline mode[..]"),
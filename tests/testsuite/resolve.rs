@@ -45,6 +45,7 @@ fn resolve_with_config(
         &[],
         &mut registry,
         &HashSet::new(),
+        None,
         config,
         false,
     )?;
@@ -363,6 +364,7 @@ fn test_resolving_minimum_version_with_transitive_deps() {
             &None,
             false,
             false,
+            false,
             &None,
             &["minimal-versions".to_string()],
         )
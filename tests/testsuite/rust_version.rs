@@ -0,0 +1,210 @@
+use support::registry::Package;
+use support::{execs, project};
+use support::hamcrest::assert_that;
+
+#[test]
+fn rust_version_satisfied() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+            rust-version = "1.0"
+        "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    assert_that(p.cargo("build"), execs());
+}
+
+#[test]
+fn rust_version_too_high() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+            rust-version = "1.9876.0"
+        "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    assert_that(
+        p.cargo("build"),
+        execs().with_status(101).with_stderr(
+            "\
+error: package `foo v0.0.1 ([..])` cannot be built because it requires \
+rustc 1.9876.0 or newer, while the currently active rustc version is [..]
+",
+        ),
+    );
+}
+
+#[test]
+fn rust_version_bad_format() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+            rust-version = "1.30-beta"
+        "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    assert_that(
+        p.cargo("build"),
+        execs().with_status(101).with_stderr(
+            "\
+error: failed to parse manifest at `[..]`
+
+Caused by:
+  failed to parse the `rust-version` key
+
+Caused by:
+  `1.30-beta` is not a valid Rust version, expected a version like `1.30` or `1.30.1`
+",
+        ),
+    );
+}
+
+#[test]
+fn dependency_rust_version_too_high() {
+    Package::new("bar", "0.1.0")
+        .rust_version("1.9876")
+        .publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = "0.1"
+        "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    assert_that(
+        p.cargo("build"),
+        execs().with_status(101).with_stderr_contains(
+            "\
+error: package `bar v0.1.0[..]` cannot be built because it requires \
+rustc 1.9876.0 or newer, while the currently active rustc version is [..]",
+        ),
+    );
+}
+
+#[test]
+fn rust_version_inherited() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [workspace]
+            members = ["bar"]
+
+            [workspace.package]
+            rust-version = "1.9876"
+        "#,
+        )
+        .file(
+            "bar/Cargo.toml",
+            r#"
+            [package]
+            name = "bar"
+            version = "0.0.1"
+            authors = []
+            rust-version = { workspace = true }
+        "#,
+        )
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    assert_that(
+        p.cargo("build"),
+        execs().with_status(101).with_stderr(
+            "\
+error: package `bar v0.0.1 ([..])` cannot be built because it requires \
+rustc 1.9876.0 or newer, while the currently active rustc version is [..]
+",
+        ),
+    );
+}
+
+#[test]
+fn resolver_prefers_compatible_versions() {
+    Package::new("bar", "0.1.0").rust_version("1.0").publish();
+    Package::new("bar", "0.1.1").rust_version("1.9876").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+            rust-version = "1.20"
+
+            [dependencies]
+            bar = "0.1"
+        "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    // Newer versions are still picked by default.
+    assert_that(p.cargo("generate-lockfile"), execs());
+    assert!(p.read_lockfile().contains("\"bar 0.1.1 "));
+
+    assert_that(
+        p.cargo("generate-lockfile")
+            .env("CARGO_RESOLVER_INCOMPATIBLE_RUST_VERSIONS", "fallback"),
+        execs(),
+    );
+    assert!(p.read_lockfile().contains("\"bar 0.1.0 "));
+}
+
+#[test]
+fn resolver_falls_back_to_incompatible_versions() {
+    Package::new("bar", "0.1.0").rust_version("1.9876").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+            rust-version = "1.20"
+
+            [dependencies]
+            bar = "0.1"
+        "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    assert_that(
+        p.cargo("generate-lockfile")
+            .env("CARGO_RESOLVER_INCOMPATIBLE_RUST_VERSIONS", "fallback"),
+        execs(),
+    );
+    assert!(p.read_lockfile().contains("\"bar 0.1.0 "));
+}
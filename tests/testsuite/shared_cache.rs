@@ -0,0 +1,263 @@
+use std::fs;
+use std::path::Path;
+use std::process::Stdio;
+use std::thread;
+
+use support::registry::Package;
+use support::{execs, paths, project};
+use support::hamcrest::assert_that;
+
+#[test]
+fn registry_deps_are_shared() {
+    Package::new("dep", "0.1.0")
+        .file("src/lib.rs", "pub fn dep() {}")
+        .publish();
+    Package::new("top", "0.1.0")
+        .dep("dep", "0.1")
+        .file("src/lib.rs", "extern crate dep; pub fn top() { dep::dep() }")
+        .publish();
+    let cache = paths::root().join("shared-cache");
+
+    let a = project()
+        .at("a")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "a"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            top = "0.1"
+        "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+    assert_that(
+        a.cargo("build -v").env("CARGO_BUILD_SHARED_CACHE", &cache),
+        execs()
+            .with_stderr_contains("[RUNNING] `rustc --crate-name dep [..]")
+            .with_stderr_contains("[RUNNING] `rustc --crate-name top [..]"),
+    );
+    assert_eq!(fs::read_dir(&cache).unwrap().count(), 4);
+
+    let b = project()
+        .at("b")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "b"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            top = "0.1"
+        "#,
+        )
+        .file("src/main.rs", "extern crate top; fn main() { top::top() }")
+        .build();
+    assert_that(
+        b.cargo("build -v").env("CARGO_BUILD_SHARED_CACHE", &cache),
+        execs()
+            .with_stderr_does_not_contain("[RUNNING] `rustc --crate-name dep [..]")
+            .with_stderr_does_not_contain("[RUNNING] `rustc --crate-name top [..]")
+            .with_stderr_contains("[RUNNING] `rustc --crate-name b [..]"),
+    );
+
+    // Outputs restored from the cache are fresh afterwards.
+    assert_that(
+        b.cargo("build -v").env("CARGO_BUILD_SHARED_CACHE", &cache),
+        execs()
+            .with_stderr_contains("[FRESH] dep v0.1.0")
+            .with_stderr_contains("[FRESH] top v0.1.0"),
+    );
+}
+
+#[test]
+fn not_shared_by_default() {
+    Package::new("dep", "0.1.0").publish();
+    let cache = paths::root().join("shared-cache");
+
+    let a = project()
+        .at("a")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "a"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            dep = "0.1"
+        "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+    assert_that(
+        a.cargo("build").env("CARGO_BUILD_SHARED_CACHE", &cache),
+        execs(),
+    );
+    let entries = cache_entries(&cache);
+    assert_eq!(entries.len(), 2);
+
+    let b = project()
+        .at("b")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "b"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            dep = "0.1"
+        "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+    assert_that(
+        b.cargo("build -v"),
+        execs().with_stderr_contains("[RUNNING] `rustc --crate-name dep [..]"),
+    );
+    assert_eq!(cache_entries(&cache), entries);
+    assert!(!b.root().join("shared-cache").exists());
+    assert!(!paths::home().join(".cargo/shared-cache").exists());
+}
+
+#[test]
+fn concurrent_builds() {
+    Package::new("dep", "0.1.0")
+        .file("src/lib.rs", "pub fn dep() {}")
+        .publish();
+    let cache = paths::root().join("shared-cache");
+
+    let p = project()
+        .no_manifest()
+        .file(
+            "a/Cargo.toml",
+            r#"
+            [package]
+            name = "a"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            dep = "0.1"
+        "#,
+        )
+        .file("a/src/main.rs", "extern crate dep; fn main() { dep::dep() }")
+        .file(
+            "b/Cargo.toml",
+            r#"
+            [package]
+            name = "b"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            dep = "0.1"
+        "#,
+        )
+        .file("b/src/main.rs", "extern crate dep; fn main() { dep::dep() }")
+        .build();
+    // Fetch first, so the builds only contend for the shared cache.
+    assert_that(p.cargo("fetch").cwd(p.root().join("a")), execs());
+    assert_that(p.cargo("fetch").cwd(p.root().join("b")), execs());
+
+    let mut a = p.cargo("build")
+        .cwd(p.root().join("a"))
+        .env("CARGO_BUILD_SHARED_CACHE", &cache)
+        .build_command();
+    let mut b = p.cargo("build")
+        .cwd(p.root().join("b"))
+        .env("CARGO_BUILD_SHARED_CACHE", &cache)
+        .build_command();
+
+    a.stdout(Stdio::piped()).stderr(Stdio::piped());
+    b.stdout(Stdio::piped()).stderr(Stdio::piped());
+
+    let a = a.spawn().unwrap();
+    let b = b.spawn().unwrap();
+    let a = thread::spawn(move || a.wait_with_output().unwrap());
+    let b = b.wait_with_output().unwrap();
+    let a = a.join().unwrap();
+
+    assert_that(a, execs());
+    assert_that(b, execs());
+
+    // One entry and its lock, and no scratch directory left behind.
+    let entries = cache_entries(&cache);
+    assert_eq!(entries.len(), 2, "{:?}", entries);
+    assert!(entries.iter().all(|name| !name.ends_with(".tmp")));
+}
+
+fn cache_entries(cache: &Path) -> Vec<String> {
+    let mut names = fs::read_dir(cache)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect::<Vec<_>>();
+    names.sort();
+    names
+}
+
+#[test]
+fn build_scripts_are_not_shared() {
+    Package::new("script", "0.1.0")
+        .file("build.rs", "fn main() {}")
+        .file("src/lib.rs", "")
+        .publish();
+    Package::new("uses-script", "0.1.0")
+        .dep("script", "0.1")
+        .file("src/lib.rs", "")
+        .publish();
+    let cache = paths::root().join("shared-cache");
+
+    let a = project()
+        .at("a")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "a"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            uses-script = "0.1"
+        "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+    assert_that(
+        a.cargo("build").env("CARGO_BUILD_SHARED_CACHE", &cache),
+        execs(),
+    );
+    assert!(!cache.exists());
+
+    let b = project()
+        .at("b")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "b"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            uses-script = "0.1"
+        "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+    assert_that(
+        b.cargo("build -v").env("CARGO_BUILD_SHARED_CACHE", &cache),
+        execs()
+            .with_stderr_contains("[RUNNING] `rustc --crate-name script [..]")
+            .with_stderr_contains("[RUNNING] `rustc --crate-name uses_script [..]"),
+    );
+}
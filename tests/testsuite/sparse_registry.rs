@@ -0,0 +1,311 @@
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::prelude::*;
+use std::io::BufReader;
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use support::registry::{registry_path, Package};
+use support::{execs, project, Project};
+use support::hamcrest::assert_that;
+
+/// A minimal static file server standing in for a sparse registry, serving
+/// the test registry's index with `ETag`s and recording every request.
+struct IndexServer {
+    addr: String,
+    log: Arc<Mutex<Vec<String>>>,
+}
+
+impl IndexServer {
+    fn start() -> IndexServer {
+        IndexServer::serve(None)
+    }
+
+    /// A server answering every request with `status`, like a registry
+    /// having an outage.
+    fn failing(status: u32) -> IndexServer {
+        IndexServer::serve(Some(status))
+    }
+
+    fn serve(fail: Option<u32>) -> IndexServer {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let root = registry_path();
+        let thread_log = log.clone();
+        thread::spawn(move || {
+            for conn in listener.incoming() {
+                let conn = conn.unwrap();
+                let entry = respond(conn, &root, fail);
+                thread_log.lock().unwrap().push(entry);
+            }
+        });
+        IndexServer { addr, log }
+    }
+
+    fn url(&self) -> String {
+        format!("sparse+http://{}/", self.addr)
+    }
+
+    /// Takes the requests made so far, like `GET /3/b/bar 200`.
+    fn requests(&self) -> Vec<String> {
+        let mut log = self.log.lock().unwrap();
+        log.drain(..).collect()
+    }
+
+    fn configure(&self, p: &Project) {
+        p.change_file(
+            ".cargo/config",
+            &format!(
+                r#"
+                [source.crates-io]
+                replace-with = 'sparse-registry'
+
+                [source.sparse-registry]
+                registry = '{}'
+            "#,
+                self.url()
+            ),
+        );
+    }
+}
+
+fn respond(conn: TcpStream, root: &Path, fail: Option<u32>) -> String {
+    let mut reader = BufReader::new(conn.try_clone().unwrap());
+    let mut request = String::new();
+    reader.read_line(&mut request).unwrap();
+    let path = request.split_whitespace().nth(1).unwrap().to_string();
+    let mut if_none_match = None;
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        let line = line.trim();
+        if line.is_empty() {
+            break;
+        }
+        let mut parts = line.splitn(2, ':');
+        let name = parts.next().unwrap().trim().to_lowercase();
+        if name == "if-none-match" {
+            if_none_match = Some(parts.next().unwrap().trim().to_string());
+        }
+    }
+
+    let file = root.join(&path[1..]);
+    let (status, status_line, body) = match fs::read(&file) {
+        _ if fail.is_some() => {
+            let status = fail.unwrap();
+            (status, format!("{} Failed", status), Vec::new())
+        }
+        Ok(contents) => {
+            let mut hasher = DefaultHasher::new();
+            contents.hash(&mut hasher);
+            let etag = format!("\"{:x}\"", hasher.finish());
+            if if_none_match.as_ref() == Some(&etag) {
+                (304, format!("304 Not Modified\r\nETag: {}", etag), Vec::new())
+            } else {
+                (200, format!("200 OK\r\nETag: {}", etag), contents)
+            }
+        }
+        Err(_) => (404, "404 Not Found".to_string(), Vec::new()),
+    };
+    let mut conn = conn;
+    write!(
+        conn,
+        "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status_line,
+        body.len()
+    ).unwrap();
+    conn.write_all(&body).unwrap();
+    format!("GET {} {}", path, status)
+}
+
+#[test]
+fn simple() {
+    Package::new("bar", "0.0.1").publish();
+    let server = IndexServer::start();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = ">= 0.0.0"
+        "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+    server.configure(&p);
+
+    assert_that(
+        p.cargo("build"),
+        execs().with_stderr(&format!(
+            "\
+[UPDATING] registry `{reg}`
+[DOWNLOADING] bar v0.0.1 (registry `{reg}`)
+[COMPILING] bar v0.0.1
+[COMPILING] foo v0.0.1 ([..])
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]s
+",
+            reg = server.url()
+        )),
+    );
+
+    let requests = server.requests();
+    assert!(requests.contains(&"GET /3/b/bar 200".to_string()));
+    assert!(requests.contains(&"GET /config.json 200".to_string()));
+}
+
+#[test]
+fn revalidates_with_etag() {
+    Package::new("bar", "0.0.1").publish();
+    let server = IndexServer::start();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = ">= 0.0.0"
+        "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+    server.configure(&p);
+
+    assert_that(p.cargo("build"), execs());
+    server.requests();
+
+    // Nothing changed, so the server can tell us our copy is still good.
+    assert_that(p.cargo("update"), execs());
+    assert_eq!(server.requests(), vec!["GET /3/b/bar 304".to_string()]);
+
+    // With the lock file in place nothing needs to be fetched at all.
+    assert_that(p.cargo("build"), execs());
+    assert_eq!(server.requests(), Vec::<String>::new());
+
+    Package::new("bar", "0.0.2").publish();
+    assert_that(
+        p.cargo("update"),
+        execs().with_stderr(&format!(
+            "\
+[UPDATING] registry `{reg}`
+[UPDATING] bar v0.0.1 -> v0.0.2
+",
+            reg = server.url()
+        )),
+    );
+    assert_eq!(server.requests(), vec!["GET /3/b/bar 200".to_string()]);
+}
+
+#[test]
+fn missing_crate() {
+    Package::new("baz", "0.0.1").publish();
+    let server = IndexServer::start();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = ">= 0.0.0"
+        "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+    server.configure(&p);
+
+    assert_that(
+        p.cargo("build"),
+        execs().with_status(101).with_stderr_contains(
+            "[ERROR] no matching package named `bar` found",
+        ),
+    );
+    assert!(server.requests().contains(&"GET /3/b/bar 404".to_string()));
+}
+
+#[test]
+fn missing_crate_tries_few_spellings() {
+    Package::new("baz", "0.0.1").publish();
+    let server = IndexServer::start();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            a-b_c-d = ">= 0.0.0"
+        "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+    server.configure(&p);
+
+    assert_that(
+        p.cargo("build"),
+        execs().with_status(101).with_stderr_contains(
+            "[ERROR] no matching package named `a-b_c-d` found",
+        ),
+    );
+    let requests = server.requests();
+    let lookups = requests
+        .iter()
+        .filter(|r| r.ends_with(" 404"))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        lookups,
+        vec![
+            "GET /a-/b_/a-b_c-d 404",
+            "GET /a-/b-/a-b-c-d 404",
+            "GET /a_/b_/a_b_c_d 404",
+        ]
+    );
+}
+
+#[test]
+fn server_error_is_reported() {
+    Package::new("bar", "0.0.1").publish();
+    let server = IndexServer::failing(500);
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = ">= 0.0.0"
+        "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+    server.configure(&p);
+
+    assert_that(
+        p.cargo("build"),
+        execs()
+            .with_status(101)
+            .with_stderr_contains("[..]failed to get 200 response from `[..]/3/b/bar`, got 500")
+            .with_stderr_does_not_contain("[..]no matching package[..]"),
+    );
+}
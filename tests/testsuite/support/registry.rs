@@ -59,6 +59,8 @@ pub struct Package {
     features: HashMap<String, Vec<String>>,
     local: bool,
     alternative: bool,
+    rust_version: Option<String>,
+    license: Option<String>,
 }
 
 struct Dependency {
@@ -140,6 +142,8 @@ impl Package {
             features: HashMap::new(),
             local: false,
             alternative: false,
+            rust_version: None,
+            license: None,
         }
     }
 
@@ -204,6 +208,16 @@ impl Package {
         self
     }
 
+    pub fn rust_version(&mut self, rust_version: &str) -> &mut Package {
+        self.rust_version = Some(rust_version.to_string());
+        self
+    }
+
+    pub fn license(&mut self, license: &str) -> &mut Package {
+        self.license = Some(license.to_string());
+        self
+    }
+
     pub fn yanked(&mut self, yanked: bool) -> &mut Package {
         self.yanked = yanked;
         self
@@ -233,14 +247,18 @@ impl Package {
             t!(t!(File::open(&self.archive_dst())).read_to_end(&mut c));
             cksum(&c)
         };
-        let line = json!({
+        let mut line = json!({
             "name": self.name,
             "vers": self.vers,
             "deps": deps,
             "cksum": cksum,
             "features": self.features,
             "yanked": self.yanked,
-        }).to_string();
+        });
+        if let Some(ref rust_version) = self.rust_version {
+            line["rust_version"] = json!(rust_version);
+        }
+        let line = line.to_string();
 
         let file = match self.name.len() {
             1 => format!("1/{}", self.name),
@@ -302,6 +320,12 @@ impl Package {
         "#,
             self.name, self.vers
         );
+        if let Some(ref rust_version) = self.rust_version {
+            manifest.push_str(&format!("rust-version = \"{}\"\n", rust_version));
+        }
+        if let Some(ref license) = self.license {
+            manifest.push_str(&format!("license = \"{}\"\n", license));
+        }
         for dep in self.deps.iter() {
             let target = match dep.target {
                 None => String::new(),
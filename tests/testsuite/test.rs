@@ -7,6 +7,7 @@ use cargo::util::process;
 use support::paths::CargoPathExt;
 use support::registry::Package;
 use support::{basic_manifest, basic_bin_manifest, basic_lib_manifest, cargo_exe, execs, project};
+use support::Project;
 use support::{is_nightly, rustc_host, sleep_ms};
 use support::hamcrest::{assert_that, existing_file, is_not};
 
@@ -3430,7 +3431,7 @@ fn json_artifact_includes_test_flag() {
         .build();
 
     assert_that(
-        p.cargo("test -v --message-format=json"),
+        p.cargo("test --no-run -v --message-format=json"),
         execs().with_json(
             r#"
     {
@@ -3549,3 +3550,284 @@ fn doctest_skip_staticlib() {
         ),
     )
 }
+
+#[test]
+fn json_test_results() {
+    let p = project()
+        .file("Cargo.toml", &basic_lib_manifest("foo"))
+        .file(
+            "src/lib.rs",
+            r#"
+            /// ```
+            /// foo::add(1, 2);
+            /// ```
+            pub fn add(a: i32, b: i32) -> i32 { a + b }
+
+            #[test]
+            fn passes() { assert_eq!(add(1, 2), 3); }
+
+            #[test]
+            #[ignore]
+            fn skipped() {}
+            "#,
+        )
+        .build();
+
+    assert_that(
+        p.cargo("test --message-format json"),
+        execs()
+            .with_stdout_contains(
+                r#"{"binary":{"executable":"[..]foo-[..]","package_id":"foo 0.5.0 [..]","target_kind":["lib"],"target_name":"foo"},"reason":"test-binary-started"}"#,
+            )
+            .with_stdout_contains(
+                r#"{"bench":null,"binary":{[..]},"name":"passes","outcome":"ok","reason":"test-result"}"#,
+            )
+            .with_stdout_contains(
+                r#"{"bench":null,"binary":{[..]},"name":"skipped","outcome":"ignored","reason":"test-result"}"#,
+            )
+            .with_stdout_contains(
+                r#"{"binary":{"executable":"[..]"[..]},"duration":[..],"exit_code":0,"reason":"test-binary-finished","success":true,"summary":{"failed":0,"filtered_out":0,"ignored":1,"measured":0,"passed":1}}"#,
+            )
+            .with_stdout_contains(
+                r#"{"binary":{"executable":null,[..]},"reason":"test-binary-started"}"#,
+            )
+            .with_stdout_contains(
+                r#"{"bench":null,"binary":{"executable":null,[..]},"name":"src/lib.rs - add (line [..])","outcome":"ok","reason":"test-result"}"#,
+            )
+            .with_stdout_does_not_contain("running [..]")
+            .with_stderr_contains("test passes ... ok"),
+    );
+}
+
+#[test]
+fn json_test_failure() {
+    let p = project()
+        .file("Cargo.toml", &basic_lib_manifest("foo"))
+        .file(
+            "src/lib.rs",
+            r#"
+            #[test]
+            fn fails() { panic!("nope"); }
+            "#,
+        )
+        .build();
+
+    assert_that(
+        p.cargo("test --message-format json"),
+        execs()
+            .with_status(101)
+            .with_stdout_contains(
+                r#"{"bench":null,"binary":{[..]},"name":"fails","outcome":"failed","reason":"test-result"}"#,
+            )
+            .with_stdout_contains(
+                r#"{"binary":{[..]},"duration":[..],"exit_code":101,"reason":"test-binary-finished","success":false,"summary":{"failed":1,"filtered_out":0,"ignored":0,"measured":0,"passed":0}}"#,
+            )
+            .with_stderr_contains("[..]nope[..]"),
+    );
+}
+
+/// The names of the test binaries `cargo test` ran, in order.
+fn tests_run(p: &Project, partition: &str) -> Vec<String> {
+    let output = p
+        .cargo("test --partition")
+        .arg(partition)
+        .exec_with_output()
+        .unwrap();
+    let stderr = str::from_utf8(&output.stderr).unwrap();
+    stderr
+        .lines()
+        .filter_map(|line| line.trim().splitn(2, "target/debug/deps/").nth(1))
+        .map(|exe| exe.splitn(2, '-').next().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn partition_by_count() {
+    let mut p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [lib]
+            test = false
+            doctest = false
+            "#,
+        )
+        .file("src/lib.rs", "");
+    for name in &["t1", "t2", "t3", "t4"] {
+        p = p.file(&format!("tests/{}.rs", name), "#[test] fn t() {}");
+    }
+    let p = p.build();
+
+    assert_that(
+        p.cargo("test --partition count:1/2"),
+        execs()
+            .with_stderr_contains("[RUNNING] target/debug/deps/t1-[..][EXE]")
+            .with_stderr_contains("[RUNNING] target/debug/deps/t3-[..][EXE]")
+            .with_stderr_does_not_contain("[RUNNING] target/debug/deps/t2-[..]")
+            .with_stderr_does_not_contain("[RUNNING] target/debug/deps/t4-[..]"),
+    );
+    assert_eq!(tests_run(&p, "count:2/2"), vec!["t2", "t4"]);
+}
+
+#[test]
+fn partition_by_hash() {
+    let mut p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [lib]
+            test = false
+            doctest = false
+            "#,
+        )
+        .file("src/lib.rs", "");
+    for name in &["t1", "t2", "t3", "t4"] {
+        p = p.file(&format!("tests/{}.rs", name), "#[test] fn t() {}");
+    }
+    let p = p.build();
+
+    let first = tests_run(&p, "hash:1/2");
+    let second = tests_run(&p, "hash:2/2");
+    let mut all = first.iter().chain(second.iter()).cloned().collect::<Vec<_>>();
+    all.sort();
+    assert_eq!(all, vec!["t1", "t2", "t3", "t4"]);
+
+    // The split is the same every time.
+    assert_eq!(tests_run(&p, "hash:1/2"), first);
+    assert_eq!(tests_run(&p, "hash:2/2"), second);
+}
+
+#[test]
+fn partition_invalid() {
+    let mut p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [lib]
+            test = false
+            doctest = false
+            "#,
+        )
+        .file("src/lib.rs", "");
+    for name in &["t1", "t2", "t3", "t4"] {
+        p = p.file(&format!("tests/{}.rs", name), "#[test] fn t() {}");
+    }
+    let p = p.build();
+
+    for partition in &["count:0/2", "count:3/2", "random:1/2", "hash:1"] {
+        assert_that(
+            p.cargo("test --partition").arg(partition),
+            execs().with_status(101).with_stderr(&format!(
+                "\
+[ERROR] invalid partition `{}`, expected `count:M/N` or `hash:M/N` where M is \
+between 1 and N
+",
+                partition
+            )),
+        );
+    }
+}
+
+#[test]
+fn test_jobs_runs_every_binary() {
+    let mut p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [lib]
+            test = false
+            doctest = false
+            "#,
+        )
+        .file("src/lib.rs", "");
+    for name in &["t1", "t2", "t3", "t4"] {
+        p = p.file(&format!("tests/{}.rs", name), "#[test] fn t() {}");
+    }
+    let p = p.build();
+
+    // Each binary's output is printed in one piece.
+    assert_that(
+        p.cargo("test --test-jobs 3"),
+        execs().with_status(0).with_stdout_contains_n(
+            "\
+running 1 test
+test t ... ok
+
+test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out
+",
+            4,
+        ),
+    );
+}
+
+#[test]
+fn test_jobs_no_fail_fast() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file("tests/t1.rs", "#[test] fn t() { panic!() }")
+        .file("tests/t2.rs", "#[test] fn t() {}")
+        .file("tests/t3.rs", "#[test] fn t() {}")
+        .build();
+
+    assert_that(
+        p.cargo("test --test-jobs 2 --no-fail-fast --test t1 --test t2 --test t3"),
+        execs()
+            .with_status(101)
+            .with_stderr_contains("[RUNNING] target/debug/deps/t1-[..][EXE]")
+            .with_stderr_contains("[RUNNING] target/debug/deps/t2-[..][EXE]")
+            .with_stderr_contains("[RUNNING] target/debug/deps/t3-[..][EXE]")
+            .with_stderr_contains("[ERROR] test failed, to rerun pass '--test t1'")
+            .with_stdout_contains_n("test result: ok.[..]", 2)
+            .with_stdout_contains("test result: FAILED.[..]"),
+    );
+}
+
+#[test]
+fn test_jobs_zero() {
+    let mut p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [lib]
+            test = false
+            doctest = false
+            "#,
+        )
+        .file("src/lib.rs", "");
+    for name in &["t1", "t2", "t3", "t4"] {
+        p = p.file(&format!("tests/{}.rs", name), "#[test] fn t() {}");
+    }
+    let p = p.build();
+
+    assert_that(
+        p.cargo("test --test-jobs 0"),
+        execs()
+            .with_status(101)
+            .with_stderr("[ERROR] `--test-jobs` must be at least 1"),
+    );
+}
@@ -0,0 +1,109 @@
+use support::registry::Package;
+use support::{execs, project};
+use support::hamcrest::{assert_that, existing_file, is_not};
+
+#[test]
+fn timings_report() {
+    Package::new("dep", "0.1.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                authors = []
+
+                [dependencies]
+                dep = "0.1"
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    assert_that(
+        p.cargo("build --timings"),
+        execs().with_stderr(
+            "\
+[UPDATING] registry `[..]`
+[DOWNLOADING] dep v0.1.0 ([..])
+[COMPILING] dep v0.1.0
+[COMPILING] foo v0.1.0 ([..])
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+      Timing report saved to [..]/foo/target/cargo-timings/cargo-timing-[..].html
+",
+        ),
+    );
+
+    let dir = p.root().join("target/cargo-timings");
+    assert_that(&dir.join("cargo-timing.html"), existing_file());
+    assert_that(&dir.join("cargo-timing.json"), existing_file());
+    let html = p.read_file("target/cargo-timings/cargo-timing.html");
+    assert!(html.contains("dep v0.1.0"));
+    assert!(html.contains("foo v0.1.0 bin &quot;foo&quot;"));
+    let json = p.read_file("target/cargo-timings/cargo-timing.json");
+    assert!(json.contains(r#""critical_path":[0,1]"#));
+}
+
+#[test]
+fn timings_not_written_by_default() {
+    Package::new("dep", "0.1.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                authors = []
+
+                [dependencies]
+                dep = "0.1"
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    assert_that(p.cargo("build"), execs());
+    assert_that(
+        &p.root().join("target/cargo-timings/cargo-timing.html"),
+        is_not(existing_file()),
+    );
+}
+
+#[test]
+fn timings_machine_messages() {
+    Package::new("dep", "0.1.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                authors = []
+
+                [dependencies]
+                dep = "0.1"
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    assert_that(
+        p.cargo("build --timings --message-format json"),
+        execs()
+            .with_stdout_contains(
+                r#"{"duration":[..],"mode":"build","package_id":"dep 0.1.0 [..]","reason":"timing-info",[..]}"#,
+            )
+            .with_stdout_contains(
+                r#"{"duration":[..],"mode":"build","package_id":"foo 0.1.0 [..]","reason":"timing-info",[..]}"#,
+            ),
+    );
+
+    // Nothing is timed when everything is fresh.
+    assert_that(
+        p.cargo("build --timings --message-format json"),
+        execs().with_stdout_does_not_contain(r#"[..]"reason":"timing-info"[..]"#),
+    );
+}
@@ -0,0 +1,277 @@
+use support::registry::Package;
+use support::{basic_manifest, execs, project};
+use support::hamcrest::assert_that;
+
+fn make_simple_registry() {
+    Package::new("c", "1.0.0").publish();
+    Package::new("b", "1.0.0").dep("c", "1.0").publish();
+    Package::new("a", "1.0.0")
+        .dep("b", "1.0")
+        .dep("c", "1.0")
+        .publish();
+    Package::new("bdep", "1.0.0").dep("b", "1.0").publish();
+    Package::new("devdep", "1.0.0").publish();
+}
+
+#[test]
+fn simple() {
+    make_simple_registry();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            a = "1.0"
+
+            [build-dependencies]
+            bdep = "1.0"
+
+            [dev-dependencies]
+            devdep = "1.0"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .file("build.rs", "fn main() {}")
+        .build();
+
+    assert_that(
+        p.cargo("tree"),
+        execs().with_stdout(
+            "\
+foo v0.1.0 ([..])
+├── a v1.0.0
+│   ├── b v1.0.0
+│   │   └── c v1.0.0
+│   └── c v1.0.0 (*)
+[build-dependencies]
+└── bdep v1.0.0
+    └── b v1.0.0 (*)
+[dev-dependencies]
+└── devdep v1.0.0
+",
+        ),
+    );
+}
+
+#[test]
+fn edge_kinds() {
+    make_simple_registry();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            a = "1.0"
+
+            [build-dependencies]
+            bdep = "1.0"
+
+            [dev-dependencies]
+            devdep = "1.0"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .file("build.rs", "fn main() {}")
+        .build();
+
+    assert_that(
+        p.cargo("tree -e build,dev"),
+        execs().with_stdout(
+            "\
+foo v0.1.0 ([..])
+[build-dependencies]
+└── bdep v1.0.0
+[dev-dependencies]
+└── devdep v1.0.0
+",
+        ),
+    );
+
+    assert_that(
+        p.cargo("tree -e bogus"),
+        execs()
+            .with_status(101)
+            .with_stderr_contains("[ERROR] unknown edge kind `bogus`[..]"),
+    );
+}
+
+#[test]
+fn invert() {
+    make_simple_registry();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            a = "1.0"
+            b = "1.0"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    assert_that(
+        p.cargo("tree --invert c"),
+        execs().with_stdout(
+            "\
+c v1.0.0
+├── a v1.0.0
+│   └── foo v0.1.0 ([..])
+└── b v1.0.0
+    ├── a v1.0.0 (*)
+    └── foo v0.1.0 ([..]) (*)
+",
+        ),
+    );
+}
+
+#[test]
+fn duplicates() {
+    Package::new("log", "0.3.9").publish();
+    Package::new("log", "0.4.1").publish();
+    Package::new("a", "1.0.0").dep("log", "0.3").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            a = "1.0"
+            log = "0.4"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    assert_that(
+        p.cargo("tree --duplicates"),
+        execs().with_stdout(
+            "\
+log v0.3.9
+└── a v1.0.0
+    └── foo v0.1.0 ([..])
+
+log v0.4.1
+└── foo v0.1.0 ([..])
+",
+        ),
+    );
+
+    assert_that(
+        p.cargo("tree --duplicates --invert log"),
+        execs()
+            .with_status(101)
+            .with_stderr_contains(
+                "[ERROR] the `--invert` and `--duplicates` flags cannot be used together",
+            ),
+    );
+}
+
+#[test]
+fn target_filtering() {
+    Package::new("cfg_dep", "1.0.0").publish();
+    Package::new("nonexistent_dep", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [target.'cfg(all())'.dependencies]
+            cfg_dep = "1.0"
+
+            [target.nonexistent-triple.dependencies]
+            nonexistent_dep = "1.0"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    assert_that(
+        p.cargo("tree"),
+        execs().with_stdout(
+            "\
+foo v0.1.0 ([..])
+└── cfg_dep v1.0.0
+",
+        ),
+    );
+
+    assert_that(
+        p.cargo("tree --target all"),
+        execs().with_stdout(
+            "\
+foo v0.1.0 ([..])
+├── cfg_dep v1.0.0
+└── nonexistent_dep v1.0.0
+",
+        ),
+    );
+}
+
+#[test]
+fn workspace_roots() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [workspace]
+            members = ["a", "b"]
+        "#,
+        )
+        .file(
+            "a/Cargo.toml",
+            r#"
+            [package]
+            name = "a"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            b = { path = "../b" }
+        "#,
+        )
+        .file("a/src/lib.rs", "")
+        .file("b/Cargo.toml", &basic_manifest("b", "0.1.0"))
+        .file("b/src/lib.rs", "")
+        .build();
+
+    assert_that(
+        p.cargo("tree -p b"),
+        execs().with_stdout("b v0.1.0 ([..])\n"),
+    );
+
+    assert_that(
+        p.cargo("tree --all"),
+        execs().with_stdout(
+            "\
+a v0.1.0 ([..])
+└── b v0.1.0 ([..])
+
+b v0.1.0 ([..])
+",
+        ),
+    );
+}
@@ -405,3 +405,176 @@ fn update_precise() {
         ),
     );
 }
+
+#[test]
+fn dry_run_upgrade_and_downgrade() {
+    Package::new("log", "0.1.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                authors = []
+
+                [dependencies]
+                log = "0.1"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    assert_that(p.cargo("generate-lockfile"), execs().with_status(0));
+    Package::new("log", "0.1.1").publish();
+
+    assert_that(
+        p.cargo("update --dry-run"),
+        execs().with_status(0).with_stderr(
+            "\
+[UPDATING] `[..]` index
+   Upgrading log v0.1.0 -> v0.1.1
+[WARNING] not updating lockfile due to dry run
+",
+        ),
+    );
+    assert!(p.read_lockfile().contains("\"0.1.0\""));
+
+    assert_that(p.cargo("update"), execs().with_status(0));
+    assert_that(
+        p.cargo("update -p log --precise 0.1.0 --dry-run"),
+        execs().with_status(0).with_stderr(
+            "\
+[UPDATING] `[..]` index
+ Downgrading log v0.1.1 -> v0.1.0
+[WARNING] not updating lockfile due to dry run
+",
+        ),
+    );
+    assert!(p.read_lockfile().contains("\"0.1.1\""));
+}
+
+#[test]
+fn breaking() {
+    Package::new("log", "0.1.0").publish();
+    Package::new("serde", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                authors = []
+
+                [dependencies]
+                log = "0.1"
+                serde = { version = "1.0", default-features = false }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    assert_that(p.cargo("generate-lockfile"), execs().with_status(0));
+    Package::new("log", "0.2.0").publish();
+    Package::new("serde", "2.0.0").publish();
+    let manifest = p.read_file("Cargo.toml");
+
+    assert_that(
+        p.cargo("update --breaking --dry-run -p log"),
+        execs()
+            .with_status(0)
+            .with_stderr_contains("   Upgrading requirement of foo on log from `0.1` to `0.2.0`")
+            .with_stderr_contains("   Upgrading log v0.1.0 -> v0.2.0 (breaking)")
+            .with_stderr_does_not_contain("[..]serde[..]")
+            .with_stderr_contains("[WARNING] not updating lockfile due to dry run"),
+    );
+    assert_eq!(p.read_file("Cargo.toml"), manifest);
+    assert!(p.read_lockfile().contains("\"0.1.0\""));
+
+    assert_that(
+        p.cargo("update --breaking"),
+        execs()
+            .with_status(0)
+            .with_stderr_contains("   Upgrading requirement of foo on log from `0.1` to `0.2.0`")
+            .with_stderr_contains(
+                "   Upgrading requirement of foo on serde from `1.0` to `2.0.0`",
+            )
+            .with_stderr_contains("[UPDATING] log v0.1.0 -> v0.2.0")
+            .with_stderr_contains("[UPDATING] serde v1.0.0 -> v2.0.0"),
+    );
+    let manifest = p.read_file("Cargo.toml");
+    assert!(manifest.contains("log = \"0.2.0\""));
+    assert!(manifest.contains("serde = { version = \"2.0.0\", default-features = false }"));
+}
+
+#[test]
+fn breaking_skips_prereleases() {
+    Package::new("log", "0.1.0").publish();
+    Package::new("serde", "2.0.0-alpha.1").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                authors = []
+
+                [dependencies]
+                log = "0.1"
+                serde = "2.0.0-alpha.1"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    assert_that(p.cargo("generate-lockfile"), execs().with_status(0));
+    Package::new("log", "0.2.0").publish();
+    Package::new("log", "0.3.0-alpha.1").publish();
+    Package::new("serde", "3.0.0-beta.1").publish();
+
+    assert_that(
+        p.cargo("update --breaking"),
+        execs()
+            .with_status(0)
+            .with_stderr_contains("   Upgrading requirement of foo on log from `0.1` to `0.2.0`")
+            .with_stderr_contains(
+                "   Upgrading requirement of foo on serde from `2.0.0-alpha.1` to `3.0.0-beta.1`",
+            )
+            .with_stderr_contains("[UPDATING] log v0.1.0 -> v0.2.0"),
+    );
+    let manifest = p.read_file("Cargo.toml");
+    assert!(manifest.contains("log = \"0.2.0\""));
+    assert!(manifest.contains("serde = \"3.0.0-beta.1\""));
+}
+
+#[test]
+fn breaking_resolve_failure() {
+    Package::new("log", "0.1.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                authors = []
+
+                [dependencies]
+                log = "0.1"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    assert_that(p.cargo("generate-lockfile"), execs().with_status(0));
+    Package::new("log", "0.2.0").dep("missing", "1.0").publish();
+    let manifest = p.read_file("Cargo.toml");
+
+    assert_that(
+        p.cargo("update --breaking"),
+        execs()
+            .with_status(101)
+            .with_stderr_contains("   Upgrading requirement of foo on log from `0.1` to `0.2.0`")
+            .with_stderr_contains("[..]no matching package named `missing` found"),
+    );
+    assert_eq!(p.read_file("Cargo.toml"), manifest);
+    assert!(p.read_lockfile().contains("\"0.1.0\""));
+}
@@ -0,0 +1,333 @@
+use git2;
+use support::git;
+use support::registry::Package;
+use support::{basic_manifest, execs, project, Project};
+use support::hamcrest::{assert_that, existing_dir, existing_file, is_not};
+
+fn add_vendor_config(p: &Project) {
+    p.change_file(
+        ".cargo/config",
+        r#"
+            [source.crates-io]
+            replace-with = 'vendor'
+
+            [source.vendor]
+            directory = 'vendor'
+        "#,
+    );
+}
+
+#[test]
+fn vendor_simple() {
+    Package::new("log", "0.3.5").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            log = "0.3.5"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    assert_that(
+        p.cargo("vendor --respect-source-config"),
+        execs().with_stdout(
+            r#"[source.crates-io]
+replace-with = "vendored-sources"
+
+[source.vendored-sources]
+directory = "vendor"
+"#,
+        ),
+    );
+
+    let manifest = p.read_file("vendor/log/Cargo.toml");
+    assert!(manifest.contains("version = \"0.3.5\""));
+    assert_that(&p.root().join("vendor/log/.cargo-checksum.json"), existing_file());
+
+    add_vendor_config(&p);
+    assert_that(p.cargo("build"), execs());
+}
+
+#[test]
+fn two_versions() {
+    Package::new("bitflags", "0.7.0").publish();
+    Package::new("bitflags", "0.8.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            bitflags = "0.8.0"
+            bar = { path = "bar" }
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "bar/Cargo.toml",
+            r#"
+            [package]
+            name = "bar"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            bitflags = "0.7.0"
+        "#,
+        )
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    assert_that(p.cargo("vendor --respect-source-config"), execs());
+
+    let manifest = p.read_file("vendor/bitflags/Cargo.toml");
+    assert!(manifest.contains("version = \"0.8.0\""));
+    let manifest = p.read_file("vendor/bitflags-0.7.0/Cargo.toml");
+    assert!(manifest.contains("version = \"0.7.0\""));
+    assert_that(&p.root().join("vendor/bar"), is_not(existing_dir()));
+
+    add_vendor_config(&p);
+    assert_that(p.cargo("build"), execs());
+}
+
+#[test]
+fn versioned_dirs() {
+    Package::new("log", "0.3.5").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            log = "0.3.5"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    assert_that(
+        p.cargo("vendor --respect-source-config --versioned-dirs"),
+        execs(),
+    );
+
+    let manifest = p.read_file("vendor/log-0.3.5/Cargo.toml");
+    assert!(manifest.contains("version = \"0.3.5\""));
+}
+
+#[test]
+fn delete_old_crates() {
+    Package::new("bitflags", "0.7.0").publish();
+    Package::new("log", "0.3.5").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            bitflags = "=0.7.0"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    assert_that(p.cargo("vendor --respect-source-config"), execs());
+    p.read_file("vendor/bitflags/Cargo.toml");
+
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            log = "=0.3.5"
+        "#,
+    );
+
+    assert_that(p.cargo("vendor --respect-source-config --no-delete"), execs());
+    p.read_file("vendor/bitflags/Cargo.toml");
+    p.read_file("vendor/log/Cargo.toml");
+
+    assert_that(p.cargo("vendor --respect-source-config"), execs());
+    assert_that(
+        &p.root().join("vendor/bitflags/Cargo.toml"),
+        is_not(existing_file()),
+    );
+    p.read_file("vendor/log/Cargo.toml");
+}
+
+#[test]
+fn git_simple() {
+    let git = git::new("git", |p| {
+        p.file("Cargo.toml", &basic_manifest("a", "0.1.0"))
+            .file("src/lib.rs", "")
+    }).unwrap();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            a = {{ git = '{}' }}
+        "#,
+                git.url()
+            ),
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    assert_that(
+        p.cargo("vendor --respect-source-config"),
+        execs().with_stdout(&format!(
+            r#"[source."{url}"]
+git = "{url}"
+branch = "master"
+replace-with = "vendored-sources"
+
+[source.vendored-sources]
+directory = "vendor"
+"#,
+            url = git.url()
+        )),
+    );
+
+    let csum = p.read_file("vendor/a/.cargo-checksum.json");
+    assert!(csum.contains("\"package\":null"));
+}
+
+#[test]
+fn git_two_references() {
+    let git = git::new("git", |p| {
+        p.file("a/Cargo.toml", &basic_manifest("a", "0.1.0"))
+            .file("a/src/lib.rs", "")
+            .file("b/Cargo.toml", &basic_manifest("b", "0.1.0"))
+            .file("b/src/lib.rs", "")
+    }).unwrap();
+    let repo = git2::Repository::open(&git.root()).unwrap();
+    git::tag(&repo, "v1");
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            a = {{ git = '{0}' }}
+            b = {{ git = '{0}', tag = 'v1' }}
+        "#,
+                git.url()
+            ),
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    assert_that(
+        p.cargo("vendor --respect-source-config"),
+        execs().with_stdout(&format!(
+            r#"[source."{url}"]
+git = "{url}"
+branch = "master"
+replace-with = "vendored-sources"
+
+[source."{url}?tag=v1"]
+git = "{url}"
+tag = "v1"
+replace-with = "vendored-sources"
+
+[source.vendored-sources]
+directory = "vendor"
+"#,
+            url = git.url()
+        )),
+    );
+    assert_that(&p.root().join("vendor/a"), existing_dir());
+    assert_that(&p.root().join("vendor/b"), existing_dir());
+}
+
+#[test]
+fn duplicate_version_from_multiple_sources() {
+    Package::new("a", "0.1.0").publish();
+    let git = git::new("git", |p| {
+        p.file("Cargo.toml", &basic_manifest("a", "0.1.0"))
+            .file("src/lib.rs", "")
+    }).unwrap();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            a = {{ git = '{}' }}
+            bar = {{ path = "bar" }}
+        "#,
+                git.url()
+            ),
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "bar/Cargo.toml",
+            r#"
+            [package]
+            name = "bar"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            a = "0.1.0"
+        "#,
+        )
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    assert_that(
+        p.cargo("vendor --respect-source-config"),
+        execs()
+            .with_status(101)
+            .with_stderr_contains(
+                "[..]found duplicate version of package `a v0.1.0` vendored from two sources:",
+            ),
+    );
+}